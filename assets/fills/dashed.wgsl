@@ -0,0 +1,46 @@
+#define_import_path smud::dashed_fill
+
+#import smud
+#import smud::instance
+
+const TAU: f32 = 6.283185307179586;
+
+// Dashes along the contour of the sdf, set by `instance::dash` as
+// (dash length, gap length, phase, stroke width), see `SmudDash`.
+//
+// Dashes have round caps, so a dash length of 0 gives dots. The position along the contour is
+// `instance::contour` when the sdf sets it, otherwise the angle around the center of the shape.
+// The angle is measured on a circle filling the frame, so the dashes line up across the shape,
+// and are only as long as requested for shapes reaching the edge of their frame.
+fn fill(d: f32, color: vec4<f32>) -> vec4<f32> {
+    let dash = instance::dash;
+    let half_width = dash.w * 0.5;
+    let period = max(dash.x + dash.y, 1e-4);
+
+    var along: f32;
+    var perimeter: f32;
+    if instance::contour.y > 0. {
+        along = instance::contour.x;
+        perimeter = instance::contour.y;
+    } else {
+        let p = instance::pos;
+        perimeter = TAU * instance::frame;
+        along = (atan2(p.y, p.x) / TAU + 0.5) * perimeter;
+    }
+
+    // Round to a whole number of dashes, so there's no seam where the contour closes
+    let count = max(round(perimeter / period), 1.);
+    let dash_period = perimeter / count;
+    // Offset from the center of the nearest dash, dashes are centered on whole periods so the
+    // caps of the first and last dash aren't cut off where the contour wraps around, and open
+    // contours start and end with a dash
+    let s = (fract((along + dash.z) / dash_period + 0.5) - 0.5) * dash_period;
+
+    // Capsule around the dash, measured along and across the contour
+    let d_along = abs(s) - dash.x * 0.5;
+    let q = vec2<f32>(d_along, abs(d));
+    let d_dash = length(max(q, vec2<f32>(0.))) + min(max(q.x, q.y), 0.) - half_width;
+
+    let a = smud::sd_fill_alpha(d_dash);
+    return vec4<f32>(color.rgb, a * color.a);
+}
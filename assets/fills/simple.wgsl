@@ -3,6 +3,6 @@
 #import smud
 
 fn fill(d: f32, color: vec4<f32>) -> vec4<f32> {
-    let a = smud::sd_fill_alpha_fwidth(d);
+    let a = smud::sd_fill_alpha(d);
     return vec4<f32>(color.rgb, a * color.a);
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-#define_import_path smud::gallery::horseshoe
+#define_import_path smud::gallery::rounded_x
 
 #import smud
 
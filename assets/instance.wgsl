@@ -0,0 +1,27 @@
+#define_import_path smud::instance
+
+// Per-instance data for the shape currently being drawn.
+// These are set by the generated fragment shader before `sdf` and `fill` are called.
+
+// The half-size of the quad of the shape
+var<private> frame: f32;
+
+// Extra per-instance parameters, the meaning depends on the sdf
+var<private> params: vec4<f32>;
+
+// (length, gap, phase, width) of the dashes of `fills/dashed.wgsl`, see `SmudDash`
+var<private> dash: vec4<f32>;
+
+// The time of the shape in seconds, see `SmudTime`
+var<private> time: f32;
+
+// The position the sdf is evaluated at, for fills that depend on it
+var<private> pos: vec2<f32>;
+
+// Position along the contour of the sdf and the length of the contour, for dashed fills.
+// Sdfs that know their arc length set this, like lines, polylines and paths do along their
+// center. A length of 0 uses the angle around the center of the shape instead.
+var<private> contour: vec2<f32>;
+
+// Multiplied with the alpha of the fill, sdfs can set this to 0 to skip parts of their quad
+var<private> coverage: f32 = 1.;
@@ -0,0 +1,27 @@
+#define_import_path smud::line
+
+#import smud
+#import smud::instance
+
+// Sdf of a straight line along the x axis, centered on the origin.
+// The instance params are (half length, half width, cap, unused), where the cap matches the
+// order of `StrokeCap`.
+fn sdf(p: vec2<f32>) -> f32 {
+    let half_length = instance::params.x;
+    let half_width = instance::params.y;
+    instance::contour = vec2<f32>(clamp(p.x + half_length, 0., 2. * half_length), 2. * half_length);
+    switch u32(instance::params.z) {
+        // round
+        case 1u: {
+            return length(vec2<f32>(max(abs(p.x) - half_length, 0.), p.y)) - half_width;
+        }
+        // square
+        case 2u: {
+            return smud::sd_box(p, vec2<f32>(half_length + half_width, half_width));
+        }
+        // butt
+        default: {
+            return smud::sd_box(p, vec2<f32>(half_length, half_width));
+        }
+    }
+}
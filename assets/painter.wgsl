@@ -0,0 +1,23 @@
+#define_import_path smud::painter
+
+#import smud
+#import smud::instance
+
+// Sdf of the shapes drawn by `SmudPainter`, which picks the shape with the last param.
+// Circles: (radius, unused, unused, 0)
+// Rectangles: (half width, half height, corner radius, 1)
+// Lines: (half length, half width, unused, 2)
+fn sdf(p: vec2<f32>) -> f32 {
+    let params = instance::params;
+    switch u32(params.w) {
+        case 1u: {
+            return smud::sd_rounded_box(p, params.xy, vec4<f32>(params.z));
+        }
+        case 2u: {
+            return smud::sd_segment(p, vec2<f32>(-params.x, 0.), vec2<f32>(params.x, 0.)) - params.y;
+        }
+        default: {
+            return smud::sd_circle(p, params.x);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+#define_import_path smud::particles
+
+#import smud::shape_instance::{Instance, store}
+
+// Settings of one `SmudParticles` emitter for the current frame, see `EmitterUniform`
+struct Emitter {
+    transform: mat4x4<f32>,
+    color: vec4<f32>,
+    end_color: vec4<f32>,
+    params: vec4<f32>,
+    velocity: vec2<f32>,
+    gravity: vec2<f32>,
+    // Particles in the range [spawn_start, spawn_start + spawn_count) are respawned this frame
+    spawn_start: u32,
+    spawn_count: u32,
+    capacity: u32,
+    seed: u32,
+    delta: f32,
+    lifetime: f32,
+    spread: f32,
+    radius: f32,
+    frame: f32,
+    scale: f32,
+    end_scale: f32,
+    padding: f32,
+};
+
+struct Particle {
+    position: vec2<f32>,
+    velocity: vec2<f32>,
+    age: f32,
+    lifetime: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> emitter: Emitter;
+
+@group(0) @binding(1)
+var<storage, read_write> particles: array<Particle>;
+
+const TAU: f32 = 6.283185307179586;
+
+// pcg hash, see https://www.jcgt.org/published/0009/03/02/
+fn hash(x: u32) -> u32 {
+    let state = x * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn random(state: ptr<function, u32>) -> f32 {
+    *state = hash(*state);
+    return f32(*state) / 4294967295.;
+}
+
+@compute @workgroup_size(64)
+fn simulate(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if index >= emitter.capacity {
+        return;
+    }
+
+    var particle = particles[index];
+    if (index + emitter.capacity - emitter.spawn_start) % emitter.capacity < emitter.spawn_count {
+        var state = hash(index ^ hash(emitter.seed));
+        let angle = random(&state) * TAU;
+        let offset = vec2<f32>(cos(angle), sin(angle)) * sqrt(random(&state)) * emitter.radius;
+        let spread = (random(&state) - 0.5) * emitter.spread;
+        let c = cos(spread);
+        let s = sin(spread);
+        let velocity = vec2<f32>(
+            emitter.velocity.x * c - emitter.velocity.y * s,
+            emitter.velocity.x * s + emitter.velocity.y * c,
+        );
+        particle.position = (emitter.transform * vec4<f32>(offset, 0., 1.)).xy;
+        particle.velocity = (emitter.transform * vec4<f32>(velocity, 0., 0.)).xy;
+        particle.age = 0.;
+        particle.lifetime = emitter.lifetime;
+    } else {
+        particle.age += emitter.delta;
+        particle.velocity += emitter.gravity * emitter.delta;
+        particle.position += particle.velocity * emitter.delta;
+    }
+    particles[index] = particle;
+
+    // Dead particles get a scale of 0, so their quads don't cover any pixels
+    let alive = particle.age < particle.lifetime;
+    let t = clamp(particle.age / max(particle.lifetime, 1e-6), 0., 1.);
+    let color = mix(emitter.color, emitter.end_color, t);
+    let scale = select(0., emitter.scale * mix(1., emitter.end_scale, t), alive);
+
+    var instance: Instance;
+    instance.anchor = vec2<f32>(0.);
+    instance.color = color;
+    instance.dash = vec4<f32>(0.);
+    instance.frame = emitter.frame;
+    instance.params = emitter.params;
+    instance.position = vec3<f32>(particle.position, emitter.transform[3].z);
+    instance.rotation = vec2<f32>(1., 0.);
+    instance.scale = scale;
+    // The time of each particle is its age, so `smud::time()` animates it from its birth
+    instance.time = vec2<f32>(particle.age, 0.);
+    // Written to binding 2, in the layout of `ShapeVertex`
+    store(index, instance);
+}
@@ -0,0 +1,178 @@
+#define_import_path smud::path
+
+#import smud
+#import smud::instance
+
+@group(1) @binding(0)
+var<storage, read> points: array<vec2<f32>>;
+
+// Keep in sync with StrokeCap and StrokeJoin in path.rs
+const CAP_BUTT: i32 = 0;
+const CAP_ROUND: i32 = 1;
+const CAP_SQUARE: i32 = 2;
+const JOIN_MITER: i32 = 0;
+const JOIN_ROUND: i32 = 1;
+const JOIN_BEVEL: i32 = 2;
+
+// Path data layout in the point buffer, starting at the offset in params.x:
+// (width, miter limit), (cap, join), then 5 points per segment:
+// (previous segment, next segment), start, control, end, (start, end) along the sub-path
+// Lines have their control point equal to their start point,
+// and neighbors are -1 at the open ends of the path.
+struct Segment {
+    prev: i32,
+    next: i32,
+    a: vec2<f32>,
+    c: vec2<f32>,
+    b: vec2<f32>,
+    arc: vec2<f32>,
+};
+
+fn segment(offset: u32, i: i32) -> Segment {
+    let base = offset + 2u + u32(i) * 5u;
+    let neighbors = points[base];
+    return Segment(
+        i32(neighbors.x),
+        i32(neighbors.y),
+        points[base + 1u],
+        points[base + 2u],
+        points[base + 3u],
+        points[base + 4u],
+    );
+}
+
+fn is_line(s: Segment) -> bool {
+    return all(s.a == s.c);
+}
+
+fn start_tangent(s: Segment) -> vec2<f32> {
+    let t = select(s.c - s.a, s.b - s.a, is_line(s));
+    return normalize(t);
+}
+
+fn end_tangent(s: Segment) -> vec2<f32> {
+    let t = select(s.b - s.c, s.b - s.a, is_line(s) || all(s.b == s.c));
+    return normalize(t);
+}
+
+fn sd_curve(p: vec2<f32>, s: Segment) -> f32 {
+    if (is_line(s)) {
+        return smud::sd_segment(p, s.a, s.b);
+    }
+    return smud::sd_bezier(p, s.a, s.c, s.b);
+}
+
+// Replaces the round end of a stroke beyond `end` with a flat one
+fn flat_end(d: f32, p: vec2<f32>, end: vec2<f32>, dir: vec2<f32>, extension: f32, half_width: f32) -> f32 {
+    let rel = p - end;
+    let along = dot(rel, dir);
+    if (along <= 0.) {
+        return d;
+    }
+    let q = vec2<f32>(along - extension, abs(dot(rel, vec2<f32>(-dir.y, dir.x)))) - vec2<f32>(0., half_width);
+    return length(max(q, vec2<f32>(0.))) + min(max(q.x, q.y), 0.);
+}
+
+// The wedge filling the outside of a corner between two flat segment ends
+fn sd_join(p: vec2<f32>, corner: vec2<f32>, t1: vec2<f32>, t2: vec2<f32>, half_width: f32, join: i32, miter_limit: f32) -> f32 {
+    let turn = t1.x * t2.y - t1.y * t2.x;
+    if (abs(turn) < 1e-5) {
+        return 1e9;
+    }
+    // The outer side of the corner is opposite to the turning direction
+    let side = -sign(turn);
+    let n1 = side * vec2<f32>(-t1.y, t1.x);
+    let n2 = side * vec2<f32>(-t2.y, t2.x);
+    let a = corner + n1 * half_width;
+    let b = corner + n2 * half_width;
+    var d = smud::sd_triangle(p, corner, a, b);
+
+    let bisector = n1 + n2;
+    if (join == JOIN_MITER && length(bisector) > 1e-5) {
+        let m = normalize(bisector);
+        let miter_length = half_width / max(dot(m, n1), 1e-5);
+        if (miter_length <= miter_limit * half_width) {
+            d = min(d, smud::sd_triangle(p, a, corner + m * miter_length, b));
+        }
+    }
+    return d;
+}
+
+// Distance to the part of the stroke belonging to segment i, including the join at its end
+fn sd_piece(p: vec2<f32>, offset: u32, i: i32, half_width: f32, cap: i32, join: i32, miter_limit: f32) -> f32 {
+    let s = segment(offset, i);
+    var d = sd_curve(p, s) - half_width;
+    let cap_extension = select(0., half_width, cap == CAP_SQUARE);
+
+    let t0 = start_tangent(s);
+    if (s.prev < 0) {
+        if (cap != CAP_ROUND) {
+            d = flat_end(d, p, s.a, -t0, cap_extension, half_width);
+        }
+    } else if (join != JOIN_ROUND) {
+        d = flat_end(d, p, s.a, -t0, 0., half_width);
+    }
+
+    let t1 = end_tangent(s);
+    if (s.next < 0) {
+        if (cap != CAP_ROUND) {
+            d = flat_end(d, p, s.b, t1, cap_extension, half_width);
+        }
+    } else if (join != JOIN_ROUND) {
+        d = flat_end(d, p, s.b, t1, 0., half_width);
+        let t2 = start_tangent(segment(offset, s.next));
+        d = min(d, sd_join(p, s.b, t1, t2, half_width, join, miter_limit));
+    }
+
+    return d;
+}
+
+fn segment_center(s: Segment) -> vec2<f32> {
+    return (min(min(s.a, s.c), s.b) + max(max(s.a, s.c), s.b)) * 0.5;
+}
+
+// Whether piece j is closer than piece i, breaking ties by index so neighboring quads agree
+fn closer(d_j: f32, j: i32, d_i: f32, i: i32) -> bool {
+    return d_j < d_i || (d_j == d_i && j < i);
+}
+
+// Each instance draws one segment of the path. The params are
+// (path offset, segment count, segment index, sub-path length), and the quad is centered on the
+// segment.
+// The segment and its neighbors are evaluated, and only the pixels closest to this segment are
+// drawn, so there are no seams or overlaps where the quads of neighboring segments meet.
+fn sdf(p_in: vec2<f32>) -> f32 {
+    let offset = u32(instance::params.x);
+    let index = i32(instance::params.z);
+
+    let style = points[offset];
+    let half_width = style.x * 0.5;
+    let miter_limit = style.y;
+    let cap_join = points[offset + 1u];
+    let cap = i32(cap_join.x);
+    let join = i32(cap_join.y);
+
+    let s = segment(offset, index);
+    let p = p_in + segment_center(s);
+
+    // Curves are short, so projecting onto the chord is close enough for dashes
+    let chord = s.b - s.a;
+    let t = clamp(dot(p - s.a, chord) / max(dot(chord, chord), 1e-12), 0., 1.);
+    instance::contour = vec2<f32>(mix(s.arc.x, s.arc.y, t), instance::params.w);
+
+    let d = sd_piece(p, offset, index, half_width, cap, join, miter_limit);
+    var d_min = d;
+    // Neighbors wrap around in closed subpaths, and are -1 at open ends
+    for (var k = 0; k < 2; k++) {
+        let j = select(s.next, s.prev, k == 0);
+        if (j < 0 || j == index) {
+            continue;
+        }
+        let d_j = sd_piece(p, offset, j, half_width, cap, join, miter_limit);
+        d_min = min(d_min, d_j);
+        if (closer(d_j, j, d, index)) {
+            instance::coverage = 0.;
+        }
+    }
+    return d_min;
+}
@@ -0,0 +1,49 @@
+#define_import_path smud::polygon
+
+#import smud::instance
+
+@group(1) @binding(0)
+var<storage, read> points: array<vec2<f32>>;
+
+// Exact sdf of the polygon stored in the point buffer.
+// The instance params are (first point, point count, fill rule, ring count),
+// where a fill rule of 1 is even-odd and 0 is nonzero.
+// Polygons with several rings, like shapes with holes, have the end of each ring
+// in the x of one more point per ring after their points.
+fn sdf(p: vec2<f32>) -> f32 {
+    let offset = u32(instance::params.x);
+    let n = u32(instance::params.y);
+    let even_odd = instance::params.z > 0.5;
+    let rings = u32(instance::params.w);
+
+    var d = dot(p - points[offset], p - points[offset]);
+    var winding = 0;
+    var start = 0u;
+    for (var r = 0u; r < max(rings, 1u); r++) {
+        let end = select(n, u32(points[offset + n + r].x), rings > 0u);
+        var j = end - 1u;
+        for (var i = start; i < end; i++) {
+            let a = points[offset + j];
+            let b = points[offset + i];
+            let e = b - a;
+            let w = p - a;
+            let h = clamp(dot(w, e) / max(dot(e, e), 1e-12), 0., 1.);
+            let c = w - e * h;
+            d = min(d, dot(c, c));
+
+            let side = e.x * w.y - e.y * w.x;
+            if (a.y <= p.y) {
+                if (b.y > p.y && side > 0.) {
+                    winding += 1;
+                }
+            } else if (b.y <= p.y && side < 0.) {
+                winding -= 1;
+            }
+            j = i;
+        }
+        start = end;
+    }
+
+    let inside = select(winding != 0, winding % 2 != 0, even_odd);
+    return select(1., -1., inside) * sqrt(d);
+}
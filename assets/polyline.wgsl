@@ -0,0 +1,36 @@
+#define_import_path smud::polyline
+
+#import smud::instance
+
+@group(1) @binding(0)
+var<storage, read> points: array<vec2<f32>>;
+
+// Sdf of an open polyline with rounded ends and joins.
+// The instance params are (first point, point count, unused, width).
+fn sdf(p: vec2<f32>) -> f32 {
+    let offset = u32(instance::params.x);
+    let n = u32(instance::params.y);
+    let half_width = instance::params.w * 0.5;
+
+    var d = dot(p - points[offset], p - points[offset]);
+    // Length of the polyline up to the current point, and where the closest point is along it
+    var length_so_far = 0.;
+    var along = 0.;
+    for (var i = 1u; i < n; i++) {
+        let a = points[offset + i - 1u];
+        let b = points[offset + i];
+        let e = b - a;
+        let w = p - a;
+        let h = clamp(dot(w, e) / max(dot(e, e), 1e-12), 0., 1.);
+        let c = w - e * h;
+        let segment_length = length(e);
+        if dot(c, c) < d {
+            d = dot(c, c);
+            along = length_so_far + h * segment_length;
+        }
+        length_so_far += segment_length;
+    }
+    instance::contour = vec2<f32>(along, length_so_far);
+
+    return sqrt(d) - half_width;
+}
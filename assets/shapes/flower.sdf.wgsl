@@ -0,0 +1,3 @@
+// Just the body of `fn sdf(p: vec2<f32>) -> f32`, try editing it while the example is running
+let r = 60. + 12. * cos(5. * atan2(p.y, p.x) + smud::time());
+return length(p) - r;
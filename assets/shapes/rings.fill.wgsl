@@ -0,0 +1,2 @@
+// A single expression is enough, `d` and `color` are in scope
+vec4<f32>(color.rgb, color.a * smud::sd_fill_alpha(max(d, abs(fract(d / 10.) - 0.5) * 10. - 2.)))
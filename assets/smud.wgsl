@@ -1,4 +1,7 @@
 #define_import_path smud
+
+#import smud::instance
+
 // Most of these are ported versions of the ones on Inigo Quilez website, https://iquilezles.org
 
 fn sd_circle(p: vec2<f32>, r: f32) -> f32 {
@@ -587,7 +590,24 @@ fn sd_fill_alpha_dpd(distance: f32) -> f32 {
 
 // Dirt cheap, but ugly
 fn sd_fill_alpha_nearest(distance: f32) -> f32 {
-    return step(-distance, 0.);
+    return step(distance, 0.);
+}
+
+// Uses the anti-aliasing mode selected in `SmudSettings`, or hard edges for `SmudPixelArt`
+fn sd_fill_alpha(distance: f32) -> f32 {
+#ifdef SMUD_PIXEL_ART
+    return sd_fill_alpha_nearest(distance);
+#else ifdef SMUD_AA_DPD
+    return sd_fill_alpha_dpd(distance);
+#else ifdef SMUD_AA_NEAREST
+    return sd_fill_alpha_nearest(distance);
+#else ifdef SMUD_AA_NONE
+    return sd_fill_alpha_nearest(distance);
+#else ifdef SMUD_AA_SUPERSAMPLED
+    return sd_fill_alpha_nearest(distance);
+#else
+    return sd_fill_alpha_fwidth(distance);
+#endif
 }
 
 fn sd_fill_with_falloff_3_(d: f32, falloff_size: f32, falloff_color: vec4<f32>, fill_color: vec4<f32>) -> vec4<f32> {
@@ -660,4 +680,14 @@ fn op_smooth_intersect(d1: f32, d2: f32, k: f32) -> f32 {
 //     var p = p;
 //     p.x = abs(p.x);
 //     return sd_segment(p, vec2<f32>(0., 0.), vec2<f32>(w, -h));
-// }
\ No newline at end of file
+// }
+
+// The parameters of the shape being drawn, see `SmudParams`
+fn params() -> vec4<f32> {
+    return instance::params;
+}
+
+// The time of the shape being drawn in seconds, which can be offset, scaled and paused per shape
+fn time() -> f32 {
+    return instance::time;
+}
@@ -1,7 +1,6 @@
 #define_import_path smud::star_bevy
 
 #import smud
-#import smud::view_bindings::globals
 
 fn bevy_head(p: vec2<f32>) -> f32 {
     let skull = smud::sd_ellipse(p, 0.22, 0.20);
@@ -65,6 +64,6 @@ fn star(p: vec2<f32>) -> f32 {
 fn sdf(p: vec2<f32>) -> f32 {
     let b = bevy(p);
     let s = star(p);
-    return mix(b, s, sin(globals.time) * 0.5 + 0.5);
+    return mix(b, s, sin(smud::time()) * 0.5 + 0.5);
     // return mix(b, s, 1.0);
 }
\ No newline at end of file
@@ -0,0 +1,26 @@
+#define_import_path smud::text
+
+#import smud::instance
+
+// Keep in sync with MSDF_RANGE in msdf.rs
+const MSDF_RANGE: f32 = 4.;
+
+@group(1) @binding(0)
+var atlas_texture: texture_2d<f32>;
+@group(1) @binding(1)
+var atlas_sampler: sampler;
+
+fn median(r: f32, g: f32, b: f32) -> f32 {
+    return max(min(r, g), min(max(r, g), b));
+}
+
+// The glyph's atlas rect is passed in the instance params as (min_u, min_v, max_u, max_v)
+fn sdf(p: vec2<f32>) -> f32 {
+    let rect = instance::params;
+    let local_uv = p / instance::frame * vec2<f32>(0.5, -0.5) + 0.5;
+    let uv = mix(rect.xy, rect.zw, local_uv);
+    let s = textureSample(atlas_texture, atlas_sampler, uv).rgb;
+    let d_px = (0.5 - median(s.r, s.g, s.b)) * 2. * MSDF_RANGE;
+    let cell_px = (rect.z - rect.x) * f32(textureDimensions(atlas_texture).x);
+    return d_px * 2. * instance::frame / cell_px;
+}
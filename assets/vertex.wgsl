@@ -1,42 +1,57 @@
 #define_import_path smud::vertex
 
-struct View {
-    view_proj: mat4x4<f32>,
-    world_position: vec3<f32>,
-};
-@group(0) @binding(0)
-var<uniform> view: View;
-
-// as specified in `specialize()`
-struct Vertex {
-    @location(0) position: vec3<f32>,
-    @location(1) color: vec4<f32>,
-    @location(2) rotation: vec2<f32>,
-    @location(3) scale: f32,
-    @location(4) frame: f32,
-};
+#import smud::view_bindings::{view, globals}
+// Generated from `ShapeVertex`, see `instance_module()`
+#import smud::shape_instance::Instance
+#ifdef SMUD_STORAGE_INSTANCES
+#import smud::shape_instance::load
+#endif
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) color: vec4<f32>,
     @location(1) pos: vec2<f32>,
+    @location(2) params: vec4<f32>,
+    @location(3) frame: f32,
+    @location(4) time: f32,
+    @location(5) dash: vec4<f32>,
 };
 
 @vertex
 fn vertex(
-    vertex: Vertex,
+#ifdef SMUD_STORAGE_INSTANCES
+    @builtin(instance_index) instance_index: u32,
+#else
+    vertex: Instance,
+#endif
     @builtin(vertex_index) i: u32
 ) -> VertexOutput {
+#ifdef SMUD_STORAGE_INSTANCES
+    let vertex = load(instance_index);
+#endif
     var out: VertexOutput;
     let x = select(-1., 1., i % 2u == 0u);
     let y = select(-1., 1., (i / 2u) % 2u == 0u);
     let c = vertex.rotation.x;
     let s = vertex.rotation.y;
-    let rotated = vec2<f32>(x * c - y * s, x * s + y * c);
-    let pos = vertex.position + vec3<f32>(rotated * vertex.scale * vertex.frame, vertex.position.z);
+    let corner = vec2<f32>(x, y) + vertex.anchor;
+    let rotated = vec2<f32>(corner.x * c - corner.y * s, corner.x * s + corner.y * c);
+#ifdef SCREEN_SPACE
+    // Size of a pixel in world units, at the position of the shape
+    let clip = view.view_proj * vec4<f32>(vertex.position, 1.);
+    let scale = vertex.scale * 2. * clip.w / (view.projection[1][1] * view.viewport.w);
+#else
+    let scale = vertex.scale;
+#endif
+    let pos = vertex.position + vec3<f32>(rotated * scale * vertex.frame, vertex.position.z);
     // Project the world position of the mesh into screen position
     out.clip_position = view.view_proj * vec4<f32>(pos, 1.);
     out.color = vertex.color;
     out.pos = vec2<f32>(x, y) * vertex.frame;
+    out.params = vertex.params;
+    out.dash = vertex.dash;
+    out.frame = vertex.frame;
+    // (own time, weight of the global time), see `vertex_time`
+    out.time = vertex.time.x + vertex.time.y * globals.time;
     return out;
 }
\ No newline at end of file
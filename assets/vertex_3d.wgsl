@@ -0,0 +1,61 @@
+#define_import_path smud::vertex_3d
+
+#import smud::view_bindings::{view, globals}
+// Generated from `ShapeVertex3d`, see `instance_module()`
+#import smud::shape_instance_3d::Instance
+#ifdef SMUD_STORAGE_INSTANCES
+#import smud::shape_instance_3d::load
+#endif
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) pos: vec2<f32>,
+    @location(2) params: vec4<f32>,
+    @location(3) frame: f32,
+    @location(4) time: f32,
+    @location(5) dash: vec4<f32>,
+};
+
+@vertex
+fn vertex(
+#ifdef SMUD_STORAGE_INSTANCES
+    @builtin(instance_index) instance_index: u32,
+#else
+    vertex: Instance,
+#endif
+    @builtin(vertex_index) i: u32
+) -> VertexOutput {
+#ifdef SMUD_STORAGE_INSTANCES
+    let vertex = load(instance_index);
+#endif
+    var out: VertexOutput;
+    let x = select(-1., 1., i % 2u == 0u);
+    let y = select(-1., 1., (i / 2u) % 2u == 0u);
+#ifdef BILLBOARD
+    // The columns of the view matrix are the axes of the camera in world space
+    let axis_x = view.view[0].xyz * length(vertex.axis_x);
+    let axis_y = view.view[1].xyz * length(vertex.axis_y);
+#else
+    let axis_x = vertex.axis_x;
+    let axis_y = vertex.axis_y;
+#endif
+#ifdef SCREEN_SPACE
+    // Size of a pixel in world units, at the position of the shape
+    let clip = view.view_proj * vec4<f32>(vertex.position, 1.);
+    let scale = 2. * clip.w / (view.projection[1][1] * view.viewport.w);
+#else
+    let scale = 1.;
+#endif
+    let corner = vec2<f32>(x, y) + vertex.anchor;
+    let pos = vertex.position + (corner.x * axis_x + corner.y * axis_y) * vertex.frame * scale;
+    out.clip_position = view.view_proj * vec4<f32>(pos, 1.);
+    out.color = vertex.color;
+    out.pos = vec2<f32>(x, y) * vertex.frame;
+    out.params = vertex.params;
+    out.dash = vertex.dash;
+    out.frame = vertex.frame;
+    // (own time, weight of the global time), see `vertex_time`
+    out.time = vertex.time.x + vertex.time.y * globals.time;
+    return out;
+}
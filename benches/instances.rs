@@ -0,0 +1,216 @@
+//! Frame times of many shapes, with retained and rebuilt instance data, rendered headless.
+//!
+//! Run with `cargo bench`. Skipped when there's no gpu adapter, a software one like llvmpipe is
+//! enough, though the render times then dominate the frame times. The time spent writing the
+//! instance data is measured on its own as well.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    core_pipeline::core_2d::Transparent2d,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_phase::{PhaseItem, RenderPhase},
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+        Render, RenderApp, RenderSet,
+    },
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
+use bevy_smud::{prelude::*, SmudInstanceUpload, SmudSettings};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const SHAPES: usize = 10_000;
+
+/// Measurements of the last frame, recorded in the render world
+#[derive(Resource, Clone, Default)]
+struct Stats(Arc<Mutex<FrameStats>>);
+
+#[derive(Default)]
+struct FrameStats {
+    /// Number of shapes drawn
+    drawn: usize,
+    prepare_start: Option<Instant>,
+    /// Time spent in `RenderSet::PrepareBindGroups`, where the instance data is written
+    prepare: Duration,
+}
+
+fn start_prepare(stats: Res<Stats>) {
+    stats.0.lock().unwrap().prepare_start = Some(Instant::now());
+}
+
+fn end_prepare(stats: Res<Stats>) {
+    let mut stats = stats.0.lock().unwrap();
+    if let Some(start) = stats.prepare_start.take() {
+        stats.prepare = start.elapsed();
+    }
+}
+
+fn count_drawn(phases: Query<&RenderPhase<Transparent2d>>, stats: Res<Stats>) {
+    stats.0.lock().unwrap().drawn = phases
+        .iter()
+        .flat_map(|phase| &phase.items)
+        .map(|item| item.batch_range().len())
+        .sum();
+}
+
+/// Whether all shapes change every frame
+#[derive(Resource)]
+struct Animate(bool);
+
+fn animate(
+    mut shapes: Query<&mut Transform, With<SmudShape>>,
+    animate: Res<Animate>,
+    mut frame: Local<u32>,
+) {
+    if !animate.0 {
+        return;
+    }
+    *frame += 1;
+    let scale = 0.01 + 0.001 * (*frame % 2) as f32;
+    for mut transform in &mut shapes {
+        transform.scale = Vec3::splat(scale);
+    }
+}
+
+fn has_gpu() -> bool {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+    bevy::tasks::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+        .is_some()
+}
+
+/// An app drawing [`SHAPES`] shapes to a small image, once their shaders are loaded
+fn app(instance_upload: SmudInstanceUpload, animated: bool) -> (App, Stats) {
+    let stats = Stats::default();
+    let mut app = App::new();
+    app.add_plugins((
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            })
+            .disable::<WinitPlugin>(),
+        SmudPlugin,
+    ))
+    .insert_resource(SmudSettings {
+        instance_upload,
+        ..default()
+    })
+    .insert_resource(Animate(animated))
+    .add_systems(Update, animate);
+    // Normally done by `App::run`, creates the render pipelines
+    app.finish();
+    app.cleanup();
+    app.sub_app_mut(RenderApp)
+        .insert_resource(stats.clone())
+        .add_systems(
+            Render,
+            (
+                start_prepare
+                    .after(RenderSet::PrepareResourcesFlush)
+                    .before(RenderSet::PrepareBindGroups),
+                end_prepare
+                    .after(RenderSet::PrepareBindGroups)
+                    .before(RenderSet::PrepareFlush),
+                count_drawn
+                    .after(RenderSet::Render)
+                    .before(RenderSet::Cleanup),
+            ),
+        );
+
+    let world = &mut app.world;
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: 64,
+            height: 64,
+            ..default()
+        },
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    image.texture_descriptor.usage |= TextureUsages::RENDER_ATTACHMENT;
+    let image = world.resource_mut::<Assets<Image>>().add(image);
+    world.spawn(Camera2dBundle {
+        camera: Camera {
+            target: RenderTarget::Image(image),
+            ..default()
+        },
+        ..default()
+    });
+
+    let sdf = world
+        .resource_mut::<Assets<Shader>>()
+        .add_sdf_expr("smud::sd_circle(p, 40.)");
+    // A grid covering the image
+    let side = (SHAPES as f32).sqrt() as usize;
+    let spacing = 64. / side as f32;
+    world.spawn_batch((0..SHAPES).map(move |i| {
+        ShapeBundle {
+            shape: SmudShape {
+                color: Color::WHITE,
+                sdf: sdf.clone(),
+                frame: Frame::Quad(50.),
+                ..default()
+            },
+            transform: Transform::from_xyz(
+                (i % side) as f32 * spacing - 32.,
+                (i / side) as f32 * spacing - 32.,
+                0.,
+            )
+            .with_scale(Vec3::splat(0.01)),
+            ..default()
+        }
+    }));
+
+    // Shaders load in the background
+    for _ in 0..1000 {
+        app.update();
+        if stats.0.lock().unwrap().drawn == SHAPES {
+            return (app, stats);
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    panic!("the shapes were never drawn");
+}
+
+fn frame_times(c: &mut Criterion) {
+    if !has_gpu() {
+        eprintln!("no gpu adapter, skipping");
+        return;
+    }
+
+    for (name, instance_upload) in [
+        ("retained", SmudInstanceUpload::Retained),
+        ("rebuild", SmudInstanceUpload::Rebuild),
+    ] {
+        for (state, animated) in [("static", false), ("animated", true)] {
+            let (mut app, stats) = app(instance_upload, animated);
+            c.bench_function(&format!("frame/{state}/{name}"), |b| {
+                b.iter(|| app.update())
+            });
+            c.bench_function(&format!("prepare/{state}/{name}"), |b| {
+                b.iter_custom(|iters| {
+                    (0..iters)
+                        .map(|_| {
+                            app.update();
+                            stats.0.lock().unwrap().prepare
+                        })
+                        .sum()
+                })
+            });
+        }
+    }
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = frame_times
+}
+criterion_main!(benches);
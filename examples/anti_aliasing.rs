@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+use bevy_smud::{prelude::*, SmudAntiAliasing, SmudSettings};
+
+const MODES: [SmudAntiAliasing; 5] = [
+    SmudAntiAliasing::Fwidth,
+    SmudAntiAliasing::Dpd,
+    SmudAntiAliasing::Nearest,
+    SmudAntiAliasing::None,
+    SmudAntiAliasing::Supersampled,
+];
+
+fn main() {
+    App::new()
+        .insert_resource(Msaa::Off)
+        .add_plugins((DefaultPlugins, SmudPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, (cycle_modes, spin))
+        .run();
+}
+
+fn setup(mut commands: Commands, mut shaders: ResMut<Assets<Shader>>) {
+    let star = shaders.add_sdf_expr("smud::sd_star_5_(p, 10., 2.)");
+    // Small shapes scaled up, so the differences between the modes are easy to see
+    commands.spawn(ShapeBundle {
+        shape: SmudShape {
+            color: Color::rgb(0.9, 0.7, 0.2),
+            sdf: star,
+            fill: SIMPLE_FILL_HANDLE,
+            frame: Frame::Quad(25.),
+            ..default()
+        },
+        transform: Transform::from_scale(Vec3::splat(12.)),
+        ..default()
+    });
+
+    commands.spawn(Camera2dBundle::default());
+    info!("Press space to switch anti-aliasing mode");
+}
+
+fn cycle_modes(keys: Res<Input<KeyCode>>, mut settings: ResMut<SmudSettings>) {
+    if keys.just_pressed(KeyCode::Space) {
+        let index = MODES
+            .iter()
+            .position(|mode| *mode == settings.anti_aliasing)
+            .unwrap_or_default();
+        settings.anti_aliasing = MODES[(index + 1) % MODES.len()];
+        info!("Anti-aliasing: {:?}", settings.anti_aliasing);
+    }
+}
+
+fn spin(time: Res<Time>, mut shapes: Query<&mut Transform, With<SmudShape>>) {
+    for mut transform in &mut shapes {
+        transform.rotation = Quat::from_rotation_z(time.elapsed_seconds() * 0.2);
+    }
+}
@@ -0,0 +1,30 @@
+use bevy::prelude::*;
+use bevy_pancam::*;
+use bevy_smud::prelude::*;
+
+fn main() {
+    App::new()
+        // bevy_smud comes with anti-aliasing built into the standards fills
+        // which is more efficient than MSAA, and also works on Linux, wayland
+        .insert_resource(Msaa::Off)
+        .insert_resource(ClearColor(Color::rgb(0.7, 0.8, 0.7)))
+        .add_plugins((DefaultPlugins, SmudPlugin, PanCamPlugin))
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn(ShapeBundle {
+        shape: SmudShape {
+            color: Color::rgb(0.9, 0.5, 0.2),
+            // These files only contain the body of the function, and are hot reloaded
+            sdf: asset_server.load("shapes/flower.sdf.wgsl"),
+            fill: asset_server.load("shapes/rings.fill.wgsl"),
+            frame: Frame::Quad(80.),
+            ..default()
+        },
+        ..default()
+    });
+
+    commands.spawn((Camera2dBundle::default(), PanCam::default()));
+}
@@ -25,8 +25,17 @@ fn main() {
             PanCamPlugin,
             bevy_lospec::PalettePlugin,
         ))
+        .init_resource::<Animate>()
         .add_systems(OnEnter(GameState::Running), setup)
-        // .add_system_set(SystemSet::on_update(GameState::Running).with_system(update))
+        .add_systems(
+            Update,
+            (
+                toggle_animation,
+                toggle_instance_upload,
+                animate.run_if(|animate: Res<Animate>| animate.0),
+            )
+                .run_if(in_state(GameState::Running)),
+        )
         .run();
 }
 
@@ -46,6 +55,14 @@ struct AssetHandles {
 #[derive(Component)]
 struct Index(usize);
 
+/// Whether all shapes change every frame
+///
+/// Static shapes keep their instance data on the gpu, so comparing the frame times with and
+/// without animation shows the cost of uploading all of it again every frame. See
+/// `benches/instances.rs` for measurements without a window.
+#[derive(Resource, Default)]
+struct Animate(bool);
+
 fn setup(
     mut commands: Commands,
     assets: Res<AssetHandles>,
@@ -95,13 +112,31 @@ fn setup(
         }
     }
     commands.spawn((Camera2dBundle::default(), PanCam::default()));
+    info!("Press space to toggle animating all shapes, and R to toggle rebuilding all instances");
+}
+
+fn toggle_animation(keys: Res<Input<KeyCode>>, mut animate: ResMut<Animate>) {
+    if keys.just_pressed(KeyCode::Space) {
+        animate.0 = !animate.0;
+        info!("Animating all shapes: {}", animate.0);
+    }
 }
 
-// fn update(mut query: Query<(&mut Transform, &Index), With<SmudShape>>, time: Res<Time>) {
-//     let t = time.time_since_startup().as_secs_f64();
+fn toggle_instance_upload(keys: Res<Input<KeyCode>>, mut settings: ResMut<SmudSettings>) {
+    if keys.just_pressed(KeyCode::R) {
+        settings.instance_upload = match settings.instance_upload {
+            SmudInstanceUpload::Retained => SmudInstanceUpload::Rebuild,
+            SmudInstanceUpload::Rebuild => SmudInstanceUpload::Retained,
+        };
+        info!("Instance upload: {:?}", settings.instance_upload);
+    }
+}
+
+fn animate(mut query: Query<(&mut Transform, &Index), With<SmudShape>>, time: Res<Time>) {
+    let t = time.elapsed_seconds();
 
-//     for (mut tx, index) in query.iter_mut() {
-//         let s = f64::sin(t + (index.0 as f64) / 1.0) as f32;
-//         tx.scale = Vec3::splat(s);
-//     }
-// }
+    for (mut tx, index) in query.iter_mut() {
+        let s = f32::sin(t + index.0 as f32);
+        tx.scale = Vec3::splat(s);
+    }
+}
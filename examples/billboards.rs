@@ -0,0 +1,85 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use bevy_smud::prelude::*;
+
+fn main() {
+    App::new()
+        .insert_resource(Msaa::Off)
+        .insert_resource(ClearColor(Color::rgb(0.1, 0.12, 0.15)))
+        .add_plugins((DefaultPlugins, SmudPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, orbit_camera)
+        .run();
+}
+
+fn setup(mut commands: Commands, mut shaders: ResMut<Assets<Shader>>) {
+    // A ground plane, lying flat in the xz plane
+    commands.spawn((
+        ShapeBundle {
+            shape: SmudShape {
+                color: Color::rgb(0.3, 0.35, 0.3),
+                sdf: shaders
+                    .add_sdf_expr("smud::sd_rounded_box(p, vec2<f32>(500.), vec4<f32>(50.))"),
+                fill: SIMPLE_FILL_HANDLE,
+                frame: Frame::Quad(510.),
+                ..default()
+            },
+            transform: Transform::from_rotation(Quat::from_rotation_x(-FRAC_PI_2))
+                .with_scale(Vec3::splat(0.01)),
+            ..default()
+        },
+        SmudShape3d::Plane,
+    ));
+
+    // Selection rings on the ground, and markers floating above them
+    let ring = shaders.add_sdf_expr("abs(smud::sd_circle(p, 40.)) - 4.");
+    let marker = shaders.add_sdf_expr("smud::sd_heart(p / 40. - vec2<f32>(0., -0.5)) * 40.");
+    for i in 0..5 {
+        let position = Vec3::new(i as f32 * 1.5 - 3., 0., (i % 2) as f32 * 1.5 - 0.75);
+        let color = Color::hsl(i as f32 * 60., 0.8, 0.6);
+
+        commands.spawn((
+            ShapeBundle {
+                shape: SmudShape {
+                    color,
+                    sdf: ring.clone(),
+                    frame: Frame::Quad(50.),
+                    ..default()
+                },
+                transform: Transform::from_translation(position + Vec3::Y * 0.01)
+                    .with_rotation(Quat::from_rotation_x(-FRAC_PI_2))
+                    .with_scale(Vec3::splat(0.01)),
+                ..default()
+            },
+            SmudShape3d::Plane,
+        ));
+
+        // Anchored at the bottom, so the markers sit above their positions
+        commands.spawn((
+            ShapeBundle {
+                shape: SmudShape {
+                    color,
+                    sdf: marker.clone(),
+                    frame: Frame::Quad(45.),
+                    anchor: Anchor::BottomCenter,
+                    ..default()
+                },
+                transform: Transform::from_translation(position + Vec3::Y)
+                    .with_scale(Vec3::splat(0.01)),
+                ..default()
+            },
+            SmudShape3d::Billboard,
+        ));
+    }
+
+    commands.spawn(Camera3dBundle::default());
+}
+
+fn orbit_camera(time: Res<Time>, mut cameras: Query<&mut Transform, With<Camera3d>>) {
+    let angle = time.elapsed_seconds() * 0.3;
+    for mut transform in &mut cameras {
+        *transform = Transform::from_xyz(angle.sin() * 8., 4., angle.cos() * 8.)
+            .looking_at(Vec3::ZERO, Vec3::Y);
+    }
+}
@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+use bevy_pancam::*;
+use bevy_smud::{prelude::*, CsgOp};
+
+fn main() {
+    App::new()
+        // bevy_smud comes with anti-aliasing built into the standards fills
+        // which is more efficient than MSAA, and also works on Linux, wayland
+        .insert_resource(Msaa::Off)
+        .insert_resource(ClearColor(Color::rgb(0.7, 0.8, 0.7)))
+        .add_plugins((DefaultPlugins, SmudPlugin, PanCamPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, (wiggle_ears, breathe))
+        .run();
+}
+
+#[derive(Component)]
+struct Ear(f32);
+
+fn setup(mut commands: Commands, mut shaders: ResMut<Assets<Shader>>) {
+    let head = shaders.add_sdf_expr("smud::sd_circle(p, 70.)");
+    let ear = shaders.add_sdf_expr("smud::sd_ellipse(p, 20., 45.)");
+    let eye = shaders.add_sdf_expr("smud::sd_circle(p, 10.)");
+
+    // The parts blend into one seamless shape, and can be moved independently
+    commands
+        .spawn((
+            ShapeBundle {
+                shape: SmudShape {
+                    color: Color::rgb(0.9, 0.6, 0.3),
+                    fill: SIMPLE_FILL_HANDLE,
+                    frame: Frame::Quad(160.),
+                    ..default()
+                },
+                ..default()
+            },
+            SmudCsg::new(CsgOp::SmoothUnion(20.)),
+        ))
+        .with_children(|parent| {
+            parent.spawn(ShapeBundle {
+                shape: SmudShape {
+                    sdf: head,
+                    ..default()
+                },
+                ..default()
+            });
+            for side in [-1., 1.] {
+                parent.spawn((
+                    ShapeBundle {
+                        shape: SmudShape {
+                            sdf: ear.clone(),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    Ear(side),
+                ));
+            }
+        });
+
+    // Parts are cut out of the first child with subtraction
+    commands
+        .spawn((
+            ShapeBundle {
+                shape: SmudShape {
+                    color: Color::rgb(0.3, 0.4, 0.5),
+                    fill: SIMPLE_FILL_HANDLE,
+                    frame: Frame::Quad(80.),
+                    ..default()
+                },
+                transform: Transform::from_xyz(250., 0., 0.),
+                ..default()
+            },
+            SmudCsg::new(CsgOp::SmoothSubtract(6.)),
+        ))
+        .with_children(|parent| {
+            parent.spawn(ShapeBundle {
+                shape: SmudShape {
+                    sdf: shaders.add_sdf_expr(
+                        "smud::sd_rounded_box(p, vec2<f32>(70., 50.), vec4<f32>(20.))",
+                    ),
+                    ..default()
+                },
+                ..default()
+            });
+            for x in [-25., 25.] {
+                parent.spawn(ShapeBundle {
+                    shape: SmudShape {
+                        sdf: eye.clone(),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(x, 10., 0.),
+                    ..default()
+                });
+            }
+        });
+
+    commands.spawn((Camera2dBundle::default(), PanCam::default()));
+}
+
+fn wiggle_ears(time: Res<Time>, mut ears: Query<(&Ear, &mut Transform)>) {
+    let t = time.elapsed_seconds();
+    for (ear, mut transform) in &mut ears {
+        let angle = 0.4 + 0.2 * (t * 3. + ear.0).sin();
+        transform.translation = Vec3::new(ear.0 * 55., 65., 0.);
+        transform.rotation = Quat::from_rotation_z(-ear.0 * angle);
+    }
+}
+
+fn breathe(time: Res<Time>, mut csgs: Query<&mut SmudCsg>) {
+    for mut csg in &mut csgs {
+        if let CsgOp::SmoothUnion(k) = &mut csg.op {
+            *k = 20. + 10. * time.elapsed_seconds().sin();
+        }
+    }
+}
@@ -27,6 +27,7 @@ fn setup(
             sdf: asset_server.load("bevy.wgsl"),
             fill: sin_fill,
             frame: Frame::Quad(295.),
+            ..default()
         },
         ..default()
     });
@@ -38,6 +39,7 @@ fn setup(
             sdf: asset_server.load("bevy.wgsl"),
             fill: SIMPLE_FILL_HANDLE,
             frame: Frame::Quad(295.),
+            ..default()
         },
         ..default()
     });
@@ -50,12 +52,13 @@ fn setup(
             fill: shaders.add_fill_body(
                 r"
 let d_2 = abs(d - 1.) - 1.;
-let a = smud::sd_fill_alpha_fwidth(d_2);
+let a = smud::sd_fill_alpha(d_2);
 return vec4<f32>(color.rgb, a * color.a);
             ",
             ),
 
             frame: Frame::Quad(295.),
+            ..default()
         },
         ..default()
     });
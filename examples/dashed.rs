@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use bevy_smud::prelude::*;
+
+fn main() {
+    App::new()
+        .insert_resource(Msaa::Off)
+        .add_plugins((DefaultPlugins, SmudPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, march)
+        .run();
+}
+
+#[derive(Component)]
+struct MarchingAnts;
+
+fn setup(mut commands: Commands, mut shaders: ResMut<Assets<Shader>>) {
+    let circle = shaders.add_sdf_expr("smud::sd_circle(p, 100.)");
+    let star = shaders.add_sdf_expr("smud::sd_star_5_(p, 90., 2.)");
+
+    // Marching ants: dashes of 12 with gaps of 8, 3 wide, moved along by `march`
+    commands.spawn((
+        ShapeBundle {
+            shape: SmudShape {
+                color: Color::WHITE,
+                sdf: circle,
+                fill: DASHED_FILL_HANDLE,
+                frame: Frame::Quad(110.),
+                ..default()
+            },
+            transform: Transform::from_xyz(-130., 0., 0.),
+            ..default()
+        },
+        SmudDash::new(12., 8., 3.),
+        MarchingAnts,
+    ));
+
+    // Dots: a dash length of 0 leaves only the round caps
+    commands.spawn((
+        ShapeBundle {
+            shape: SmudShape {
+                color: Color::rgb(0.9, 0.7, 0.2),
+                sdf: star,
+                fill: DASHED_FILL_HANDLE,
+                frame: Frame::Quad(110.),
+                ..default()
+            },
+            transform: Transform::from_xyz(130., 0., 0.),
+            ..default()
+        },
+        SmudDash::new(0., 10., 5.),
+    ));
+
+    // A dashed line: with a width of 0, the dashes follow the center of the polyline
+    commands.spawn((
+        ShapeBundle {
+            shape: SmudShape {
+                color: Color::rgb(0.4, 0.8, 0.9),
+                fill: DASHED_FILL_HANDLE,
+                ..default()
+            },
+            transform: Transform::from_xyz(0., -180., 0.),
+            ..default()
+        },
+        SmudPolyline::new(
+            [
+                Vec2::new(-250., 0.),
+                Vec2::new(-100., 40.),
+                Vec2::new(100., -40.),
+                Vec2::new(250., 0.),
+            ],
+            0.,
+        ),
+        SmudDash::new(16., 8., 4.),
+        MarchingAnts,
+    ));
+
+    commands.spawn(Camera2dBundle::default());
+}
+
+fn march(time: Res<Time>, mut dashes: Query<&mut SmudDash, With<MarchingAnts>>) {
+    for mut dash in &mut dashes {
+        dash.phase = time.elapsed_seconds() * 20.;
+    }
+}
@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+use bevy_pancam::*;
+use bevy_smud::prelude::*;
+
+fn main() {
+    App::new()
+        // bevy_smud comes with anti-aliasing built into the standards fills
+        // which is more efficient than MSAA, and also works on Linux, wayland
+        .insert_resource(Msaa::Off)
+        .add_plugins((DefaultPlugins, SmudPlugin, PanCamPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, animate)
+        .run();
+}
+
+fn setup(mut commands: Commands, mut shaders: ResMut<Assets<Shader>>) {
+    let circle = shaders.add_sdf_expr("smud::sd_circle(p, 70.)");
+    let star = shaders.add_sdf_expr("smud::sd_star_5_(p, 70., 0.5)");
+    let heart = shaders.add_sdf_expr("smud::sd_heart((p / 100.) - vec2<f32>(0., -0.5)) * 100.");
+
+    for (i, target) in [star, heart].into_iter().enumerate() {
+        commands.spawn((
+            ShapeBundle {
+                transform: Transform::from_translation(Vec3::new(i as f32 * 200. - 100., 0., 0.)),
+                shape: SmudShape {
+                    color: Color::TOMATO,
+                    sdf: circle.clone(),
+                    // The frame needs to cover both shapes
+                    frame: Frame::Quad(100.),
+                    ..default()
+                },
+                ..default()
+            },
+            // Each shape morphs with its own phase, all sharing the same shader
+            SmudMorph::new(target, 0.),
+        ));
+    }
+
+    commands.spawn((Camera2dBundle::default(), PanCam::default()));
+}
+
+fn animate(time: Res<Time>, mut morphs: Query<(&mut SmudMorph, &Transform)>) {
+    for (mut morph, transform) in &mut morphs {
+        let phase = time.elapsed_seconds() + transform.translation.x / 100.;
+        morph.t = phase.sin() * 0.5 + 0.5;
+    }
+}
@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+use bevy_pancam::*;
+use bevy_smud::prelude::*;
+
+fn main() {
+    App::new()
+        // bevy_smud comes with anti-aliasing built into the standards fills
+        // which is more efficient than MSAA, and also works on Linux, wayland
+        .insert_resource(Msaa::Off)
+        .insert_resource(ClearColor(Color::rgb(0.2, 0.2, 0.25)))
+        .add_plugins((DefaultPlugins, SmudPlugin, PanCamPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, (draw_clock, draw_cursor))
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((Camera2dBundle::default(), PanCam::default()));
+}
+
+// No entities needed, the shapes are drawn again every frame
+fn draw_clock(mut painter: SmudPainter, time: Res<Time>) {
+    painter.circle(Vec2::ZERO, 160., Color::rgb(0.9, 0.9, 0.85));
+
+    for hour in 0..12 {
+        let angle = hour as f32 / 12. * std::f32::consts::TAU;
+        painter.rounded_rect(
+            Vec2::from_angle(angle) * 140.,
+            angle,
+            Vec2::new(20., 6.),
+            3.,
+            Color::DARK_GRAY,
+        );
+    }
+
+    let seconds = time.elapsed_seconds();
+    let hand =
+        |turns: f32| Vec2::from_angle(std::f32::consts::FRAC_PI_2 - turns * std::f32::consts::TAU);
+    painter.line(Vec2::ZERO, hand(seconds / 3600.) * 80., 10., Color::BLACK);
+    painter.line(Vec2::ZERO, hand(seconds / 60.) * 120., 6., Color::BLACK);
+    painter.line(Vec2::ZERO, hand(seconds) * 130., 2., Color::RED);
+    painter.circle(Vec2::ZERO, 8., Color::RED);
+}
+
+fn draw_cursor(
+    mut painter: SmudPainter,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+) {
+    let (camera, camera_transform) = cameras.single();
+    let Some(position) = windows
+        .single()
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor))
+    else {
+        return;
+    };
+    painter.circle(position, 12., Color::ORANGE.with_a(0.7));
+}
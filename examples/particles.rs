@@ -0,0 +1,83 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_smud::prelude::*;
+
+fn main() {
+    App::new()
+        .insert_resource(Msaa::Off)
+        .add_plugins((
+            DefaultPlugins,
+            SmudPlugin,
+            bevy::diagnostic::LogDiagnosticsPlugin::default(),
+            bevy::diagnostic::FrameTimeDiagnosticsPlugin,
+        ))
+        .add_systems(Startup, setup)
+        .add_systems(Update, spin)
+        .run();
+}
+
+#[derive(Component)]
+struct Spin;
+
+fn setup(mut commands: Commands, mut shaders: ResMut<Assets<Shader>>) {
+    // A fountain of small circles, falling back down
+    commands.spawn((
+        ShapeBundle {
+            shape: SmudShape {
+                color: Color::rgb(0.3, 0.6, 1.),
+                sdf: shaders.add_sdf_expr("smud::sd_circle(p, 3.)"),
+                fill: SIMPLE_FILL_HANDLE,
+                frame: Frame::Quad(5.),
+                ..default()
+            },
+            transform: Transform::from_xyz(-250., -200., 0.),
+            ..default()
+        },
+        SmudParticles {
+            capacity: 20_000,
+            rate: 5_000.,
+            lifetime: 4.,
+            velocity: Vec2::new(0., 400.),
+            spread: 0.4,
+            gravity: Vec2::new(0., -200.),
+            ..default()
+        },
+    ));
+
+    // Spinning stars that shrink and turn red, the sdf pulses with the age of each particle
+    commands.spawn((
+        ShapeBundle {
+            shape: SmudShape {
+                color: Color::rgb(1., 0.9, 0.3),
+                sdf: shaders
+                    .add_sdf_expr("smud::sd_star_5_(p, 8. + 2. * sin(smud::time() * 10.), 2.)"),
+                fill: SIMPLE_FILL_HANDLE,
+                frame: Frame::Quad(12.),
+                ..default()
+            },
+            transform: Transform::from_xyz(250., 0., 0.),
+            ..default()
+        },
+        SmudParticles {
+            capacity: 10_000,
+            rate: 2_000.,
+            lifetime: 3.,
+            velocity: Vec2::new(150., 0.),
+            spread: PI / 4.,
+            radius: 10.,
+            end_scale: 0.2,
+            end_color: Some(Color::rgba(1., 0.1, 0., 0.)),
+            ..default()
+        },
+        Spin,
+    ));
+
+    commands.spawn(Camera2dBundle::default());
+}
+
+fn spin(time: Res<Time>, mut emitters: Query<&mut Transform, With<Spin>>) {
+    for mut transform in &mut emitters {
+        transform.rotate_z(time.delta_seconds() * 2.);
+    }
+}
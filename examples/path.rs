@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+use bevy_pancam::*;
+use bevy_smud::prelude::*;
+use bevy_smud::{StrokeCap, StrokeJoin};
+
+fn main() {
+    App::new()
+        // bevy_smud comes with anti-aliasing built into the standards fills
+        // which is more efficient than MSAA, and also works on Linux, wayland
+        .insert_resource(Msaa::Off)
+        .add_plugins((DefaultPlugins, SmudPlugin, PanCamPlugin))
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    let zigzag = |y: f32| {
+        SmudPath::new(16.)
+            .move_to(Vec2::new(-120., y - 20.))
+            .line_to(Vec2::new(-60., y + 20.))
+            .line_to(Vec2::new(0., y - 20.))
+            .quad_to(Vec2::new(30., y + 60.), Vec2::new(60., y))
+            .line_to(Vec2::new(120., y + 20.))
+    };
+
+    // Every combination of cap and join
+    for (i, (cap, join)) in [
+        (StrokeCap::Butt, StrokeJoin::Miter),
+        (StrokeCap::Round, StrokeJoin::Round),
+        (StrokeCap::Square, StrokeJoin::Bevel),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        commands.spawn((
+            ShapeBundle {
+                transform: Transform::from_translation(Vec3::new(
+                    -150.,
+                    150. - i as f32 * 120.,
+                    0.,
+                )),
+                shape: SmudShape {
+                    color: Color::TOMATO,
+                    fill: SIMPLE_FILL_HANDLE,
+                    ..default()
+                },
+                ..default()
+            },
+            zigzag(0.).with_cap(cap).with_join(join),
+        ));
+    }
+
+    // A closed heart made of cubic curves
+    commands.spawn((
+        ShapeBundle {
+            transform: Transform::from_translation(Vec3::new(200., 0., 0.)),
+            shape: SmudShape {
+                color: Color::TEAL,
+                fill: SIMPLE_FILL_HANDLE,
+                ..default()
+            },
+            ..default()
+        },
+        SmudPath::new(10.)
+            .move_to(Vec2::new(0., -100.))
+            .cubic_to(
+                Vec2::new(-150., 0.),
+                Vec2::new(-80., 120.),
+                Vec2::new(0., 50.),
+            )
+            .cubic_to(
+                Vec2::new(80., 120.),
+                Vec2::new(150., 0.),
+                Vec2::new(0., -100.),
+            )
+            .close(),
+    ));
+
+    commands.spawn((Camera2dBundle::default(), PanCam::default()));
+}
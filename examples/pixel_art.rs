@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use bevy_smud::{prelude::*, SmudPixelArt};
+
+const PIXEL_SCALE: u32 = 4;
+
+fn main() {
+    App::new()
+        .insert_resource(Msaa::Off)
+        .add_plugins((DefaultPlugins, SmudPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, (drift, toggle_pixel_art))
+        .run();
+}
+
+fn setup(mut commands: Commands, mut shaders: ResMut<Assets<Shader>>) {
+    let star = shaders.add_sdf_expr("smud::sd_star_5_(p, 10., 2.)");
+    let circle = shaders.add_sdf_expr("smud::sd_circle(p, 6.)");
+
+    for (i, sdf) in [star, circle].into_iter().enumerate() {
+        commands.spawn(ShapeBundle {
+            shape: SmudShape {
+                color: Color::rgb(0.9, 0.7 - i as f32 * 0.4, 0.2 + i as f32 * 0.5),
+                sdf,
+                fill: SIMPLE_FILL_HANDLE,
+                frame: Frame::Quad(12.),
+                ..default()
+            },
+            transform: Transform::from_xyz(i as f32 * 30. - 15., 0., 0.),
+            ..default()
+        });
+    }
+
+    commands.spawn((
+        Camera2dBundle {
+            projection: OrthographicProjection {
+                scale: 1. / PIXEL_SCALE as f32,
+                ..default()
+            },
+            ..default()
+        },
+        SmudPixelArt {
+            pixel_scale: PIXEL_SCALE,
+        },
+    ));
+    info!("Press space to toggle pixel art");
+}
+
+// Slow movement and rotation, which is where snapping makes a difference
+fn drift(time: Res<Time>, mut shapes: Query<&mut Transform, With<SmudShape>>) {
+    let t = time.elapsed_seconds();
+    for (i, mut transform) in shapes.iter_mut().enumerate() {
+        transform.translation.y = (t * 0.5 + i as f32).sin() * 20.;
+        transform.rotation = Quat::from_rotation_z(t * 0.1);
+    }
+}
+
+fn toggle_pixel_art(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    cameras: Query<(Entity, Has<SmudPixelArt>), With<Camera>>,
+) {
+    if keys.just_pressed(KeyCode::Space) {
+        for (camera, pixel_art) in &cameras {
+            if pixel_art {
+                commands.entity(camera).remove::<SmudPixelArt>();
+            } else {
+                commands.entity(camera).insert(SmudPixelArt {
+                    pixel_scale: PIXEL_SCALE,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_pancam::*;
+use bevy_smud::FillRule;
+use bevy_smud::{prelude::*, StrokeCap};
+
+fn main() {
+    App::new()
+        // bevy_smud comes with anti-aliasing built into the standards fills
+        // which is more efficient than MSAA, and also works on Linux, wayland
+        .insert_resource(Msaa::Off)
+        .add_plugins((DefaultPlugins, SmudPlugin, PanCamPlugin))
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    // A self-intersecting pentagram, drawn with both fill rules
+    let pentagram: Vec<Vec2> = (0..5)
+        .map(|i| {
+            let angle = TAU / 4. + i as f32 * 2. * TAU / 5.;
+            Vec2::from_angle(angle) * 100.
+        })
+        .collect();
+
+    for (x, fill_rule) in [(-150., FillRule::NonZero), (150., FillRule::EvenOdd)] {
+        commands.spawn((
+            ShapeBundle {
+                transform: Transform::from_translation(Vec3::new(x, 100., 0.)),
+                shape: SmudShape {
+                    color: Color::TOMATO,
+                    // The frame needs to cover all the points
+                    frame: Frame::Quad(110.),
+                    ..default()
+                },
+                ..default()
+            },
+            SmudPolygon::new(pentagram.clone()).with_fill_rule(fill_rule),
+        ));
+    }
+
+    // Polylines are open, and have a thickness instead of an inside
+    let wave: Vec<Vec2> = (0..=20)
+        .map(|i| {
+            let x = i as f32 * 20. - 200.;
+            Vec2::new(x, (x / 30.).sin() * 40.)
+        })
+        .collect();
+
+    commands.spawn((
+        ShapeBundle {
+            transform: Transform::from_translation(Vec3::Y * -150.),
+            shape: SmudShape {
+                color: Color::TEAL,
+                fill: SIMPLE_FILL_HANDLE,
+                // The frame of polylines is computed from the points
+                ..default()
+            },
+            ..default()
+        },
+        SmudPolyline::new(wave, 8.),
+    ));
+
+    // Single lines get their own transform and frame, and don't need storage buffers
+    for (i, cap) in [StrokeCap::Butt, StrokeCap::Round, StrokeCap::Square]
+        .into_iter()
+        .enumerate()
+    {
+        let y = -230. - i as f32 * 30.;
+        commands.spawn((
+            ShapeBundle {
+                shape: SmudShape {
+                    color: Color::ORANGE,
+                    fill: SIMPLE_FILL_HANDLE,
+                    ..default()
+                },
+                ..default()
+            },
+            SmudLine::new(Vec2::new(-150., y), Vec2::new(150., y + 20.), 12.).with_cap(cap),
+        ));
+    }
+
+    commands.spawn((Camera2dBundle::default(), PanCam::default()));
+}
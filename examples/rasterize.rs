@@ -0,0 +1,56 @@
+//! Renders shapes on the cpu and saves them as a png, no gpu or window needed
+
+use bevy::prelude::*;
+use bevy_smud::{sdf, CpuFill, Frame, SmudRasterizer, SmudShape, SIMPLE_FILL_HANDLE};
+
+fn main() {
+    let mut rasterizer = SmudRasterizer::new(480, 160);
+    rasterizer.clear_color = Color::rgb(0.1, 0.1, 0.1);
+
+    // Any handles work, as long as the same ones are registered with the rasterizer and used by
+    // the shapes. In an app, these would be the handles of the wgsl versions.
+    let star = Handle::weak_from_u128(0x5eed_0001);
+    let heart = Handle::weak_from_u128(0x5eed_0002);
+    let blob = Handle::weak_from_u128(0x5eed_0003);
+    let outline = Handle::weak_from_u128(0x5eed_0004);
+
+    rasterizer
+        .register_sdf(&star, |p| sdf::sd_star_5(p, 50., 0.5))
+        .register_sdf(&heart, |p| {
+            sdf::sd_heart((p / 60.) - Vec2::new(0., -0.5)) * 60.
+        })
+        .register_sdf(&blob, |p| {
+            sdf::op_smooth_union(
+                sdf::sd_circle(p - Vec2::new(-20., 0.), 30.),
+                sdf::sd_box(p - Vec2::new(20., 10.), Vec2::new(25., 20.)),
+                10.,
+            )
+        })
+        .register_fill(&outline, CpuFill::Outline);
+
+    let shapes = [
+        (star, SIMPLE_FILL_HANDLE, Color::GOLD, -160.),
+        (heart, outline, Color::PINK, 0.),
+        // The default fill is the cubic falloff
+        (blob, SmudShape::default().fill, Color::TEAL, 160.),
+    ]
+    .map(|(sdf, fill, color, x)| {
+        (
+            SmudShape {
+                color,
+                sdf,
+                fill,
+                frame: Frame::Quad(75.),
+                ..default()
+            },
+            GlobalTransform::from_translation(Vec3::X * x),
+        )
+    });
+
+    let image = rasterizer
+        .render(shapes.iter().map(|(shape, transform)| (shape, transform)))
+        .unwrap();
+    let path = "rasterized.png";
+    image.try_into_dynamic().unwrap().save(path).unwrap();
+    println!("Saved {path}");
+}
@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use bevy_pancam::*;
+use bevy_smud::prelude::*;
+
+fn main() {
+    App::new()
+        // bevy_smud comes with anti-aliasing built into the standards fills
+        // which is more efficient than MSAA, and also works on Linux, wayland
+        .insert_resource(Msaa::Off)
+        .add_plugins((DefaultPlugins, SmudPlugin, PanCamPlugin))
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(mut commands: Commands, mut shaders: ResMut<Assets<Shader>>) {
+    // Islands on a map, which grow and shrink as the camera zooms
+    let island = shaders.add_sdf_expr("smud::sd_blobby_cross(p / 100., 0.7) * 100.");
+    // A map pin, with its tip at the bottom of the frame
+    let pin = shaders.add_sdf_body(
+        "let head = smud::sd_circle(p - vec2<f32>(0., 6.), 10.);
+        let tip = smud::sd_triangle(p, vec2<f32>(-9., 2.), vec2<f32>(9., 2.), vec2<f32>(0., -20.));
+        let hole = smud::sd_circle(p - vec2<f32>(0., 6.), 4.);
+        return max(min(head, tip), -hole);",
+    );
+
+    for (i, position) in [(-300., -100.), (50., 200.), (350., -150.)]
+        .into_iter()
+        .enumerate()
+    {
+        let position = Vec2::from(position);
+        commands.spawn(ShapeBundle {
+            shape: SmudShape {
+                color: Color::rgb(0.5, 0.7, 0.4),
+                sdf: island.clone(),
+                fill: SIMPLE_FILL_HANDLE,
+                frame: Frame::Quad(120.),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(0.)),
+            ..default()
+        });
+
+        // The pins stay the same size on screen, with their tips on the islands
+        commands.spawn((
+            ShapeBundle {
+                shape: SmudShape {
+                    color: Color::hsl(i as f32 * 120., 0.8, 0.5),
+                    sdf: pin.clone(),
+                    fill: SIMPLE_FILL_HANDLE,
+                    frame: Frame::Quad(20.),
+                    anchor: Anchor::BottomCenter,
+                },
+                transform: Transform::from_translation(position.extend(1.)),
+                ..default()
+            },
+            SmudScreenSpace,
+        ));
+    }
+
+    commands.spawn((Camera2dBundle::default(), PanCam::default()));
+}
@@ -0,0 +1,32 @@
+use bevy::prelude::*;
+use bevy_pancam::*;
+use bevy_smud::prelude::*;
+
+fn main() {
+    App::new()
+        // bevy_smud comes with anti-aliasing built into the standards fills
+        // which is more efficient than MSAA, and also works on Linux, wayland
+        .insert_resource(Msaa::Off)
+        .insert_resource(ClearColor(Color::rgb(0.7, 0.8, 0.7)))
+        .add_plugins((DefaultPlugins, SmudPlugin, PanCamPlugin))
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    // The sdf, fill, frame and color all come from the asset
+    commands.spawn((
+        ShapeBundle::default(),
+        asset_server.load::<SmudShapeAsset>("shapes/bevy.smud.ron"),
+    ));
+
+    commands.spawn((
+        ShapeBundle {
+            transform: Transform::from_translation(Vec3::new(500., 0., 0.)),
+            ..default()
+        },
+        asset_server.load::<SmudShapeAsset>("shapes/ring.smud.ron"),
+    ));
+
+    commands.spawn((Camera2dBundle::default(), PanCam::default()));
+}
@@ -0,0 +1,30 @@
+use bevy::prelude::*;
+use bevy_pancam::*;
+use bevy_smud::{prelude::*, SmudSvg};
+
+fn main() {
+    App::new()
+        // bevy_smud comes with anti-aliasing built into the standards fills
+        // which is more efficient than MSAA, and also works on Linux, wayland
+        .insert_resource(Msaa::Off)
+        .insert_resource(ClearColor(Color::rgb(0.7, 0.8, 0.7)))
+        .add_plugins((DefaultPlugins, SmudPlugin, PanCamPlugin))
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(mut commands: Commands, mut shaders: ResMut<Assets<Shader>>) {
+    let svg = SmudSvg::parse(include_str!("../assets/icons/bevy_smud.svg"), &mut shaders)
+        .expect("valid svg");
+
+    // The shapes stay crisp at any scale
+    for (i, scale) in [1., 2., 4., 8.].into_iter().enumerate() {
+        let icon = svg.spawn(&mut commands);
+        commands.entity(icon).insert(SpatialBundle::from_transform(
+            Transform::from_translation(Vec3::new(i as f32 * 200. - 300., 0., 0.))
+                .with_scale(Vec3::splat(scale)),
+        ));
+    }
+
+    commands.spawn((Camera2dBundle::default(), PanCam::default()));
+}
@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+use bevy_pancam::*;
+use bevy_smud::prelude::*;
+
+fn main() {
+    App::new()
+        // bevy_smud comes with anti-aliasing built into the standards fills
+        // which is more efficient than MSAA, and also works on Linux, wayland
+        .insert_resource(Msaa::Off)
+        .insert_resource(ClearColor(Color::rgb(0.1, 0.1, 0.15)))
+        .add_plugins((DefaultPlugins, SmudPlugin, PanCamPlugin))
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    // The glyph atlas is generated when the font is loaded
+    let font = asset_server.load("fonts/FiraMono-subset.ttf");
+
+    commands.spawn(SmudTextBundle {
+        text: SmudText {
+            text: "Hello, bevy_smud!".into(),
+            font: font.clone(),
+            font_size: 64.,
+            color: Color::WHITE,
+            ..default()
+        },
+        transform: Transform::from_translation(Vec3::Y * 100.),
+        ..default()
+    });
+
+    // Text can use the same fills as shapes
+    commands.spawn(SmudTextBundle {
+        text: SmudText {
+            text: "Outlines\nand glows".into(),
+            font: font.clone(),
+            font_size: 48.,
+            color: Color::TOMATO,
+            fill: asset_server.load("fills/outline.wgsl"),
+            ..default()
+        },
+        transform: Transform::from_translation(Vec3::new(-200., -100., 0.)),
+        ..default()
+    });
+
+    commands.spawn(SmudTextBundle {
+        text: SmudText {
+            text: "Outlines\nand glows".into(),
+            font,
+            font_size: 48.,
+            color: Color::GOLD,
+            fill: DEFAULT_FILL_HANDLE,
+            ..default()
+        },
+        transform: Transform::from_translation(Vec3::new(200., -100., 0.)),
+        ..default()
+    });
+
+    commands.spawn((Camera2dBundle::default(), PanCam::default()));
+}
@@ -10,6 +10,7 @@ fn main() {
         .insert_resource(ClearColor(Color::rgb(0.7, 0.8, 0.7)))
         .add_plugins((DefaultPlugins, SmudPlugin, PanCamPlugin))
         .add_systems(Startup, setup)
+        .add_systems(Update, toggle_pause)
         .run();
 }
 
@@ -19,12 +20,45 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.spawn(ShapeBundle {
         shape: SmudShape {
             color: Color::rgb(0.36, 0.41, 0.45),
-            sdf: bevy_shape_shader,
+            sdf: bevy_shape_shader.clone(),
             frame: Frame::Quad(400.),
             ..default()
         },
         ..default()
     });
 
+    // A row of smaller copies, each with its own offset and speed
+    for i in 0..8 {
+        commands.spawn((
+            ShapeBundle {
+                transform: Transform::from_translation(Vec3::new(
+                    i as f32 * 100. - 350.,
+                    -350.,
+                    0.,
+                ))
+                .with_scale(Vec3::splat(0.1)),
+                shape: SmudShape {
+                    color: Color::rgb(0.36, 0.41, 0.45),
+                    sdf: bevy_shape_shader.clone(),
+                    frame: Frame::Quad(400.),
+                    ..default()
+                },
+                ..default()
+            },
+            SmudTime::from_offset(i as f32 * 0.4).with_scale(1. + i as f32 * 0.2),
+        ));
+    }
+
     commands.spawn((Camera2dBundle::default(), PanCam::default()));
 }
+
+/// Pausing the game clock pauses all shapes
+fn toggle_pause(keys: Res<Input<KeyCode>>, mut time: ResMut<Time<Virtual>>) {
+    if keys.just_pressed(KeyCode::Space) {
+        if time.is_paused() {
+            time.unpause();
+        } else {
+            time.pause();
+        }
+    }
+}
@@ -3,7 +3,6 @@ use bevy_pancam::{PanCam, PanCamPlugin};
 use bevy_smud::*;
 
 /// This example just shows that transforms work
-
 fn main() {
     App::new()
         // bevy_smud comes with anti-aliasing built into the standards fills
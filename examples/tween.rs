@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+use bevy_pancam::*;
+use bevy_smud::prelude::*;
+use bevy_smud::{TweenEasing, TweenRepeat};
+
+fn main() {
+    App::new()
+        // bevy_smud comes with anti-aliasing built into the standards fills
+        // which is more efficient than MSAA, and also works on Linux, wayland
+        .insert_resource(Msaa::Off)
+        .add_plugins((DefaultPlugins, SmudPlugin, PanCamPlugin))
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(mut commands: Commands, mut shaders: ResMut<Assets<Shader>>) {
+    let circle = shaders.add_sdf_expr("smud::sd_circle(p, 50.)");
+    let square = shaders.add_sdf_expr("smud::sd_box(p, vec2<f32>(40., 40.))");
+
+    // Color and morph, eased back and forth
+    commands.spawn((
+        ShapeBundle {
+            transform: Transform::from_translation(Vec3::X * -100.),
+            shape: SmudShape {
+                sdf: circle.clone(),
+                frame: Frame::Quad(60.),
+                ..default()
+            },
+            ..default()
+        },
+        SmudMorph::new(square, 0.),
+        SmudTween::new(1.5)
+            .with_color(Color::TOMATO, Color::TEAL)
+            .with_morph(0., 1.)
+            .with_easing(TweenEasing::EaseInOut)
+            .with_repeat(TweenRepeat::PingPong),
+    ));
+
+    // Growing frame, clipping the shape while it's small
+    commands.spawn((
+        ShapeBundle {
+            transform: Transform::from_translation(Vec3::X * 100.),
+            shape: SmudShape {
+                color: Color::GOLD,
+                sdf: circle,
+                ..default()
+            },
+            ..default()
+        },
+        SmudTween::new(2.)
+            .with_frame(Frame::Quad(0.), Frame::Quad(60.))
+            .with_repeat(TweenRepeat::Loop),
+    ));
+
+    commands.spawn((Camera2dBundle::default(), PanCam::default()));
+}
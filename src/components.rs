@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::DEFAULT_FILL_HANDLE;
 
@@ -18,6 +19,8 @@ pub struct SmudShape {
     pub fill: Handle<Shader>, // todo: wrap in newtypes?
     /// The outer bounds for the shape, should be bigger than the sdf shape
     pub frame: Frame,
+    /// Which point of the frame is placed at the transform of the shape
+    pub anchor: Anchor,
 }
 
 impl Default for SmudShape {
@@ -27,17 +30,181 @@ impl Default for SmudShape {
             sdf: default(),
             frame: default(),
             fill: DEFAULT_FILL_HANDLE,
+            anchor: default(),
         }
     }
 }
 
+impl SmudShape {
+    /// The transform of the space the sdf is evaluated in, with the anchor applied
+    pub(crate) fn anchored(&self, transform: &GlobalTransform) -> GlobalTransform {
+        let Frame::Quad(frame) = self.frame;
+        match self.anchor {
+            Anchor::Center => *transform,
+            anchor => {
+                let offset = anchor.frame_offset() * frame;
+                *transform * GlobalTransform::from_translation(offset.extend(0.))
+            }
+        }
+    }
+}
+
+#[derive(Component, Reflect, Debug, Default, Clone)]
+#[reflect(Component)]
+/// Morphs the sdf of the [`SmudShape`] on the same entity into another sdf
+///
+/// The resulting distance is `mix(a(p), b(p), t)`, where `a` is the sdf of the shape and `b` is
+/// the target. Since `t` is sent along with each instance, it can be animated without generating
+/// new shaders. Make sure the frame of the shape covers both sdfs.
+///
+/// Morphing is not supported for polygons, polylines and paths.
+pub struct SmudMorph {
+    /// Shader containing the sdf to morph into
+    pub target: Handle<Shader>,
+    /// The blend factor, where 0 is the sdf of the shape and 1 is the target
+    pub t: f32,
+}
+
+impl SmudMorph {
+    /// Creates a morph into the given sdf shader
+    pub fn new(target: Handle<Shader>, t: f32) -> Self {
+        Self { target, t }
+    }
+}
+
+#[derive(Component, Reflect, Debug, Default, Clone, Copy)]
+#[reflect(Component)]
+/// Extra parameters for the shaders of the [`SmudShape`] on the same entity
+///
+/// Shaders read them through `smud::params()`. This lets several shapes share an sdf or fill
+/// shader, while still varying things like thickness or count, without generating new shaders.
+///
+/// Ignored for polygons, polylines, paths and morphs, which use the parameters internally.
+pub struct SmudParams(pub Vec4);
+
+#[derive(Component, Reflect, Debug, Default, Clone, Copy, PartialEq)]
+#[reflect(Component)]
+/// The dashes drawn by [`DASHED_FILL_HANDLE`](crate::DASHED_FILL_HANDLE) on the same entity
+///
+/// The dashes are separate from [`SmudParams`], so they work with any sdf, including lines,
+/// polylines and paths.
+///
+/// ```
+/// # use bevy_smud::SmudDash;
+/// // Dots, 5 wide and 10 apart
+/// let dots = SmudDash::new(0., 10., 5.);
+/// ```
+pub struct SmudDash {
+    /// Length of each dash, not including its round caps, so 0 draws dots
+    pub length: f32,
+    /// Space between the caps of neighboring dashes
+    pub gap: f32,
+    /// Distance the dashes are moved along the contour, animate it for marching ants
+    pub phase: f32,
+    /// Thickness of the dashes
+    pub width: f32,
+}
+
+impl SmudDash {
+    /// Creates dashes starting at the beginning of the contour
+    pub fn new(length: f32, gap: f32, width: f32) -> Self {
+        Self {
+            length,
+            gap,
+            phase: 0.,
+            width,
+        }
+    }
+
+    /// Moves the dashes along the contour
+    pub fn with_phase(mut self, phase: f32) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    /// The instance data read by `fills/dashed.wgsl`
+    pub(crate) fn instance_data(&self) -> Vec4 {
+        Vec4::new(self.length, self.gap, self.phase, self.width)
+    }
+}
+
+#[derive(Component, Reflect, Debug, Default, Clone, Copy)]
+#[reflect(Component)]
+/// Measures the frame and sdf of the [`SmudShape`] on the same entity in physical screen pixels
+///
+/// The shape is still positioned in world space, but keeps the same size on screen when the
+/// camera zooms, which is handy for map markers and selection handles. The scale of the transform
+/// still applies, and the [`Anchor`] is measured in pixels too.
+///
+/// Zooming is detected through the projection of the camera, so scaling the camera transform
+/// instead scales the shapes as usual. With 3d cameras, shapes are sized by their distance to
+/// the camera. Not supported for paths, text, lines and polylines.
+pub struct SmudScreenSpace;
+
 /// Bounds for describing how far the fragment shader of a shape will reach, should be bigger than the shape unless you want to clip it
-#[derive(Reflect, Debug, Clone, Copy)]
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Frame {
     /// A quad with a given half-size (!)
     Quad(f32), // todo: it probably makes sense for this to be the full width instead...
 }
 
+/// Which point of the [`Frame`] of a shape is placed at its transform
+///
+/// Both the quad and the space the sdf is evaluated in are moved, so with [`Anchor::BottomLeft`]
+/// the origin of the sdf is half a frame up and to the right of the transform. Rotation and
+/// scale happen around the anchor.
+///
+/// Ignored for paths, lines and polylines, which compute their frames from their points.
+#[derive(Reflect, Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Anchor {
+    /// The center of the frame, the default
+    #[default]
+    Center,
+    /// The bottom left corner
+    BottomLeft,
+    /// The middle of the bottom edge
+    BottomCenter,
+    /// The bottom right corner
+    BottomRight,
+    /// The middle of the left edge
+    CenterLeft,
+    /// The middle of the right edge
+    CenterRight,
+    /// The top left corner
+    TopLeft,
+    /// The middle of the top edge
+    TopCenter,
+    /// The top right corner
+    TopRight,
+    /// A custom point, where `(-0.5, -0.5)` is the bottom left corner and `(0.5, 0.5)` the top
+    /// right one
+    Custom(Vec2),
+}
+
+impl Anchor {
+    /// The anchor point relative to the frame, where `(-0.5, -0.5)` is the bottom left corner
+    /// and `(0.5, 0.5)` the top right one
+    pub fn as_vec(&self) -> Vec2 {
+        match self {
+            Self::Center => Vec2::ZERO,
+            Self::BottomLeft => Vec2::new(-0.5, -0.5),
+            Self::BottomCenter => Vec2::new(0., -0.5),
+            Self::BottomRight => Vec2::new(0.5, -0.5),
+            Self::CenterLeft => Vec2::new(-0.5, 0.),
+            Self::CenterRight => Vec2::new(0.5, 0.),
+            Self::TopLeft => Vec2::new(-0.5, 0.5),
+            Self::TopCenter => Vec2::new(0., 0.5),
+            Self::TopRight => Vec2::new(0.5, 0.5),
+            Self::Custom(point) => *point,
+        }
+    }
+
+    /// How far the center of the frame is from the anchor point, in half frames
+    pub(crate) fn frame_offset(&self) -> Vec2 {
+        -self.as_vec() * 2.
+    }
+}
+
 impl Frame {
     const DEFAULT_QUAD: Self = Self::Quad(1.);
 }
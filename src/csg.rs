@@ -0,0 +1,265 @@
+use std::mem::{discriminant, Discriminant};
+
+use bevy::{ecs::query::Has, prelude::*, render::Extract};
+
+use crate::{
+    import_path,
+    time::{instance_time, SmudTime},
+    util::generate_shader_id,
+    CsgOp, ExtractedShape, ExtractedShapes, Frame, ShapeChanged, SmudDash, SmudPipeline,
+    SmudScreenSpace, SmudShape, SmudShape3d,
+};
+
+/// Combines the sdfs of the child shapes of an entity into a single seamless shape
+///
+/// The entity also needs a [`SmudShape`], which provides the color, fill and frame of the
+/// combined shape, while its own sdf is ignored. Of the children, only the sdfs and
+/// [`Transform`]s are used, and they aren't drawn on their own. The children are combined in
+/// order, so with [`CsgOp::Subtract`] all other children are cut out of the first one.
+///
+/// Moving, rotating and uniformly scaling the children is cheap, and so is changing the size of
+/// smooth operators. Adding, removing or reordering children, or changing their sdfs, generates
+/// a new shader.
+///
+/// The child transforms are stored in the same storage buffer as polygons, so this is not
+/// supported on WebGL2. Children can't be polygons, polylines, paths or morphs, and their sdfs
+/// see the params of the combined shape rather than their own.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_smud::prelude::*;
+/// # use bevy_smud::{CsgOp, SmudCsg};
+/// fn setup(mut commands: Commands, mut shaders: ResMut<Assets<Shader>>) {
+///     let head = shaders.add_sdf_expr("smud::sd_circle(p, 40.)");
+///     let ear = shaders.add_sdf_expr("smud::sd_ellipse(p, 12., 24.)");
+///     commands
+///         .spawn((
+///             ShapeBundle {
+///                 shape: SmudShape {
+///                     color: Color::ORANGE,
+///                     frame: Frame::Quad(80.),
+///                     ..default()
+///                 },
+///                 ..default()
+///             },
+///             SmudCsg::new(CsgOp::SmoothUnion(10.)),
+///         ))
+///         .with_children(|parent| {
+///             for x in [-30., 30.] {
+///                 parent.spawn(ShapeBundle {
+///                     shape: SmudShape { sdf: ear.clone(), ..default() },
+///                     transform: Transform::from_xyz(x, 40., 0.),
+///                     ..default()
+///                 });
+///             }
+///             parent.spawn(ShapeBundle {
+///                 shape: SmudShape { sdf: head, ..default() },
+///                 ..default()
+///             });
+///         });
+/// }
+/// ```
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq)]
+#[reflect(Component)]
+pub struct SmudCsg {
+    /// How the children are combined
+    pub op: CsgOp,
+}
+
+impl Default for SmudCsg {
+    fn default() -> Self {
+        Self::new(CsgOp::Union)
+    }
+}
+
+impl SmudCsg {
+    /// Combines the children with the given operator
+    pub fn new(op: CsgOp) -> Self {
+        Self { op }
+    }
+}
+
+/// Generated csg sdfs are shared by all shapes with the same kind of operator and child sdfs
+pub(crate) type CsgKey = (Discriminant<CsgOp>, Vec<AssetId<Shader>>);
+
+pub(crate) fn csg_key(op: CsgOp, sdfs: &[Handle<Shader>]) -> CsgKey {
+    (discriminant(&op), sdfs.iter().map(Handle::id).collect())
+}
+
+/// The sdfs and transforms of the children of a csg shape that are shapes themselves
+pub(crate) fn csg_parts<'a>(
+    children: Option<&Children>,
+    mut part: impl FnMut(Entity) -> Option<(&'a SmudShape, &'a Transform)>,
+) -> Vec<(&'a SmudShape, &'a Transform)> {
+    children
+        .into_iter()
+        .flatten()
+        .filter_map(|child| part(*child))
+        .collect()
+}
+
+/// Gets or generates an sdf combining the given sdfs
+///
+/// The instance params are (offset of the part transforms in the point buffer, smoothness, 0, 0)
+/// and each part has two points: its translation, and its rotation as a unit vector scaled by its
+/// scale. Returns `None` if any of the sdfs isn't loaded yet.
+pub(crate) fn csg_sdf(
+    pipeline: &mut SmudPipeline,
+    shaders: &mut Assets<Shader>,
+    op: CsgOp,
+    sdfs: &[Handle<Shader>],
+) -> Option<Handle<Shader>> {
+    let key = csg_key(op, sdfs);
+    if let Some(csg) = pipeline.csgs.get(&key) {
+        return Some(csg.clone_weak());
+    }
+
+    // Parts sharing an sdf share an import
+    let mut modules: Vec<AssetId<Shader>> = Vec::new();
+    let mut imports = String::new();
+    let mut body = String::new();
+    for (i, sdf) in sdfs.iter().enumerate() {
+        let module = match modules.iter().position(|id| *id == sdf.id()) {
+            Some(module) => module,
+            None => {
+                let import_path = import_path(shaders, sdf)?;
+                imports += &format!("#import {import_path} as part_{}\n", modules.len());
+                modules.push(sdf.id());
+                modules.len() - 1
+            }
+        };
+        body += &format!(
+            r#"
+    let r{i} = points[offset + {r}u];
+    let s{i} = dot(r{i}, r{i});
+    let v{i} = p - points[offset + {t}u];
+    let d{i} = part_{module}::sdf(vec2<f32>(dot(v{i}, r{i}), v{i}.y * r{i}.x - v{i}.x * r{i}.y) / s{i}) * sqrt(s{i});"#,
+            t = 2 * i,
+            r = 2 * i + 1,
+        );
+    }
+    body += "\n    var d = d0;";
+    for i in 1..sdfs.len() {
+        body += &format!("\n    d = {};", op.wgsl_with_k("d", &format!("d{i}"), "k"));
+    }
+
+    debug!("Generating csg sdf");
+    let id = generate_shader_id();
+    let csg = shaders.add(Shader::from_wgsl(
+        format!(
+            r#"
+#define_import_path smud::generated::csg_{id}
+
+#import smud
+#import smud::instance
+{imports}
+@group(1) @binding(0)
+var<storage, read> points: array<vec2<f32>>;
+
+fn sdf(p: vec2<f32>) -> f32 {{
+    let offset = u32(instance::params.x);
+    let k = instance::params.y;
+{body}
+    return d;
+}}
+"#
+        ),
+        format!("smud::generated::csg::{id}"),
+    ));
+    pipeline.csg_sdfs.insert(csg.id());
+    let weak = csg.clone_weak();
+    pipeline.csgs.insert(key, csg);
+    Some(weak)
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn extract_csgs(
+    mut extracted_shapes: ResMut<ExtractedShapes>,
+    pipeline: Res<SmudPipeline>,
+    csg_query: Extract<
+        Query<(
+            Entity,
+            &ViewVisibility,
+            &SmudCsg,
+            &SmudShape,
+            &GlobalTransform,
+            Option<&Children>,
+            Option<&SmudDash>,
+            Option<&SmudTime>,
+            Option<&SmudShape3d>,
+            Has<SmudScreenSpace>,
+        )>,
+    >,
+    part_query: Extract<Query<(&SmudShape, &Transform)>>,
+    changed_query: Extract<Query<(), Or<ShapeChanged>>>,
+) {
+    for (
+        entity,
+        view_visibility,
+        csg,
+        shape,
+        transform,
+        children,
+        dash,
+        shape_time,
+        three_d,
+        screen_space,
+    ) in csg_query.iter()
+    {
+        if !view_visibility.get() {
+            continue;
+        }
+
+        let parts = csg_parts(children, |child| part_query.get(child).ok());
+        if parts.is_empty() {
+            continue;
+        }
+        let sdfs: Vec<Handle<Shader>> = parts
+            .iter()
+            .map(|(part, _)| part.sdf.clone_weak())
+            .collect();
+        let Some(sdf_shader) = pipeline.csgs.get(&csg_key(csg.op, &sdfs)) else {
+            // The csg sdf is generated in `extract_sdf_shaders`
+            continue;
+        };
+
+        let Some(offset) = extracted_shapes.push_points(
+            entity,
+            parts.iter().flat_map(|(_, transform)| {
+                let rotation = (transform.rotation * Vec3::X)
+                    .truncate()
+                    .normalize_or_zero();
+                [
+                    transform.translation.truncate(),
+                    rotation * transform.scale.x,
+                ]
+            }),
+        ) else {
+            continue;
+        };
+
+        // The parts are only in the point buffer, so they don't change the instance data
+        let changed = changed_query.contains(entity) || extracted_shapes.points_moved(entity);
+
+        let Frame::Quad(frame) = shape.frame;
+        extracted_shapes.shapes.insert(
+            entity,
+            ExtractedShape {
+                color: shape.color,
+                frame,
+                params: Vec4::new(offset, csg.op.smoothness(), 0., 0.),
+                dash: dash.map_or(Vec4::ZERO, SmudDash::instance_data),
+                time: instance_time(shape_time),
+                sdf_shader: sdf_shader.clone_weak(),
+                fill_shader: shape.fill.clone_weak(),
+                transform: *transform,
+                anchor: shape.anchor.frame_offset(),
+                screen_space,
+                atlas: None,
+                three_d: three_d.copied(),
+                changed,
+                key: (entity, 0),
+            },
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::ops::Range;
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{Buffer, BufferDescriptor, BufferUsages},
+        renderer::{RenderDevice, RenderQueue},
+    },
+};
+use bytemuck::Pod;
+
+use crate::{
+    SmudCsg, SmudDash, SmudInstanceUpload, SmudLine, SmudMorph, SmudParams, SmudPolygon,
+    SmudPolyline, SmudShape, SmudShape3d, SmudTime,
+};
+
+/// Identifies an instance across frames, by the main world entity it's drawn for and the index of
+/// the glyph or path segment within it
+///
+/// Glyphs, path segments and painted shapes get new render world entities every frame, so those
+/// can't tell whether a slot still holds the same instance.
+pub(crate) type InstanceKey = (Entity, u32);
+
+/// Unchanged slots between two changed ones that are uploaded anyway, to make fewer writes
+const MERGE_GAP: usize = 16;
+
+/// Instance data kept on the gpu between frames
+///
+/// The buffer is bound as a storage buffer, or as a vertex buffer where storage buffers aren't
+/// supported.
+///
+/// Instances are pushed in drawing order every frame, and each one keeps the slot it got last
+/// frame as long as the order stays the same. Only slots that changed, or now belong to a
+/// different instance, are written again, and only if their data differs from what's already on
+/// the gpu, so static scenes don't upload anything. With
+/// [`SmudInstanceUpload::Rebuild`], all instances are written and uploaded every frame instead.
+pub(crate) struct InstanceBuffer<V: Pod> {
+    values: Vec<V>,
+    /// The instance each slot was written for
+    keys: Vec<InstanceKey>,
+    len: usize,
+    buffer: Option<Buffer>,
+    /// Number of instances the gpu buffer has room for
+    capacity: usize,
+    /// Slots to upload in the next `write_buffer`, in increasing order
+    dirty: Vec<Range<usize>>,
+    /// Whether all slots are written this frame, regardless of changes
+    rebuild: bool,
+    label: &'static str,
+}
+
+impl<V: Pod> InstanceBuffer<V> {
+    pub(crate) fn new(label: &'static str) -> Self {
+        Self {
+            values: Vec::new(),
+            keys: Vec::new(),
+            len: 0,
+            buffer: None,
+            capacity: 0,
+            dirty: Vec::new(),
+            rebuild: false,
+            label,
+        }
+    }
+
+    /// Starts a new frame, keeping the previous contents to compare with when retained
+    pub(crate) fn clear(&mut self, upload: SmudInstanceUpload) {
+        self.len = 0;
+        self.rebuild = upload == SmudInstanceUpload::Rebuild;
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn buffer(&self) -> Option<&Buffer> {
+        self.buffer.as_ref()
+    }
+
+    /// Adds the next instance, only computing its data when the slot needs to be written
+    pub(crate) fn push(&mut self, key: InstanceKey, changed: bool, value: impl FnOnce() -> V) {
+        let index = self.len;
+        self.len += 1;
+
+        if index < self.values.len() {
+            if !changed && !self.rebuild && self.keys[index] == key {
+                return;
+            }
+            let value = value();
+            self.keys[index] = key;
+            if !self.rebuild
+                && bytemuck::bytes_of(&value) == bytemuck::bytes_of(&self.values[index])
+            {
+                return;
+            }
+            self.values[index] = value;
+        } else {
+            self.values.push(value());
+            self.keys.push(key);
+        }
+
+        if self.rebuild {
+            return;
+        }
+        match self.dirty.last_mut() {
+            Some(range) if index <= range.end + MERGE_GAP => range.end = index + 1,
+            _ => self.dirty.push(index..index + 1),
+        }
+    }
+
+    /// Uploads the changed slots, or everything if the gpu buffer needs to grow
+    pub(crate) fn write_buffer(&mut self, render_device: &RenderDevice, queue: &RenderQueue) {
+        self.values.truncate(self.len);
+        self.keys.truncate(self.len);
+
+        if self.rebuild {
+            self.dirty.clear();
+            self.dirty.push(0..self.len);
+        }
+
+        if self.len > self.capacity {
+            self.capacity = self.len.next_power_of_two();
+            self.buffer = Some(render_device.create_buffer(&BufferDescriptor {
+                label: Some(self.label),
+                size: (self.capacity * std::mem::size_of::<V>()) as u64,
+                usage: BufferUsages::VERTEX | BufferUsages::STORAGE | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }));
+            self.dirty.clear();
+            self.dirty.push(0..self.len);
+        }
+
+        let Some(buffer) = &self.buffer else {
+            return;
+        };
+        for range in self.dirty.drain(..) {
+            let end = range.end.min(self.len);
+            if range.start >= end {
+                continue;
+            }
+            queue.write_buffer(
+                buffer,
+                (range.start * std::mem::size_of::<V>()) as u64,
+                bytemuck::cast_slice(&self.values[range.start..end]),
+            );
+        }
+    }
+}
+
+/// Marks shapes as changed when components affecting their instance data are removed, which
+/// change detection doesn't catch
+///
+/// Without a polygon, polyline or csg component, the shape goes back to its own sdf and params,
+/// which have to be uploaded again.
+pub(crate) fn mark_removed_instance_data(
+    mut params: RemovedComponents<SmudParams>,
+    mut dashes: RemovedComponents<SmudDash>,
+    mut times: RemovedComponents<SmudTime>,
+    mut morphs: RemovedComponents<SmudMorph>,
+    mut lines: RemovedComponents<SmudLine>,
+    mut three_d: RemovedComponents<SmudShape3d>,
+    mut polygons: RemovedComponents<SmudPolygon>,
+    mut polylines: RemovedComponents<SmudPolyline>,
+    mut csgs: RemovedComponents<SmudCsg>,
+    mut shapes: Query<&mut SmudShape>,
+) {
+    let removed = params
+        .read()
+        .chain(dashes.read())
+        .chain(times.read())
+        .chain(morphs.read())
+        .chain(lines.read())
+        .chain(three_d.read())
+        .chain(polygons.read())
+        .chain(polylines.read())
+        .chain(csgs.read());
+    for entity in removed {
+        if let Ok(mut shape) = shapes.get_mut(entity) {
+            shape.set_changed();
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt::Write;
+
+use bevy::render::render_resource::{
+    VertexAttribute, VertexBufferLayout, VertexFormat, VertexStepMode,
+};
+use bytemuck::Pod;
+
+/// Types that can be fields of instance data
+pub(crate) trait InstanceAttribute {
+    const FORMAT: VertexFormat;
+}
+
+impl InstanceAttribute for f32 {
+    const FORMAT: VertexFormat = VertexFormat::Float32;
+}
+
+impl InstanceAttribute for [f32; 2] {
+    const FORMAT: VertexFormat = VertexFormat::Float32x2;
+}
+
+impl InstanceAttribute for [f32; 3] {
+    const FORMAT: VertexFormat = VertexFormat::Float32x3;
+}
+
+impl InstanceAttribute for [f32; 4] {
+    const FORMAT: VertexFormat = VertexFormat::Float32x4;
+}
+
+/// A field of instance data, as laid out in the Rust struct
+pub(crate) struct InstanceField {
+    pub(crate) name: &'static str,
+    pub(crate) format: VertexFormat,
+    /// Offset from the start of the struct in bytes
+    pub(crate) offset: usize,
+}
+
+/// Per-instance data of shapes, with a layout the shaders are generated from
+///
+/// Implemented with [`instance_data!`], so fields are only listed once.
+pub(crate) trait InstanceData: Pod {
+    const FIELDS: &'static [InstanceField];
+}
+
+/// Declares a struct of instance data, implementing [`InstanceData`] for it
+macro_rules! instance_data {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$field_meta:meta])* $field:ident: $ty:ty,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(C)]
+        #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+        $vis struct $name {
+            $($(#[$field_meta])* pub $field: $ty,)*
+        }
+
+        impl $crate::instance_layout::InstanceData for $name {
+            const FIELDS: &'static [$crate::instance_layout::InstanceField] = &[$(
+                $crate::instance_layout::InstanceField {
+                    name: stringify!($field),
+                    format: <$ty as $crate::instance_layout::InstanceAttribute>::FORMAT,
+                    offset: std::mem::offset_of!($name, $field),
+                },
+            )*];
+        }
+    };
+}
+pub(crate) use instance_data;
+
+/// The vertex buffer layout of the instance data, where each field has the location of its index
+pub(crate) fn vertex_buffer_layout<T: InstanceData>() -> VertexBufferLayout {
+    VertexBufferLayout {
+        array_stride: std::mem::size_of::<T>() as u64,
+        step_mode: VertexStepMode::Instance,
+        attributes: T::FIELDS
+            .iter()
+            .enumerate()
+            .map(|(location, field)| VertexAttribute {
+                format: field.format,
+                offset: field.offset as u64,
+                shader_location: location as u32,
+            })
+            .collect(),
+    }
+}
+
+fn component_count(format: VertexFormat) -> usize {
+    match format {
+        VertexFormat::Float32 => 1,
+        VertexFormat::Float32x2 => 2,
+        VertexFormat::Float32x3 => 3,
+        VertexFormat::Float32x4 => 4,
+        _ => unreachable!("not an instance attribute format"),
+    }
+}
+
+fn wgsl_type(format: VertexFormat) -> String {
+    match component_count(format) {
+        1 => "f32".into(),
+        n => format!("vec{n}<f32>"),
+    }
+}
+
+/// Generates a shader module with an `Instance` struct matching the instance data
+///
+/// The struct can be used as vertex input directly. With `SMUD_STORAGE_INSTANCES`, `load` reads
+/// instances from a storage buffer in bind group `SMUD_INSTANCE_GROUP` instead, and with
+/// `SMUD_INSTANCE_WRITER`, `store` writes them from compute shaders. The buffers are arrays of
+/// floats, so the Rust layout can be packed tighter than wgsl structs allow.
+pub(crate) fn instance_module<T: InstanceData>(import_path: &str) -> String {
+    let stride = std::mem::size_of::<T>() / 4;
+    // Indices of the floats of each field in the buffer, relative to the instance
+    let floats = |field: &InstanceField| {
+        let start = field.offset / 4;
+        start..start + component_count(field.format)
+    };
+    let access = |field: &InstanceField, component: usize| {
+        if component_count(field.format) == 1 {
+            field.name.to_string()
+        } else {
+            format!("{}[{}]", field.name, component)
+        }
+    };
+
+    let mut source = format!(
+        "#define_import_path {import_path}\n\n\
+        // Generated from the Rust struct of the instance data, see `instance_data!`\n\
+        struct Instance {{\n"
+    );
+    for (location, field) in T::FIELDS.iter().enumerate() {
+        let ty = wgsl_type(field.format);
+        writeln!(source, "    @location({location}) {}: {ty},", field.name).unwrap();
+    }
+    source.push_str("};\n\n");
+
+    write!(
+        source,
+        "#ifdef SMUD_STORAGE_INSTANCES\n\
+        @group(#{{SMUD_INSTANCE_GROUP}}) @binding(0)\n\
+        var<storage, read> instances: array<f32>;\n\n\
+        fn load(index: u32) -> Instance {{\n    \
+            let i = index * {stride}u;\n    \
+            var instance: Instance;\n"
+    )
+    .unwrap();
+    for field in T::FIELDS {
+        let values: Vec<String> = floats(field)
+            .map(|float| format!("instances[i + {float}u]"))
+            .collect();
+        let value = match values.len() {
+            1 => values[0].clone(),
+            _ => format!("{}({})", wgsl_type(field.format), values.join(", ")),
+        };
+        writeln!(source, "    instance.{} = {value};", field.name).unwrap();
+    }
+    source.push_str("    return instance;\n}\n");
+
+    write!(
+        source,
+        "#else ifdef SMUD_INSTANCE_WRITER\n\
+        @group(0) @binding(2)\n\
+        var<storage, read_write> instances: array<f32>;\n\n\
+        fn store(index: u32, instance: Instance) {{\n    \
+            let i = index * {stride}u;\n"
+    )
+    .unwrap();
+    for field in T::FIELDS {
+        for (component, float) in floats(field).enumerate() {
+            writeln!(
+                source,
+                "    instances[i + {float}u] = instance.{};",
+                access(field, component)
+            )
+            .unwrap();
+        }
+    }
+    source.push_str("}\n#endif\n");
+
+    source
+}
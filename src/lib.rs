@@ -2,12 +2,15 @@
 #![doc = include_str!("../README.md")]
 #![allow(clippy::too_many_arguments)]
 
-use std::ops::Range;
+use std::{ops::Range, sync::Once};
 
 use bevy::{
-    core_pipeline::core_2d::Transparent2d,
+    core_pipeline::{
+        core_2d::Transparent2d,
+        core_3d::{Transparent3d, CORE_3D_DEPTH_FORMAT},
+    },
     ecs::{
-        query::ROQueryItem,
+        query::{Has, ROQueryItem},
         system::{
             lifetimeless::{Read, SRes},
             SystemParamItem,
@@ -16,45 +19,104 @@ use bevy::{
     math::Vec3Swizzles,
     prelude::*,
     render::{
+        extract_component::ExtractComponentPlugin,
+        extract_resource::ExtractResourcePlugin,
         globals::{GlobalsBuffer, GlobalsUniform},
+        render_asset::RenderAssets,
         render_phase::{
             AddRenderCommand, DrawFunctions, PhaseItem, RenderCommand, RenderCommandResult,
             RenderPhase, SetItemPipeline, TrackedRenderPass,
         },
         render_resource::{
             BindGroup, BindGroupEntries, BindGroupLayout, BindGroupLayoutDescriptor,
-            BindGroupLayoutEntry, BindingType, BlendState, BufferBindingType, BufferUsages,
-            BufferVec, CachedRenderPipelineId, ColorTargetState, ColorWrites, Face, FragmentState,
-            FrontFace, MultisampleState, PipelineCache, PolygonMode, PrimitiveState,
-            PrimitiveTopology, RenderPipelineDescriptor, ShaderImport, ShaderStages, ShaderType,
-            SpecializedRenderPipeline, SpecializedRenderPipelines, TextureFormat, VertexAttribute,
-            VertexBufferLayout, VertexFormat, VertexState, VertexStepMode,
+            BindGroupLayoutEntry, BindingType, BlendState, Buffer, BufferBindingType, BufferUsages,
+            BufferVec, CachedRenderPipelineId, ColorTargetState, ColorWrites, CompareFunction,
+            DepthBiasState, DepthStencilState, Face, FragmentState, FrontFace, MultisampleState,
+            PipelineCache, PolygonMode, PrimitiveState, PrimitiveTopology,
+            RenderPipelineDescriptor, SamplerBindingType, ShaderDefVal, ShaderImport, ShaderStages,
+            ShaderType, SpecializedRenderPipeline, SpecializedRenderPipelines, StencilState,
+            TextureFormat, TextureSampleType, TextureViewDimension, VertexBufferLayout,
+            VertexState,
         },
         renderer::{RenderDevice, RenderQueue},
-        texture::BevyDefault,
+        texture::{BevyDefault, Image},
         view::{
             ExtractedView, ViewTarget, ViewUniform, ViewUniformOffset, ViewUniforms,
             VisibleEntities,
         },
         Extract, MainWorld, Render, RenderApp, RenderSet,
     },
-    utils::{EntityHashMap, FloatOrd, HashMap},
+    utils::{EntityHashMap, FloatOrd, HashMap, HashSet},
 };
-use bytemuck::{Pod, Zeroable};
+use bytemuck::Pod;
 use fixedbitset::FixedBitSet;
 use shader_loading::*;
 // use ui::UiShapePlugin;
 
 pub use bundle::ShapeBundle;
 pub use components::*;
-pub use shader_loading::{DEFAULT_FILL_HANDLE, SIMPLE_FILL_HANDLE};
-
-use crate::util::generate_shader_id;
+pub use csg::SmudCsg;
+pub use line::SmudLine;
+pub use painter::{SmudPainter, SmudPainterConfig};
+pub use particles::SmudParticles;
+pub use path::{PathCommand, SmudPath, StrokeCap, StrokeJoin};
+pub use pixel_art::SmudPixelArt;
+pub use polygon::{FillRule, SmudPolygon, SmudPolyline};
+pub use raster::{CpuFill, CpuSdf, RasterizeError, SmudRasterizer};
+pub use sdf_assets::{SdfBodyLoader, SdfBodyLoaderError};
+pub use sdf_builder::{CsgOp, SmudSdf};
+pub use settings::{SmudAntiAliasing, SmudInstanceUpload, SmudSettings};
+pub use shader_loading::{DASHED_FILL_HANDLE, DEFAULT_FILL_HANDLE, SIMPLE_FILL_HANDLE};
+pub use shape_3d::SmudShape3d;
+#[cfg(feature = "ron")]
+pub use shape_asset::{SmudShapeAsset, SmudShapeLoader, SmudShapeLoaderError};
+pub use shape_source::{SmudShaderSource, SmudShapeSource};
+#[cfg(feature = "svg")]
+pub use svg::{SmudSvg, SmudSvgError, SvgGeometry, SvgShape};
+#[cfg(feature = "text")]
+pub use text::{
+    SmudFont, SmudFontLoader, SmudFontLoaderError, SmudText, SmudTextAlignment, SmudTextBundle,
+};
+pub use time::SmudTime;
+pub use tween::{SmudTween, TweenEasing, TweenRepeat};
+
+use crate::{
+    instance_buffer::{InstanceBuffer, InstanceKey},
+    line::LINE_PADDING,
+    time::instance_time,
+    util::generate_shader_id,
+};
 
 mod bundle;
 mod components;
+mod csg;
+mod instance_buffer;
+mod instance_layout;
+mod line;
+#[cfg(feature = "text")]
+mod msdf;
+mod painter;
+mod particles;
+mod path;
+mod pixel_art;
+mod polygon;
+mod raster;
+pub mod sdf;
 mod sdf_assets;
+mod sdf_builder;
+mod settings;
 mod shader_loading;
+mod shape_3d;
+#[cfg(feature = "ron")]
+mod shape_asset;
+mod shape_source;
+#[cfg(feature = "svg")]
+mod svg;
+mod svg_export;
+#[cfg(feature = "text")]
+mod text;
+mod time;
+mod tween;
 mod util;
 // mod ui;
 
@@ -67,14 +129,37 @@ mod util;
 pub mod prelude {
     pub use crate::{
         sdf_assets::SdfAssets,
+        Anchor,
         Frame,
         ShapeBundle,
+        SmudCsg,
+        SmudDash,
+        SmudLine,
+        SmudMorph,
+        SmudPainter,
+        SmudParams,
+        SmudParticles,
+        SmudPath,
         SmudPlugin,
+        SmudPolygon,
+        SmudPolyline,
+        SmudScreenSpace,
+        SmudSdf,
         SmudShape,
+        SmudShape3d,
+        SmudShapeSource,
+        SmudTime,
+        SmudTween,
         // UiShapeBundle,
+        DASHED_FILL_HANDLE,
         DEFAULT_FILL_HANDLE,
         SIMPLE_FILL_HANDLE,
     };
+
+    #[cfg(feature = "ron")]
+    pub use crate::SmudShapeAsset;
+    #[cfg(feature = "text")]
+    pub use crate::{SmudText, SmudTextBundle};
 }
 
 #[derive(Default)]
@@ -85,35 +170,114 @@ impl Plugin for SmudPlugin {
     fn build(&self, app: &mut App) {
         // All the messy boiler-plate for loading a bunch of shaders
         app.add_plugins(ShaderLoadingPlugin);
+
+        app.init_resource::<SmudSettings>().add_plugins((
+            ExtractResourcePlugin::<SmudSettings>::default(),
+            ExtractComponentPlugin::<SmudPixelArt>::default(),
+        ));
         // app.add_plugins(UiShapePlugin);
 
         if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
             render_app
                 .add_render_command::<Transparent2d, DrawSmudShape>()
-                .init_resource::<ExtractedShapes>()
+                .add_render_command::<Transparent3d, DrawSmudShape>()
                 .init_resource::<ShapeMeta>()
                 .init_resource::<SpecializedRenderPipelines<SmudPipeline>>()
-                .add_systems(ExtractSchedule, (extract_shapes, extract_sdf_shaders))
+                .add_systems(
+                    ExtractSchedule,
+                    (
+                        extract_shapes,
+                        path::extract_paths.after(extract_shapes),
+                        painter::extract_painted_shapes.after(extract_shapes),
+                        csg::extract_csgs.after(extract_shapes),
+                        extract_sdf_shaders,
+                    ),
+                )
                 .add_systems(
                     Render,
                     (
                         queue_shapes.in_set(RenderSet::Queue),
+                        shape_3d::queue_shapes_3d.in_set(RenderSet::Queue),
                         prepare_shapes.in_set(RenderSet::PrepareBindGroups),
                     ),
                 );
         }
 
-        app.register_type::<SmudShape>();
+        #[cfg(feature = "text")]
+        {
+            app.init_asset::<SmudFont>()
+                .init_asset_loader::<SmudFontLoader>()
+                .register_type::<SmudText>();
+
+            if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+                render_app.add_systems(ExtractSchedule, text::extract_texts.after(extract_shapes));
+            }
+        }
+
+        #[cfg(feature = "ron")]
+        app.init_asset::<SmudShapeAsset>()
+            .init_asset_loader::<SmudShapeLoader>()
+            .add_systems(Update, shape_asset::resolve_shape_assets);
+
+        app.init_asset_loader::<SdfBodyLoader>();
+
+        app.init_resource::<shape_source::SourceShaders>();
+
+        app.init_resource::<SmudPainterConfig>()
+            .init_resource::<painter::PaintedShapes>()
+            .add_systems(First, painter::clear_painted_shapes);
+
+        app.add_systems(Last, instance_buffer::mark_removed_instance_data);
+
+        app.add_systems(
+            Update,
+            (
+                tween::animate_tweens,
+                time::advance_shape_times,
+                shape_source::resolve_shape_sources,
+            ),
+        );
+
+        app.register_type::<SmudShape>()
+            .register_type::<SmudShape3d>()
+            .register_type::<Frame>()
+            .register_type::<Anchor>()
+            .register_type::<SmudTween>()
+            .register_type::<SmudTime>()
+            .register_type::<SmudMorph>()
+            .register_type::<SmudCsg>()
+            .register_type::<CsgOp>()
+            .register_type::<SmudParams>()
+            .register_type::<SmudDash>()
+            .register_type::<SmudScreenSpace>()
+            .register_type::<SmudSettings>()
+            .register_type::<SmudAntiAliasing>()
+            .register_type::<SmudInstanceUpload>()
+            .register_type::<SmudPixelArt>()
+            .register_type::<SmudShaderSource>()
+            .register_type::<SmudShapeSource>()
+            .register_type::<SmudPolygon>()
+            .register_type::<SmudPolyline>()
+            .register_type::<SmudLine>()
+            .register_type::<SmudParticles>()
+            .register_type::<SmudPath>();
     }
 
     fn finish(&self, app: &mut App) {
-        app.get_sub_app_mut(RenderApp)
-            .unwrap()
-            .init_resource::<SmudPipeline>();
+        let render_app = app.get_sub_app_mut(RenderApp).unwrap();
+        render_app
+            .init_resource::<SmudPipeline>()
+            .init_resource::<ExtractedShapes>();
+        particles::add_render_systems(render_app);
     }
 }
 
-type DrawSmudShape = (SetItemPipeline, SetShapeViewBindGroup<0>, DrawShapeBatch);
+type DrawSmudShape = (
+    SetItemPipeline,
+    SetShapeViewBindGroup<0>,
+    SetShapeDataBindGroup<1>,
+    DrawShapeBatch,
+);
 
 struct SetShapeViewBindGroup<const I: usize>;
 impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetShapeViewBindGroup<I> {
@@ -137,9 +301,39 @@ impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetShapeViewBindGroup<I>
     }
 }
 
+struct SetShapeDataBindGroup<const I: usize>;
+impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetShapeDataBindGroup<I> {
+    type Param = (SRes<ShapeMeta>, SRes<SmudPipeline>);
+    type ViewWorldQuery = ();
+    type ItemWorldQuery = Read<ShapeBatch>;
+
+    fn render<'w>(
+        _item: &P,
+        _view: (),
+        batch: &'_ ShapeBatch,
+        (shape_meta, pipeline): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let shape_meta = shape_meta.into_inner();
+        let bind_group = if let Some(atlas) = batch.atlas {
+            shape_meta.atlas_bind_groups.get(&atlas)
+        } else if pipeline.uses_points(batch.shader.0) {
+            shape_meta.points_bind_group.as_ref()
+        } else {
+            // Regular shapes don't have any extra data
+            return RenderCommandResult::Success;
+        };
+        let Some(bind_group) = bind_group else {
+            return RenderCommandResult::Failure;
+        };
+        pass.set_bind_group(I, bind_group, &[]);
+        RenderCommandResult::Success
+    }
+}
+
 struct DrawShapeBatch;
 impl<P: PhaseItem> RenderCommand<P> for DrawShapeBatch {
-    type Param = SRes<ShapeMeta>;
+    type Param = (SRes<ShapeMeta>, SRes<SmudPipeline>);
     type ViewWorldQuery = ();
     type ItemWorldQuery = Read<ShapeBatch>;
 
@@ -147,11 +341,25 @@ impl<P: PhaseItem> RenderCommand<P> for DrawShapeBatch {
         _item: &P,
         _view: (),
         batch: &'_ ShapeBatch,
-        shape_meta: SystemParamItem<'w, '_, Self::Param>,
+        (shape_meta, pipeline): SystemParamItem<'w, '_, Self::Param>,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
         let shape_meta = shape_meta.into_inner();
-        pass.set_vertex_buffer(0, shape_meta.vertices.buffer().unwrap().slice(..));
+        if let Some(group) = pipeline.instance_group(batch.shader.0) {
+            let bind_group = if batch.three_d {
+                shape_meta.instance_bind_group_3d.as_ref()
+            } else {
+                shape_meta.instance_bind_group.as_ref()
+            };
+            pass.set_bind_group(group as usize, bind_group.unwrap(), &[]);
+        } else {
+            let buffer = if batch.three_d {
+                shape_meta.vertices_3d.buffer()
+            } else {
+                shape_meta.vertices.buffer()
+            };
+            pass.set_vertex_buffer(0, buffer.unwrap().slice(..));
+        }
         pass.draw(0..4, batch.range.clone());
         RenderCommandResult::Success
     }
@@ -160,12 +368,27 @@ impl<P: PhaseItem> RenderCommand<P> for DrawShapeBatch {
 #[derive(Resource)]
 struct SmudPipeline {
     view_layout: BindGroupLayout,
+    atlas_layout: BindGroupLayout,
+    /// The point buffer of polygons, polylines, paths and csg shapes, when storage buffers are
+    /// supported
+    points_layout: Option<BindGroupLayout>,
+    /// Instance data read by the vertex shader, when storage buffers are supported
+    ///
+    /// Otherwise, e.g. on WebGL2, the instance data is read from vertex buffers instead.
+    instance_layout: Option<BindGroupLayout>,
     shaders: ShapeShaders,
+    /// Generated sdfs morphing between two other sdfs
+    morphs: HashMap<(AssetId<Shader>, AssetId<Shader>), Handle<Shader>>,
+    /// Generated sdfs combining the parts of `SmudCsg` shapes
+    csgs: HashMap<csg::CsgKey, Handle<Shader>>,
+    /// Ids of the generated csg sdfs, which read their part transforms from the point buffer
+    csg_sdfs: HashSet<AssetId<Shader>>,
 }
 
 impl FromWorld for SmudPipeline {
     fn from_world(world: &mut World) -> Self {
         let render_device = world.get_resource::<RenderDevice>().unwrap();
+        let storage_buffers = render_device.limits().max_storage_buffers_per_shader_stage > 0;
 
         let view_layout = render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
             entries: &[
@@ -193,9 +416,69 @@ impl FromWorld for SmudPipeline {
             label: Some("shape_view_layout"),
         });
 
+        let atlas_layout = render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        multisampled: false,
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("shape_atlas_layout"),
+        });
+
+        let points_layout = storage_buffers.then(|| {
+            render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+                entries: &[BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("shape_points_layout"),
+            })
+        });
+
+        let instance_layout = storage_buffers.then(|| {
+            render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+                entries: &[BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::VERTEX,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("shape_instance_layout"),
+            })
+        });
+
         Self {
             view_layout,
+            atlas_layout,
+            points_layout,
+            instance_layout,
             shaders: default(),
+            morphs: default(),
+            csgs: default(),
+            csg_sdfs: default(),
         }
     }
 }
@@ -205,6 +488,11 @@ struct SmudPipelineKey {
     mesh: PipelineKey,
     shader: (AssetId<Shader>, AssetId<Shader>),
     hdr: bool,
+    three_d: Option<SmudShape3d>,
+    screen_space: bool,
+    anti_aliasing: SmudAntiAliasing,
+    /// The size of art pixels in screen pixels, if the view renders pixel art
+    pixel_art: Option<u32>,
 }
 
 impl SpecializedRenderPipeline for SmudPipeline {
@@ -214,59 +502,61 @@ impl SpecializedRenderPipeline for SmudPipeline {
         let shader = self.shaders.0.get(&key.shader).unwrap();
         debug!("specializing for {shader:?}");
 
-        // Customize how to store the meshes' vertex attributes in the vertex buffer
-        // Our meshes only have position and color
-        let vertex_attributes = vec![
-            // (GOTCHA! attributes are sorted alphabetically, and offsets need to reflect this)
-            // Color
-            VertexAttribute {
-                format: VertexFormat::Float32x4,
-                offset: 0,
-                shader_location: 1,
-            },
-            // Frame
-            VertexAttribute {
-                format: VertexFormat::Float32,
-                offset: (4) * 4,
-                shader_location: 4,
-            },
-            // Position
-            VertexAttribute {
-                format: VertexFormat::Float32x3,
-                offset: (4 + 1) * 4,
-                shader_location: 0,
-            },
-            // Rotation
-            VertexAttribute {
-                format: VertexFormat::Float32x2,
-                offset: (4 + 1 + 3) * 4,
-                shader_location: 2,
-            },
-            // Scale
-            VertexAttribute {
-                format: VertexFormat::Float32,
-                offset: (4 + 1 + 3 + 2) * 4,
-                shader_location: 3,
-            },
-        ];
-        // This is the sum of the size of the attributes above
-        let vertex_array_stride = (4 + 1 + 3 + 2 + 1) * 4;
+        let mut vertex_defs = Vec::new();
+        if key.screen_space {
+            vertex_defs.push("SCREEN_SPACE".into());
+        }
 
-        RenderPipelineDescriptor {
-            vertex: VertexState {
+        // Pixel art replaces the anti-aliasing of the view
+        let shader_defs = match key.pixel_art {
+            Some(pixel_scale) => vec![
+                "SMUD_PIXEL_ART".into(),
+                ShaderDefVal::UInt("SMUD_PIXEL_SCALE".into(), pixel_scale),
+            ],
+            None => key
+                .anti_aliasing
+                .shader_def()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        };
+
+        if let Some(group) = self.instance_group(key.shader.0) {
+            vertex_defs.push("SMUD_STORAGE_INSTANCES".into());
+            vertex_defs.push(ShaderDefVal::UInt("SMUD_INSTANCE_GROUP".into(), group));
+        }
+
+        let vertex = match key.three_d {
+            None => VertexState {
                 shader: VERTEX_SHADER_HANDLE,
                 entry_point: "vertex".into(),
-                shader_defs: Vec::new(),
-                buffers: vec![VertexBufferLayout {
-                    array_stride: vertex_array_stride,
-                    step_mode: VertexStepMode::Instance,
-                    attributes: vertex_attributes,
-                }],
+                shader_defs: vertex_defs,
+                buffers: self.instance_buffers::<ShapeVertex>(),
             },
+            Some(three_d) => shape_3d::vertex_state(three_d, vertex_defs, self),
+        };
+
+        let mut layout = vec![
+            // Bind group 0 is the view uniform
+            self.view_layout.clone(),
+        ];
+
+        // Bind group 1 is the glyph atlas for text, or the point buffer for polygons
+        if key.shader.0 == TEXT_SDF_HANDLE.id() {
+            layout.push(self.atlas_layout.clone());
+        } else if self.uses_points(key.shader.0) {
+            layout.extend(self.points_layout.clone());
+        }
+
+        // The instance data comes last, since the groups before it depend on the sdf
+        layout.extend(self.instance_layout.clone());
+
+        RenderPipelineDescriptor {
+            vertex,
             fragment: Some(FragmentState {
                 shader: shader.clone_weak(),
                 entry_point: "fragment".into(),
-                shader_defs: Vec::new(),
+                shader_defs,
                 targets: vec![Some(ColorTargetState {
                     format: if key.hdr {
                         ViewTarget::TEXTURE_FORMAT_HDR
@@ -277,20 +567,25 @@ impl SpecializedRenderPipeline for SmudPipeline {
                     write_mask: ColorWrites::ALL,
                 })],
             }),
-            layout: vec![
-                // Bind group 0 is the view uniform
-                self.view_layout.clone(),
-            ],
+            layout,
             primitive: PrimitiveState {
                 front_face: FrontFace::Ccw,
-                cull_mode: Some(Face::Back),
+                // Planes are visible from both sides
+                cull_mode: key.three_d.is_none().then_some(Face::Back),
                 unclipped_depth: false, // What is this?
                 polygon_mode: PolygonMode::Fill,
                 conservative: false, // What is this?
                 topology: key.mesh.primitive_topology(),
                 strip_index_format: None, // TODO: what does this do?
             },
-            depth_stencil: None,
+            // Shapes are tested against the depth of opaque 3d geometry, but don't write depth
+            depth_stencil: key.three_d.map(|_| DepthStencilState {
+                format: CORE_3D_DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::GreaterEqual,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
             multisample: MultisampleState {
                 count: key.mesh.msaa_samples(),
                 mask: !0,                         // what does the mask do?
@@ -302,6 +597,58 @@ impl SpecializedRenderPipeline for SmudPipeline {
     }
 }
 
+impl SmudPipeline {
+    /// Whether the sdf reads its points from the shared point buffer
+    fn uses_points(&self, sdf: AssetId<Shader>) -> bool {
+        sdf == POLYGON_SDF_HANDLE.id()
+            || sdf == POLYLINE_SDF_HANDLE.id()
+            || sdf == PATH_SDF_HANDLE.id()
+            || self.csg_sdfs.contains(&sdf)
+    }
+
+    /// The vertex buffers of the pipeline, none when instances are read from storage buffers
+    fn instance_buffers<T: instance_layout::InstanceData>(&self) -> Vec<VertexBufferLayout> {
+        if self.instance_layout.is_some() {
+            Vec::new()
+        } else {
+            vec![instance_layout::vertex_buffer_layout::<T>()]
+        }
+    }
+
+    /// The bind group of the instance storage buffer, after the extra data of the sdf
+    ///
+    /// `None` when the instances are read from vertex buffers instead.
+    fn instance_group(&self, sdf: AssetId<Shader>) -> Option<u32> {
+        self.instance_layout.as_ref()?;
+        if sdf == TEXT_SDF_HANDLE.id() || self.uses_points(sdf) {
+            Some(2)
+        } else {
+            Some(1)
+        }
+    }
+}
+
+/// The sdf used for a shape, polygons, polylines, lines and paths replace the one on the shape
+fn shape_sdf(
+    shape: &SmudShape,
+    polygon: bool,
+    polyline: bool,
+    line: bool,
+    path: bool,
+) -> Handle<Shader> {
+    if path {
+        PATH_SDF_HANDLE
+    } else if polygon {
+        POLYGON_SDF_HANDLE
+    } else if polyline {
+        POLYLINE_SDF_HANDLE
+    } else if line {
+        LINE_SDF_HANDLE
+    } else {
+        shape.sdf.clone_weak()
+    }
+}
+
 #[derive(Default)]
 struct ShapeShaders(HashMap<(AssetId<Shader>, AssetId<Shader>), Handle<Shader>>);
 
@@ -309,45 +656,81 @@ struct ShapeShaders(HashMap<(AssetId<Shader>, AssetId<Shader>), Handle<Shader>>)
 // reference to MainWorld.
 fn extract_sdf_shaders(mut main_world: ResMut<MainWorld>, mut pipeline: ResMut<SmudPipeline>) {
     main_world.resource_scope(|world, mut shaders: Mut<Assets<Shader>>| {
-        let mut shapes = world.query::<&SmudShape>();
+        let mut shapes = world.query_filtered::<(
+            &SmudShape,
+            Option<&SmudMorph>,
+            Has<SmudPolygon>,
+            Has<SmudPolyline>,
+            Has<SmudLine>,
+            Has<SmudPath>,
+        ), Without<SmudCsg>>();
+        #[cfg(feature = "text")]
+        let mut texts = world.query::<&SmudText>();
+        let mut csgs = world.query::<(&SmudCsg, &SmudShape, Option<&Children>)>();
+        let mut parts = world.query::<(&SmudShape, &Transform)>();
+
+        let mut shader_pairs = Vec::new();
+        for (shape, morph, polygon, polyline, line, path) in shapes.iter(world) {
+            let sdf = match morph {
+                Some(morph) if !(polygon || polyline || line || path) => {
+                    let Some(sdf) = morph_sdf(
+                        &mut pipeline.morphs,
+                        &mut shaders,
+                        &shape.sdf,
+                        &morph.target,
+                    ) else {
+                        debug!("Waiting for morph sdfs to load");
+                        continue;
+                    };
+                    sdf
+                }
+                _ => shape_sdf(shape, polygon, polyline, line, path),
+            };
+            shader_pairs.push((sdf, shape.fill.clone_weak()));
+        }
+        #[cfg(feature = "text")]
+        shader_pairs.extend(
+            texts
+                .iter(world)
+                .map(|text| (TEXT_SDF_HANDLE, text.fill.clone_weak())),
+        );
+        for (csg, shape, children) in csgs.iter(world) {
+            let sdfs: Vec<Handle<Shader>> =
+                csg::csg_parts(children, |child| parts.get(world, child).ok())
+                    .iter()
+                    .map(|(part, _)| part.sdf.clone_weak())
+                    .collect();
+            if sdfs.is_empty() {
+                continue;
+            }
+            let Some(sdf) = csg::csg_sdf(&mut pipeline, &mut shaders, csg.op, &sdfs) else {
+                debug!("Waiting for csg sdfs to load");
+                continue;
+            };
+            shader_pairs.push((sdf, shape.fill.clone_weak()));
+        }
+        shader_pairs.extend(
+            world
+                .resource::<painter::PaintedShapes>()
+                .shapes()
+                .map(|shape| (shape.sdf.clone_weak(), shape.fill.clone_weak())),
+        );
 
-        for shape in shapes.iter(world) {
-            let shader_key = (shape.sdf.id(), shape.fill.id());
+        for (sdf, fill) in shader_pairs {
+            let shader_key = (sdf.id(), fill.id());
             if pipeline.shaders.0.contains_key(&shader_key) {
                 continue;
             }
 
             // todo use asset events instead?
-            let sdf_import_path = match shaders.get_mut(&shape.sdf.clone()) {
-                Some(shader) => match shader.import_path() {
-                    ShaderImport::Custom(p) => p.to_owned(),
-                    _ => {
-                        let id = generate_shader_id();
-                        let path = format!("smud::generated::{id}");
-                        shader.set_import_path(&path);
-                        path
-                    }
-                },
-                None => {
-                    debug!("Waiting for sdf to load");
-                    continue;
-                }
+            let Some(sdf_import_path) = import_path(&mut shaders, &sdf) else {
+                debug!("Waiting for sdf to load");
+                continue;
             };
 
-            let fill_import_path = match shaders.get_mut(&shape.fill.clone()) {
-                Some(shader) => match shader.import_path() {
-                    ShaderImport::Custom(p) => p.to_owned(),
-                    _ => {
-                        let id = generate_shader_id();
-                        let path = format!("smud::generated::{id}");
-                        shader.set_import_path(&path);
-                        path
-                    }
-                },
-                None => {
-                    debug!("Waiting for fill to load");
-                    continue;
-                }
+            let Some(fill_import_path) = import_path(&mut shaders, &fill) else {
+                debug!("Waiting for fill to load");
+                continue;
             };
 
             debug!("Generating shader");
@@ -357,18 +740,63 @@ fn extract_sdf_shaders(mut main_world: ResMut<MainWorld>, mut pipeline: ResMut<S
 #import bevy_render::globals::Globals
 @group(0) @binding(1)
 var<uniform> globals: Globals;
+#import smud::instance
 #import {sdf_import_path} as sdf
 #import {fill_import_path} as fill
 
 struct FragmentInput {{
+    @builtin(position) position: vec4<f32>,
     @location(0) color: vec4<f32>,
     @location(1) pos: vec2<f32>,
+    @location(2) params: vec4<f32>,
+    @location(3) frame: f32,
+    @location(4) time: f32,
+    @location(5) dash: vec4<f32>,
 }};
 
+fn shade(p: vec2<f32>, color: vec4<f32>) -> vec4<f32> {{
+    instance::coverage = 1.;
+    instance::pos = p;
+    instance::contour = vec2<f32>(0.);
+    let d = sdf::sdf(p);
+    let fill_color = fill::fill(d, color);
+    return vec4<f32>(fill_color.rgb, fill_color.a * instance::coverage);
+}}
+
 @fragment
 fn fragment(in: FragmentInput) -> @location(0) vec4<f32> {{
-    let d = sdf::sdf(in.pos);
-    return fill::fill(d, in.color);
+    instance::frame = in.frame;
+    instance::params = in.params;
+    instance::time = in.time;
+    instance::dash = in.dash;
+#ifdef SMUD_PIXEL_ART
+    // Sample the center of the art pixel, so all screen pixels in it get the same color
+    let cell = f32(#{{SMUD_PIXEL_SCALE}});
+    let offset = (floor(in.position.xy / cell) + 0.5) * cell - in.position.xy;
+    return shade(in.pos + offset.x * dpdx(in.pos) + offset.y * dpdy(in.pos), in.color);
+#else ifdef SMUD_AA_SUPERSAMPLED
+    // Rotated grid of samples within the pixel, blended with premultiplied alpha
+    let dx = dpdx(in.pos);
+    let dy = dpdy(in.pos);
+    let offsets = array<vec2<f32>, 4>(
+        vec2<f32>(-0.125, -0.375),
+        vec2<f32>(0.375, -0.125),
+        vec2<f32>(0.125, 0.375),
+        vec2<f32>(-0.375, 0.125),
+    );
+    var sum = vec4<f32>(0.);
+    for (var i = 0; i < 4; i++) {{
+        let offset = offsets[i];
+        let sample = shade(in.pos + offset.x * dx + offset.y * dy, in.color);
+        sum += vec4<f32>(sample.rgb * sample.a, sample.a);
+    }}
+    return vec4<f32>(sum.rgb / max(sum.a, 1e-5), sum.a / 4.);
+#else ifdef SMUD_AA_NEAREST
+    // Sample the center of the sdf unit, like nearest sampling of a texture
+    return shade(floor(in.pos) + 0.5, in.color);
+#else
+    return shade(in.pos, in.color);
+#endif
 }}
 "#
                 ),
@@ -386,46 +814,350 @@ fn fragment(in: FragmentInput) -> @location(0) vec4<f32> {{
     });
 }
 
+/// The import path of a shader, assigning a generated one if it doesn't have any
+///
+/// Returns `None` if the shader isn't loaded yet.
+fn import_path(shaders: &mut Assets<Shader>, handle: &Handle<Shader>) -> Option<String> {
+    let shader = shaders.get_mut(handle)?;
+    Some(match shader.import_path() {
+        ShaderImport::Custom(p) => p.to_owned(),
+        _ => {
+            let id = generate_shader_id();
+            let path = format!("smud::generated::{id}");
+            shader.set_import_path(&path);
+            path
+        }
+    })
+}
+
+/// Gets or generates an sdf module blending between two sdfs using the first instance param
+///
+/// Returns `None` if any of the sdfs isn't loaded yet.
+fn morph_sdf(
+    morphs: &mut HashMap<(AssetId<Shader>, AssetId<Shader>), Handle<Shader>>,
+    shaders: &mut Assets<Shader>,
+    from: &Handle<Shader>,
+    to: &Handle<Shader>,
+) -> Option<Handle<Shader>> {
+    let key = (from.id(), to.id());
+    if let Some(morph) = morphs.get(&key) {
+        return Some(morph.clone_weak());
+    }
+
+    let from_import_path = import_path(shaders, from)?;
+    let to_import_path = import_path(shaders, to)?;
+
+    debug!("Generating morph sdf");
+    let id = generate_shader_id();
+    let morph = shaders.add(Shader::from_wgsl(
+        format!(
+            r#"
+#define_import_path smud::generated::morph_{id}
+
+#import smud::instance
+#import {from_import_path} as from
+#import {to_import_path} as to
+
+fn sdf(p: vec2<f32>) -> f32 {{
+    return mix(from::sdf(p), to::sdf(p), instance::params.x);
+}}
+"#
+        ),
+        format!("smud::generated::morph::{key:?}"),
+    ));
+    let weak = morph.clone_weak();
+    morphs.insert(key, morph);
+    Some(weak)
+}
+
 #[derive(Component, Clone, Debug)]
-struct ExtractedShape {
+pub(crate) struct ExtractedShape {
     color: Color,
     frame: f32,
+    params: Vec4,
+    /// (length, gap, phase, width) of the dashes, see [`SmudDash`]
+    dash: Vec4,
+    time: Option<f32>,
     sdf_shader: Handle<Shader>,
     fill_shader: Handle<Shader>,
     transform: GlobalTransform,
+    atlas: Option<AssetId<Image>>,
+    /// How the shape is drawn with 3d cameras, 2d cameras only draw shapes without it
+    three_d: Option<SmudShape3d>,
+    /// Offset of the quad from the transform, in half frames
+    anchor: Vec2,
+    /// Whether the frame and sdf are measured in pixels, see [`SmudScreenSpace`]
+    screen_space: bool,
+    /// Whether the instance data may differ from last frame, and needs to be uploaded again
+    changed: bool,
+    /// Identifies the shape across frames, to find the instance data uploaded for it last frame
+    key: InstanceKey,
 }
 
-#[derive(Resource, Default, Debug)]
-struct ExtractedShapes {
+#[derive(Resource, Debug)]
+pub(crate) struct ExtractedShapes {
     shapes: EntityHashMap<Entity, ExtractedShape>,
+    /// Contents of the point buffer, shapes refer to it by offset in their params
+    points: Vec<[f32; 2]>,
+    /// Offsets into the point buffer by the entity the points belong to, this frame and last frame
+    point_offsets: EntityHashMap<Entity, f32>,
+    previous_point_offsets: EntityHashMap<Entity, f32>,
+    /// Whether the device supports the storage buffer the points are uploaded to, which WebGL2
+    /// doesn't
+    point_buffer: bool,
 }
 
+impl FromWorld for ExtractedShapes {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            shapes: default(),
+            points: default(),
+            point_offsets: default(),
+            previous_point_offsets: default(),
+            point_buffer: world.resource::<SmudPipeline>().points_layout.is_some(),
+        }
+    }
+}
+
+/// Offsets into the point buffer are passed to the shaders in `f32` params, which hold integers
+/// exactly up to 2^24
+const MAX_POINTS: usize = 1 << 24;
+
+impl ExtractedShapes {
+    /// Starts extracting a new frame, keeping the point offsets of the last one to compare with
+    fn clear(&mut self) {
+        self.shapes.clear();
+        self.points.clear();
+        std::mem::swap(&mut self.point_offsets, &mut self.previous_point_offsets);
+        self.point_offsets.clear();
+    }
+
+    /// Appends the points of an entity to the point buffer, returning the offset of the first one
+    ///
+    /// Returns `None` without adding anything if the buffer would grow beyond [`MAX_POINTS`] this
+    /// frame, since the offset can't be passed to the shader exactly, or if there's no point
+    /// buffer at all.
+    pub(crate) fn push_points(
+        &mut self,
+        entity: Entity,
+        points: impl IntoIterator<Item = Vec2>,
+    ) -> Option<f32> {
+        if !self.point_buffer {
+            static WARNED: Once = Once::new();
+            WARNED.call_once(|| {
+                warn!(
+                    "Skipping polygons, polylines, paths and csg shapes, they need storage buffers, \
+                    which this device doesn't support"
+                );
+            });
+            return None;
+        }
+        let offset = self.points.len();
+        self.points.extend(points.into_iter().map(|p| p.to_array()));
+        if self.points.len() > MAX_POINTS {
+            warn!("Skipping shape, more than {MAX_POINTS} points in the point buffer this frame");
+            self.points.truncate(offset);
+            return None;
+        }
+        self.point_offsets.insert(entity, offset as f32);
+        Some(offset as f32)
+    }
+
+    /// Whether the points of the entity are at a different offset than last frame
+    ///
+    /// Shapes refer to their points by offset, so their instance data changes when other shapes
+    /// before them in the point buffer change, even if they didn't.
+    pub(crate) fn points_moved(&self, entity: Entity) -> bool {
+        self.point_offsets.get(&entity) != self.previous_point_offsets.get(&entity)
+    }
+}
+
+#[allow(clippy::type_complexity)]
 fn extract_shapes(
     mut extracted_shapes: ResMut<ExtractedShapes>,
-    shape_query: Extract<Query<(Entity, &ViewVisibility, &SmudShape, &GlobalTransform)>>,
+    pipeline: Res<SmudPipeline>,
+    shape_query: Extract<
+        Query<
+            (
+                Entity,
+                &ViewVisibility,
+                &SmudShape,
+                &GlobalTransform,
+                Option<&SmudPolygon>,
+                Option<&SmudPolyline>,
+                Option<&SmudLine>,
+                Option<&SmudMorph>,
+                Option<&SmudParams>,
+                Option<&SmudDash>,
+                Option<&SmudTime>,
+                Option<&Parent>,
+                Option<&SmudShape3d>,
+                Has<SmudScreenSpace>,
+            ),
+            (Without<SmudPath>, Without<SmudCsg>, Without<SmudParticles>),
+        >,
+    >,
+    csg_query: Extract<Query<(), With<SmudCsg>>>,
+    changed_query: Extract<Query<(), Or<ShapeChanged>>>,
 ) {
-    extracted_shapes.shapes.clear();
-
-    for (entity, view_visibility, shape, transform) in shape_query.iter() {
+    extracted_shapes.clear();
+
+    for (
+        entity,
+        view_visibility,
+        shape,
+        transform,
+        polygon,
+        polyline,
+        line,
+        morph,
+        shape_params,
+        dash,
+        shape_time,
+        parent,
+        three_d,
+        screen_space,
+    ) in shape_query.iter()
+    {
         if !view_visibility.get() {
             continue;
         }
 
-        let Frame::Quad(frame) = shape.frame;
+        // Parts of csg shapes are only drawn as part of their parent
+        if parent.is_some_and(|parent| csg_query.contains(parent.get())) {
+            continue;
+        }
+
+        let Frame::Quad(mut frame) = shape.frame;
+        let mut transform = *transform;
+        let mut anchor = shape.anchor.frame_offset();
+
+        // Lines and polylines compute their frames, which need room for dashes along them
+        let dash_reach = dash.map_or(0., |dash| dash.width / 2.);
+
+        let mut sdf_shader = shape_sdf(
+            shape,
+            polygon.is_some(),
+            polyline.is_some(),
+            line.is_some(),
+            false,
+        );
+
+        let params = match (polygon, polyline, line) {
+            (Some(polygon), _, _) => {
+                if polygon.points.is_empty() {
+                    continue;
+                }
+                let fill_rule = match polygon.fill_rule {
+                    FillRule::NonZero => 0.,
+                    FillRule::EvenOdd => 1.,
+                };
+                let Some(ring_ends) = polygon.ring_ends() else {
+                    continue;
+                };
+                // A single ring doesn't need its end
+                let ring_ends = if ring_ends.len() > 1 {
+                    ring_ends
+                } else {
+                    Vec::new()
+                };
+                let n = polygon.points.len() as f32;
+                let rings = ring_ends.len() as f32;
+                let Some(offset) = extracted_shapes.push_points(
+                    entity,
+                    polygon
+                        .points
+                        .iter()
+                        .copied()
+                        .chain(ring_ends.iter().map(|end| Vec2::new(*end as f32, 0.))),
+                ) else {
+                    continue;
+                };
+                Vec4::new(offset, n, fill_rule, rings)
+            }
+            (None, Some(polyline), _) => {
+                if polyline.points.is_empty() {
+                    continue;
+                }
+                // Center the quad on the points, so the frame can be tight
+                let bounds = polyline.bounds();
+                let center = bounds.center();
+                transform = transform * GlobalTransform::from_translation(center.extend(0.));
+                frame = bounds.half_size().max_element()
+                    + polyline.width / 2.
+                    + dash_reach
+                    + LINE_PADDING;
+                anchor = Vec2::ZERO;
+
+                let n = polyline.points.len() as f32;
+                let Some(offset) = extracted_shapes
+                    .push_points(entity, polyline.points.iter().map(|point| *point - center))
+                else {
+                    continue;
+                };
+                Vec4::new(offset, n, 0., polyline.width)
+            }
+            (None, None, Some(line)) => {
+                transform = transform * GlobalTransform::from(line.quad_transform());
+                frame = line.frame() + dash_reach;
+                anchor = Vec2::ZERO;
+                line.params()
+            }
+            (None, None, None) => match morph {
+                Some(morph) => {
+                    let Some(morph_sdf) = pipeline.morphs.get(&(shape.sdf.id(), morph.target.id()))
+                    else {
+                        // The morph sdf is generated in `extract_sdf_shaders`
+                        continue;
+                    };
+                    sdf_shader = morph_sdf.clone_weak();
+                    Vec4::new(morph.t, 0., 0., 0.)
+                }
+                None => shape_params.map_or(Vec4::ZERO, |params| params.0),
+            },
+        };
+
+        let changed = changed_query.contains(entity) || extracted_shapes.points_moved(entity);
 
         extracted_shapes.shapes.insert(
             entity,
             ExtractedShape {
                 color: shape.color,
-                transform: *transform,
-                sdf_shader: shape.sdf.clone_weak(),
+                transform,
+                anchor,
+                screen_space,
+                sdf_shader,
                 fill_shader: shape.fill.clone_weak(),
                 frame,
+                params,
+                dash: dash.map_or(Vec4::ZERO, SmudDash::instance_data),
+                time: instance_time(shape_time),
+                atlas: None,
+                three_d: three_d.copied(),
+                changed,
+                key: (entity, 0),
             },
         );
     }
 }
 
+/// Components affecting the instance data of shapes, see [`InstanceBuffer`]
+pub(crate) type ShapeChanged = (
+    Changed<SmudShape>,
+    Changed<GlobalTransform>,
+    Changed<SmudParams>,
+    Changed<SmudDash>,
+    Changed<SmudTime>,
+    Changed<SmudMorph>,
+    Changed<SmudLine>,
+    Changed<SmudPolygon>,
+    Changed<SmudPolyline>,
+    Changed<SmudPath>,
+    Changed<SmudCsg>,
+    Changed<SmudShape3d>,
+    Changed<SmudScreenSpace>,
+);
+
 // fork of Mesh2DPipelineKey (in order to remove bevy_sprite dependency)
 // todo: merge with SmudPipelineKey?
 bitflags::bitflags! {
@@ -481,18 +1213,20 @@ fn queue_shapes(
     mut pipelines: ResMut<SpecializedRenderPipelines<SmudPipeline>>,
     pipeline_cache: ResMut<PipelineCache>,
     msaa: Res<Msaa>,
+    settings: Res<SmudSettings>,
     extracted_shapes: ResMut<ExtractedShapes>,
     mut views: Query<(
         &mut RenderPhase<Transparent2d>,
         &VisibleEntities,
         &ExtractedView,
+        Option<&SmudPixelArt>,
     )>,
     // ?
 ) {
     let draw_smud_shape_function = draw_functions.read().get_id::<DrawSmudShape>().unwrap();
 
     // Iterate over each view (a camera is a view)
-    for (mut transparent_phase, visible_entities, view) in &mut views {
+    for (mut transparent_phase, visible_entities, view, pixel_art) in &mut views {
         // todo: bevy_sprite does some hdr stuff, should we?
         // let mut view_key = SpritePipelineKey::from_hdr(view.hdr) | msaa_key;
 
@@ -507,6 +1241,10 @@ fn queue_shapes(
             .reserve(extracted_shapes.shapes.len());
 
         for (entity, extracted_shape) in extracted_shapes.shapes.iter() {
+            if extracted_shape.three_d.is_some() {
+                continue;
+            }
+
             let shader = (
                 extracted_shape.sdf_shader.id(),
                 extracted_shape.fill_shader.id(),
@@ -520,6 +1258,10 @@ fn queue_shapes(
                     mesh: mesh_key,
                     shader,
                     hdr: view.hdr,
+                    three_d: None,
+                    screen_space: extracted_shape.screen_space,
+                    anti_aliasing: settings.anti_aliasing,
+                    pixel_art: pixel_art.map(|pixel_art| pixel_art.pixel_scale.max(1)),
                 };
                 pipeline = pipelines.specialize(&pipeline_cache, &smud_pipeline, specialize_key);
             }
@@ -555,16 +1297,25 @@ fn prepare_shapes(
     view_uniforms: Res<ViewUniforms>,
     smud_pipeline: Res<SmudPipeline>,
     extracted_shapes: Res<ExtractedShapes>,
-    mut phases: Query<&mut RenderPhase<Transparent2d>>,
+    settings: Res<SmudSettings>,
+    mut phases: Query<(
+        &mut RenderPhase<Transparent2d>,
+        &ExtractedView,
+        Option<&SmudPixelArt>,
+    )>,
+    mut phases_3d: Query<&mut RenderPhase<Transparent3d>>,
     globals_buffer: Res<GlobalsBuffer>,
+    gpu_images: Res<RenderAssets<Image>>,
 ) {
     let globals = globals_buffer.buffer.binding().unwrap(); // todo if-let
 
     if let Some(view_binding) = view_uniforms.uniforms.binding() {
         let mut batches: Vec<(Entity, ShapeBatch)> = Vec::with_capacity(*previous_len);
 
-        // Clear the vertex buffer
-        shape_meta.vertices.clear();
+        // Clear the vertex buffers
+        shape_meta.vertices.clear(settings.instance_upload);
+        shape_meta.vertices_3d.clear(settings.instance_upload);
+        shape_meta.atlas_bind_groups.clear();
 
         shape_meta.view_bind_group = Some(render_device.create_bind_group(
             "smud_shape_view_bind_group",
@@ -572,109 +1323,260 @@ fn prepare_shapes(
             &BindGroupEntries::sequential((view_binding, globals.clone())),
         ));
 
-        // Vertex buffer index
-        let mut index = 0;
-
-        for mut transparent_phase in &mut phases {
-            let mut batch_item_index = 0;
-            // let mut batch_image_size = Vec2::ZERO;
-            // let mut batch_image_handle = AssetId::invalid();
-            let mut batch_shader_handles = (AssetId::invalid(), AssetId::invalid());
-
-            // Iterate through the phase items and detect when successive shapes that can be batched.
-            // Spawn an entity with a `ShapeBatch` component for each possible batch.
-            // Compatible items share the same entity.
-            for item_index in 0..transparent_phase.items.len() {
-                let item = &transparent_phase.items[item_index];
-                let Some(extracted_shape) = extracted_shapes.shapes.get(&item.entity) else {
-                    // If there is a phase item that is not a shape, then we must start a new
-                    // batch to draw the other phase item(s) and to respect draw order. This can be
-                    // done by invalidating the batch_shader_handles
-                    batch_shader_handles = (AssetId::invalid(), AssetId::invalid());
-                    continue;
-                };
+        let ShapeMeta {
+            vertices,
+            vertices_3d,
+            atlas_bind_groups,
+            ..
+        } = &mut *shape_meta;
+        let mut batcher = ShapeBatcher {
+            render_device: &render_device,
+            smud_pipeline: &smud_pipeline,
+            extracted_shapes: &extracted_shapes,
+            gpu_images: &gpu_images,
+            atlas_bind_groups,
+            batches: &mut batches,
+        };
+        for (mut transparent_phase, view, pixel_art) in &mut phases {
+            let pixel_grid = pixel_art.map(|pixel_art| pixel_art::PixelGrid::new(view, pixel_art));
+            // Snapped positions depend on the camera
+            let rewrite = pixel_grid.is_some();
+            batcher.batch(&mut transparent_phase, vertices, false, rewrite, |shape| {
+                let mut vertex = ShapeVertex::new(shape);
+                if let Some(pixel_grid) = &pixel_grid {
+                    vertex.position = pixel_grid.snap(vertex.position.into()).into();
+                }
+                vertex
+            });
+        }
+        for mut transparent_phase in &mut phases_3d {
+            batcher.batch(
+                &mut transparent_phase,
+                vertices_3d,
+                true,
+                false,
+                shape_3d::ShapeVertex3d::new,
+            );
+        }
 
-                let shader_handles = (
-                    extracted_shape.sdf_shader.id(),
-                    extracted_shape.fill_shader.id(),
-                );
+        shape_meta
+            .vertices
+            .write_buffer(&render_device, &render_queue);
+        shape_meta
+            .vertices_3d
+            .write_buffer(&render_device, &render_queue);
 
-                let batch_shader_changed = batch_shader_handles != shader_handles;
+        if let Some(instance_layout) = &smud_pipeline.instance_layout {
+            let instance_bind_group = |vertices: Option<&Buffer>| {
+                vertices.map(|buffer| {
+                    render_device.create_bind_group(
+                        "smud_shape_instance_bind_group",
+                        instance_layout,
+                        &BindGroupEntries::single(buffer.as_entire_binding()),
+                    )
+                })
+            };
+            shape_meta.instance_bind_group = instance_bind_group(shape_meta.vertices.buffer());
+            shape_meta.instance_bind_group_3d =
+                instance_bind_group(shape_meta.vertices_3d.buffer());
+        }
 
-                let color = extracted_shape.color.as_linear_rgba_f32();
+        if let (Some(points_layout), false) = (
+            &smud_pipeline.points_layout,
+            extracted_shapes.points.is_empty(),
+        ) {
+            shape_meta.points.clear();
+            for point in &extracted_shapes.points {
+                shape_meta.points.push(*point);
+            }
+            shape_meta
+                .points
+                .write_buffer(&render_device, &render_queue);
+            let points_bind_group = render_device.create_bind_group(
+                "smud_shape_points_bind_group",
+                points_layout,
+                &BindGroupEntries::single(shape_meta.points.buffer().unwrap().as_entire_binding()),
+            );
+            shape_meta.points_bind_group = Some(points_bind_group);
+        }
 
-                let position = extracted_shape.transform.translation();
-                let position = position.into();
+        *previous_len = batches.len();
+        commands.insert_or_spawn_batch(batches);
+    }
+}
 
-                let rotation_and_scale = extracted_shape
-                    .transform
-                    .affine()
-                    .transform_vector3(Vec3::X)
-                    .xy();
+/// Groups successive shapes of render phases into batches that can be drawn together
+struct ShapeBatcher<'a> {
+    render_device: &'a RenderDevice,
+    smud_pipeline: &'a SmudPipeline,
+    extracted_shapes: &'a ExtractedShapes,
+    gpu_images: &'a RenderAssets<Image>,
+    atlas_bind_groups: &'a mut HashMap<AssetId<Image>, BindGroup>,
+    batches: &'a mut Vec<(Entity, ShapeBatch)>,
+}
 
-                let scale = rotation_and_scale.length();
-                let rotation = (rotation_and_scale / scale).into();
+impl ShapeBatcher<'_> {
+    /// Writes the instance data of the shapes in the phase, and spawns an entity with a
+    /// `ShapeBatch` for each batch
+    ///
+    /// With `rewrite`, the instance data of all shapes is written again, for data that depends on
+    /// the view.
+    fn batch<P: PhaseItem, V: Pod>(
+        &mut self,
+        transparent_phase: &mut RenderPhase<P>,
+        vertices: &mut InstanceBuffer<V>,
+        three_d: bool,
+        rewrite: bool,
+        vertex: impl Fn(&ExtractedShape) -> V,
+    ) {
+        let mut batch_item_index = 0;
+        let mut batch_shader_handles = (AssetId::invalid(), AssetId::invalid());
+        let mut batch_atlas = None;
+        let mut batch_screen_space = false;
+        let mut batch_mode = None;
+
+        // Iterate through the phase items and detect when successive shapes that can be batched.
+        // Spawn an entity with a `ShapeBatch` component for each possible batch.
+        // Compatible items share the same entity.
+        for item_index in 0..transparent_phase.items.len() {
+            let item = &transparent_phase.items[item_index];
+            let Some(extracted_shape) = self.extracted_shapes.shapes.get(&item.entity()) else {
+                // If there is a phase item that is not a shape, then we must start a new
+                // batch to draw the other phase item(s) and to respect draw order. This can be
+                // done by invalidating the batch_shader_handles
+                batch_shader_handles = (AssetId::invalid(), AssetId::invalid());
+                continue;
+            };
 
-                let vertex = ShapeVertex {
-                    position,
-                    color,
-                    rotation,
-                    scale,
-                    frame: extracted_shape.frame,
-                };
-                shape_meta.vertices.push(vertex);
-
-                if batch_shader_changed {
-                    batch_item_index = item_index;
-
-                    batches.push((
-                        item.entity,
-                        ShapeBatch {
-                            shader: shader_handles,
-                            range: index..index,
-                        },
-                    ));
+            if let Some(atlas) = extracted_shape.atlas {
+                if !self.atlas_bind_groups.contains_key(&atlas) {
+                    let Some(gpu_image) = self.gpu_images.get(atlas) else {
+                        // The atlas is not uploaded yet, so skip the shape for now
+                        batch_shader_handles = (AssetId::invalid(), AssetId::invalid());
+                        continue;
+                    };
+                    let bind_group = self.render_device.create_bind_group(
+                        "smud_shape_atlas_bind_group",
+                        &self.smud_pipeline.atlas_layout,
+                        &BindGroupEntries::sequential((
+                            &gpu_image.texture_view,
+                            &gpu_image.sampler,
+                        )),
+                    );
+                    self.atlas_bind_groups.insert(atlas, bind_group);
                 }
+            }
 
-                transparent_phase.items[batch_item_index]
-                    .batch_range_mut()
-                    .end += 1;
+            let shader_handles = (
+                extracted_shape.sdf_shader.id(),
+                extracted_shape.fill_shader.id(),
+            );
 
-                batches.last_mut().unwrap().1.range.end += 1;
-                index += 1;
+            let batch_shader_changed = batch_shader_handles != shader_handles
+                || batch_atlas != extracted_shape.atlas
+                || batch_screen_space != extracted_shape.screen_space
+                // Billboards and planes have different vertex shaders
+                || batch_mode != extracted_shape.three_d;
+
+            let index = vertices.len() as u32;
+            vertices.push(
+                extracted_shape.key,
+                rewrite || extracted_shape.changed,
+                || vertex(extracted_shape),
+            );
+
+            if batch_shader_changed {
+                batch_item_index = item_index;
+                batch_shader_handles = shader_handles;
+                batch_atlas = extracted_shape.atlas;
+                batch_screen_space = extracted_shape.screen_space;
+                batch_mode = extracted_shape.three_d;
+
+                self.batches.push((
+                    item.entity(),
+                    ShapeBatch {
+                        shader: shader_handles,
+                        atlas: extracted_shape.atlas,
+                        three_d,
+                        range: index..index,
+                    },
+                ));
             }
-        }
 
-        shape_meta
-            .vertices
-            .write_buffer(&render_device, &render_queue);
+            transparent_phase.items[batch_item_index]
+                .batch_range_mut()
+                .end += 1;
 
-        *previous_len = batches.len();
-        commands.insert_or_spawn_batch(batches);
+            self.batches.last_mut().unwrap().1.range.end += 1;
+        }
+    }
+}
+
+instance_layout::instance_data! {
+    /// Instance data of shapes drawn with 2d cameras
+    struct ShapeVertex {
+        /// Offset of the quad from the position, in half frames
+        anchor: [f32; 2],
+        color: [f32; 4],
+        dash: [f32; 4],
+        frame: f32,
+        params: [f32; 4],
+        position: [f32; 3],
+        rotation: [f32; 2],
+        scale: f32,
+        /// (own time, weight of the global time), see `vertex_time`
+        time: [f32; 2],
     }
 }
 
-#[repr(C)]
-#[derive(Debug, Copy, Clone, Pod, Zeroable)]
-struct ShapeVertex {
-    pub color: [f32; 4],
-    pub frame: f32,
-    pub position: [f32; 3],
-    pub rotation: [f32; 2],
-    pub scale: f32,
+impl ShapeVertex {
+    fn new(shape: &ExtractedShape) -> Self {
+        let rotation_and_scale = shape.transform.affine().transform_vector3(Vec3::X).xy();
+
+        let scale = rotation_and_scale.length();
+        let rotation = (rotation_and_scale / scale).into();
+
+        Self {
+            anchor: shape.anchor.into(),
+            position: shape.transform.translation().into(),
+            color: shape.color.as_linear_rgba_f32(),
+            rotation,
+            scale,
+            frame: shape.frame,
+            params: shape.params.into(),
+            dash: shape.dash.into(),
+            time: time::vertex_time(shape.time),
+        }
+    }
 }
 
 #[derive(Resource)]
 pub(crate) struct ShapeMeta {
-    vertices: BufferVec<ShapeVertex>,
+    vertices: InstanceBuffer<ShapeVertex>,
+    /// Instance data of shapes drawn with 3d cameras
+    vertices_3d: InstanceBuffer<shape_3d::ShapeVertex3d>,
+    /// Binds `vertices` as a storage buffer, if supported
+    instance_bind_group: Option<BindGroup>,
+    /// Binds `vertices_3d` as a storage buffer, if supported
+    instance_bind_group_3d: Option<BindGroup>,
     view_bind_group: Option<BindGroup>,
+    atlas_bind_groups: HashMap<AssetId<Image>, BindGroup>,
+    /// Points of all polygons and polylines
+    points: BufferVec<[f32; 2]>,
+    points_bind_group: Option<BindGroup>,
 }
 
 impl Default for ShapeMeta {
     fn default() -> Self {
         Self {
-            vertices: BufferVec::new(BufferUsages::VERTEX),
+            vertices: InstanceBuffer::new("smud_shape_vertices"),
+            vertices_3d: InstanceBuffer::new("smud_shape_vertices_3d"),
+            instance_bind_group: None,
+            instance_bind_group_3d: None,
             view_bind_group: None,
+            atlas_bind_groups: default(),
+            points: BufferVec::new(BufferUsages::STORAGE),
+            points_bind_group: None,
         }
     }
 }
@@ -682,5 +1584,8 @@ impl Default for ShapeMeta {
 #[derive(Component, Eq, PartialEq, Clone)]
 pub(crate) struct ShapeBatch {
     shader: (AssetId<Shader>, AssetId<Shader>),
+    atlas: Option<AssetId<Image>>,
+    /// Whether the instances are in the buffer of shapes drawn with 3d cameras
+    three_d: bool,
     range: Range<u32>,
 }
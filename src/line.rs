@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+
+use crate::StrokeCap;
+
+/// Room around lines and polylines covered by their frames, for anti-aliasing
+pub(crate) const LINE_PADDING: f32 = 2.;
+
+#[derive(Component, Reflect, Debug, Default, Clone, Copy, PartialEq)]
+#[reflect(Component)]
+/// A straight line between two points, replacing the sdf of the [`SmudShape`](crate::SmudShape)
+/// on the same entity
+///
+/// The quad is rotated along the line and its frame is computed from the points, so the frame
+/// and anchor of the `SmudShape` are ignored. All lines share one sdf, and don't need storage
+/// buffers, so they batch together and work on WebGL2.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_smud::{prelude::*, StrokeCap};
+/// fn setup(mut commands: Commands) {
+///     commands.spawn((
+///         ShapeBundle {
+///             shape: SmudShape {
+///                 color: Color::WHITE,
+///                 fill: SIMPLE_FILL_HANDLE,
+///                 ..default()
+///             },
+///             ..default()
+///         },
+///         SmudLine::new(Vec2::ZERO, Vec2::new(100., 50.), 4.).with_cap(StrokeCap::Round),
+///     ));
+/// }
+/// ```
+pub struct SmudLine {
+    /// Where the line starts, in the shape's local space
+    pub start: Vec2,
+    /// Where the line ends, in the shape's local space
+    pub end: Vec2,
+    /// The thickness of the line
+    pub width: f32,
+    /// Shape of the ends of the line
+    pub cap: StrokeCap,
+}
+
+impl SmudLine {
+    /// Creates a line with [`StrokeCap::Butt`] ends
+    pub fn new(start: Vec2, end: Vec2, width: f32) -> Self {
+        Self {
+            start,
+            end,
+            width,
+            cap: default(),
+        }
+    }
+
+    /// Sets the shape of the ends of the line
+    pub fn with_cap(mut self, cap: StrokeCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    /// The local transform of the quad, centered on the line with x along it
+    pub(crate) fn quad_transform(&self) -> Transform {
+        let direction = self.end - self.start;
+        Transform::from_translation(((self.start + self.end) / 2.).extend(0.))
+            .with_rotation(Quat::from_rotation_z(direction.y.atan2(direction.x)))
+    }
+
+    /// Half the size of the smallest square frame covering the line
+    pub(crate) fn frame(&self) -> f32 {
+        let half_length = self.start.distance(self.end) / 2.;
+        let half_width = self.width / 2.;
+        let cap_length = match self.cap {
+            StrokeCap::Butt => 0.,
+            StrokeCap::Round | StrokeCap::Square => half_width,
+        };
+        (half_length + cap_length).max(half_width) + LINE_PADDING
+    }
+
+    /// The instance params read by `line.wgsl`
+    pub(crate) fn params(&self) -> Vec4 {
+        Vec4::new(
+            self.start.distance(self.end) / 2.,
+            self.width / 2.,
+            self.cap as u8 as f32,
+            0.,
+        )
+    }
+}
@@ -0,0 +1,326 @@
+//! Cpu generation of multi-channel signed distance fields (msdf) from glyph outlines
+//!
+//! This is a simplified port of the approach used by [msdfgen](https://github.com/Chlumsky/msdfgen):
+//! edges are colored so that corners are shared by two channels only, each channel stores the
+//! pseudo-distance to the closest edge of its color, and the median of the three channels
+//! reconstructs sharp corners. Curves are flattened into polylines before distances are computed.
+
+use bevy::math::Vec2;
+
+/// How many pixels of distance the field spans on each side of the edge
+pub const MSDF_RANGE: f32 = 4.;
+
+const RED: u8 = 0b001;
+const GREEN: u8 = 0b010;
+const BLUE: u8 = 0b100;
+const YELLOW: u8 = RED | GREEN;
+const MAGENTA: u8 = RED | BLUE;
+const CYAN: u8 = GREEN | BLUE;
+const WHITE: u8 = RED | GREEN | BLUE;
+
+const CURVE_SUBDIVISIONS: usize = 8;
+
+/// sin of the angle above which a change in direction is considered a corner
+const CORNER_THRESHOLD: f32 = 0.141_120_01; // sin(3.)
+
+struct Edge {
+    /// Flattened points of the edge, at least two
+    points: Vec<Vec2>,
+    color: u8,
+}
+
+impl Edge {
+    fn start_direction(&self) -> Vec2 {
+        (self.points[1] - self.points[0]).normalize_or_zero()
+    }
+
+    fn end_direction(&self) -> Vec2 {
+        let n = self.points.len();
+        (self.points[n - 1] - self.points[n - 2]).normalize_or_zero()
+    }
+
+    /// Returns (distance, orthogonality, pseudo distance)
+    ///
+    /// Distances are signed, positive to the right of the edge direction.
+    fn signed_distance(&self, p: Vec2) -> (f32, f32, f32) {
+        let last_segment = self.points.len() - 2;
+        let mut best = (f32::INFINITY, 0., 0.);
+
+        for (i, segment) in self.points.windows(2).enumerate() {
+            let (a, b) = (segment[0], segment[1]);
+            let ab = b - a;
+            let ap = p - a;
+            let len_sq = ab.length_squared();
+            if len_sq == 0. {
+                continue;
+            }
+            let t = ap.dot(ab) / len_sq;
+            let closest = a + ab * t.clamp(0., 1.);
+            let distance = (p - closest).length();
+
+            let dir = ab / len_sq.sqrt();
+
+            // Orthogonality is used to break ties between segments meeting at a shared point
+            let orthogonality = dir.perp_dot((p - closest).normalize_or_zero()).abs();
+
+            let closer = distance < best.0.abs() - f32::EPSILON;
+            let tie = distance <= best.0.abs() + f32::EPSILON && orthogonality > best.1;
+            if !(closer || tie) {
+                continue;
+            }
+
+            let signed = -dir.perp_dot(ap).signum() * distance;
+
+            // Extend the first and last segments into infinite lines for the pseudo distance
+            let mut pseudo = signed;
+            if (i == 0 && t < 0.) || (i == last_segment && t > 1.) {
+                let orthogonal = -dir.perp_dot(ap);
+                if orthogonal.abs() <= distance {
+                    pseudo = orthogonal;
+                }
+            }
+
+            best = (signed, orthogonality, pseudo);
+        }
+
+        best
+    }
+}
+
+#[derive(Default)]
+struct Contour {
+    edges: Vec<Edge>,
+}
+
+impl Contour {
+    /// Twice the signed area of the contour, positive for counter-clockwise contours
+    fn signed_area(&self) -> f32 {
+        self.edges
+            .iter()
+            .flat_map(|e| e.points.windows(2))
+            .map(|s| s[0].perp_dot(s[1]))
+            .sum()
+    }
+
+    fn color_edges(&mut self) {
+        let n = self.edges.len();
+        if n == 0 {
+            return;
+        }
+
+        let corners: Vec<usize> = (0..n)
+            .filter(|&i| {
+                let prev = self.edges[(i + n - 1) % n].end_direction();
+                let next = self.edges[i].start_direction();
+                prev.dot(next) <= 0. || prev.perp_dot(next).abs() > CORNER_THRESHOLD
+            })
+            .collect();
+
+        match corners.len() {
+            0 => {
+                for edge in &mut self.edges {
+                    edge.color = WHITE;
+                }
+            }
+            1 => {
+                // "Teardrop" shape, split the contour into three differently colored parts
+                let start = corners[0];
+                let colors = [MAGENTA, WHITE, YELLOW];
+                for k in 0..n {
+                    let third = (k * 3 / n).min(2);
+                    self.edges[(start + k) % n].color = colors[third];
+                }
+            }
+            _ => {
+                let colors = [CYAN, MAGENTA, YELLOW];
+                let start = corners[0];
+                let mut color_index = 0;
+                let mut corner_index = 0;
+                for k in 0..n {
+                    let i = (start + k) % n;
+                    if corner_index < corners.len() && corners[corner_index] == i {
+                        if corner_index > 0 {
+                            color_index = (color_index + 1) % 3;
+                            // The last run wraps around to the first one, so they must differ
+                            if corner_index == corners.len() - 1 && color_index == 0 {
+                                color_index = 1;
+                            }
+                        }
+                        corner_index += 1;
+                    }
+                    self.edges[i].color = colors[color_index];
+                }
+            }
+        }
+    }
+}
+
+/// A glyph or shape outline, consisting of closed contours
+#[derive(Default)]
+pub struct Outline {
+    contours: Vec<Contour>,
+    current: Vec<Edge>,
+    start: Vec2,
+    last: Vec2,
+}
+
+impl Outline {
+    /// Start a new contour
+    pub fn move_to(&mut self, p: Vec2) {
+        self.close();
+        self.start = p;
+        self.last = p;
+    }
+
+    /// Add a straight edge
+    pub fn line_to(&mut self, p: Vec2) {
+        if p != self.last {
+            self.push_edge(vec![self.last, p]);
+        }
+    }
+
+    /// Add a quadratic bezier edge
+    pub fn quad_to(&mut self, c: Vec2, p: Vec2) {
+        let a = self.last;
+        let points = (0..=CURVE_SUBDIVISIONS)
+            .map(|i| {
+                let t = i as f32 / CURVE_SUBDIVISIONS as f32;
+                let s = 1. - t;
+                a * s * s + c * 2. * s * t + p * t * t
+            })
+            .collect();
+        self.push_edge(points);
+    }
+
+    /// Add a cubic bezier edge
+    pub fn cubic_to(&mut self, c1: Vec2, c2: Vec2, p: Vec2) {
+        let a = self.last;
+        let points = (0..=CURVE_SUBDIVISIONS)
+            .map(|i| {
+                let t = i as f32 / CURVE_SUBDIVISIONS as f32;
+                let s = 1. - t;
+                a * s * s * s + c1 * 3. * s * s * t + c2 * 3. * s * t * t + p * t * t * t
+            })
+            .collect();
+        self.push_edge(points);
+    }
+
+    /// Close the current contour
+    pub fn close(&mut self) {
+        if self.last != self.start {
+            self.push_edge(vec![self.last, self.start]);
+        }
+        if !self.current.is_empty() {
+            self.contours.push(Contour {
+                edges: std::mem::take(&mut self.current),
+            });
+        }
+        self.last = self.start;
+    }
+
+    fn push_edge(&mut self, points: Vec<Vec2>) {
+        self.last = *points.last().unwrap();
+        self.current.push(Edge {
+            points,
+            color: WHITE,
+        });
+    }
+
+    /// Nonzero winding test against the flattened outline
+    fn contains(&self, p: Vec2) -> bool {
+        let mut winding = 0;
+        for segment in self
+            .contours
+            .iter()
+            .flat_map(|c| c.edges.iter())
+            .flat_map(|e| e.points.windows(2))
+        {
+            let (a, b) = (segment[0], segment[1]);
+            if a.y <= p.y {
+                if b.y > p.y && (b - a).perp_dot(p - a) > 0. {
+                    winding += 1;
+                }
+            } else if b.y <= p.y && (b - a).perp_dot(p - a) < 0. {
+                winding -= 1;
+            }
+        }
+        winding != 0
+    }
+
+    /// Render the outline into an rgba8 msdf bitmap
+    ///
+    /// `origin` is the outline-space position of the top left corner of the bitmap, and
+    /// `units_per_pixel` how many outline units a single pixel spans. Values are encoded so that
+    /// 0.5 is on the edge, and the field spans [`MSDF_RANGE`] pixels on each side of it.
+    pub fn generate_msdf(
+        mut self,
+        width: usize,
+        height: usize,
+        origin: Vec2,
+        units_per_pixel: f32,
+    ) -> Vec<u8> {
+        self.close();
+
+        for contour in &mut self.contours {
+            contour.color_edges();
+        }
+
+        // Make sure positive distances are outside regardless of the winding convention of the font
+        let orientation = if self.contours.iter().map(Contour::signed_area).sum::<f32>() < 0. {
+            -1.
+        } else {
+            1.
+        };
+
+        let mut data = Vec::with_capacity(width * height * 4);
+
+        for y in 0..height {
+            for x in 0..width {
+                let p = origin + Vec2::new(x as f32 + 0.5, -(y as f32 + 0.5)) * units_per_pixel;
+
+                // (distance, orthogonality, pseudo distance) per channel
+                let mut channels = [(f32::INFINITY, 0., 0.); 3];
+                let mut closest = f32::INFINITY;
+
+                for edge in self.contours.iter().flat_map(|c| c.edges.iter()) {
+                    let (distance, orthogonality, pseudo) = edge.signed_distance(p);
+                    closest = closest.min(distance.abs());
+                    for (channel, best) in channels.iter_mut().enumerate() {
+                        if edge.color & (1 << channel) == 0 {
+                            continue;
+                        }
+                        let closer = distance.abs() < best.0.abs() - f32::EPSILON;
+                        let tie = (distance.abs() - best.0.abs()).abs() <= f32::EPSILON
+                            && orthogonality > best.1;
+                        if closer || tie {
+                            *best = (distance, orthogonality, pseudo);
+                        }
+                    }
+                }
+
+                let mut distances = channels.map(|(_, _, pseudo)| pseudo * orientation);
+
+                // Fall back to a plain sdf where the channels disagree with the true inside test
+                let inside = self.contains(p);
+                let median = median(distances[0], distances[1], distances[2]);
+                if (median < 0.) != inside || !median.is_finite() {
+                    let d = if inside { -closest } else { closest };
+                    distances = [d; 3];
+                }
+
+                for d in distances {
+                    let d = d / units_per_pixel;
+                    let value = (0.5 - d / (2. * MSDF_RANGE)).clamp(0., 1.);
+                    data.push((value * 255.).round() as u8);
+                }
+                data.push(255);
+            }
+        }
+
+        data
+    }
+}
+
+fn median(a: f32, b: f32, c: f32) -> f32 {
+    a.min(b).max(a.max(b).min(c))
+}
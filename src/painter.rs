@@ -0,0 +1,196 @@
+use bevy::{ecs::system::SystemParam, prelude::*, render::Extract};
+
+use crate::{
+    shader_loading::PAINTER_SDF_HANDLE, time::instance_time, ExtractedShape, ExtractedShapes,
+    Frame, SmudShape, SIMPLE_FILL_HANDLE,
+};
+
+/// Extra space around painted shapes covered by their frames, for anti-aliasing
+const FRAME_PADDING: f32 = 2.;
+
+/// Settings for [`SmudPainter`]
+#[derive(Resource, Debug, Clone)]
+pub struct SmudPainterConfig {
+    /// Set to `false` to stop drawing painted shapes
+    pub enabled: bool,
+    /// The z coordinate of shapes painted with a 2d position
+    pub depth: f32,
+    /// The fill of the circles, rectangles and lines
+    pub fill: Handle<Shader>,
+}
+
+impl Default for SmudPainterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            depth: 0.,
+            fill: SIMPLE_FILL_HANDLE,
+        }
+    }
+}
+
+/// A system param for drawing shapes for a single frame, without spawning entities
+///
+/// Like bevy's gizmos, the shapes need to be drawn again every frame they should be visible,
+/// which is handy for debug overlays and quick tools.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_smud::SmudPainter;
+/// fn draw(mut painter: SmudPainter, time: Res<Time>) {
+///     let x = time.elapsed_seconds().sin() * 100.;
+///     painter.circle(Vec2::new(x, 0.), 20., Color::ORANGE);
+///     painter.rect(Vec2::ZERO, 0.3, Vec2::new(50., 30.), Color::TEAL);
+///     painter.line(Vec2::ZERO, Vec2::new(x, 0.), 4., Color::WHITE);
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct SmudPainter<'w> {
+    config: Res<'w, SmudPainterConfig>,
+    shapes: ResMut<'w, PaintedShapes>,
+}
+
+impl SmudPainter<'_> {
+    /// Draws a circle
+    pub fn circle(&mut self, position: Vec2, radius: f32, color: Color) {
+        self.primitive(
+            Transform::from_translation(position.extend(self.config.depth)),
+            radius,
+            Vec4::new(radius, 0., 0., 0.),
+            color,
+        );
+    }
+
+    /// Draws a rectangle of the given size, rotated counter-clockwise by `rotation` radians
+    pub fn rect(&mut self, position: Vec2, rotation: f32, size: Vec2, color: Color) {
+        self.rounded_rect(position, rotation, size, 0., color);
+    }
+
+    /// Draws a rectangle with rounded corners
+    pub fn rounded_rect(
+        &mut self,
+        position: Vec2,
+        rotation: f32,
+        size: Vec2,
+        radius: f32,
+        color: Color,
+    ) {
+        let half_size = size / 2.;
+        self.primitive(
+            Transform::from_translation(position.extend(self.config.depth))
+                .with_rotation(Quat::from_rotation_z(rotation)),
+            half_size.max_element(),
+            Vec4::new(half_size.x, half_size.y, radius, 1.),
+            color,
+        );
+    }
+
+    /// Draws a line with rounded ends
+    pub fn line(&mut self, start: Vec2, end: Vec2, width: f32, color: Color) {
+        let delta = end - start;
+        let half_length = delta.length() / 2.;
+        self.primitive(
+            Transform::from_translation(((start + end) / 2.).extend(self.config.depth))
+                .with_rotation(Quat::from_rotation_z(delta.y.atan2(delta.x))),
+            half_length + width / 2.,
+            Vec4::new(half_length, width / 2., 0., 2.),
+            color,
+        );
+    }
+
+    /// Draws an sdf shader with the configured fill
+    pub fn sdf(
+        &mut self,
+        sdf: &Handle<Shader>,
+        transform: impl Into<GlobalTransform>,
+        frame: Frame,
+        color: Color,
+    ) {
+        let shape = SmudShape {
+            color,
+            sdf: sdf.clone(),
+            fill: self.config.fill.clone(),
+            frame,
+            anchor: default(),
+        };
+        self.shape(&shape, transform);
+    }
+
+    /// Draws a shape, with its own fill
+    pub fn shape(&mut self, shape: &SmudShape, transform: impl Into<GlobalTransform>) {
+        self.push(shape.clone(), transform.into(), Vec4::ZERO);
+    }
+
+    fn primitive(&mut self, transform: Transform, extent: f32, params: Vec4, color: Color) {
+        let shape = SmudShape {
+            color,
+            sdf: PAINTER_SDF_HANDLE,
+            fill: self.config.fill.clone(),
+            frame: Frame::Quad(extent + FRAME_PADDING),
+            anchor: default(),
+        };
+        self.push(shape, transform.into(), params);
+    }
+
+    fn push(&mut self, shape: SmudShape, transform: GlobalTransform, params: Vec4) {
+        if self.config.enabled {
+            self.shapes.0.push(PaintedShape {
+                shape,
+                transform,
+                params,
+            });
+        }
+    }
+}
+
+/// Shapes drawn by [`SmudPainter`] this frame
+#[derive(Resource, Default)]
+pub(crate) struct PaintedShapes(Vec<PaintedShape>);
+
+impl PaintedShapes {
+    pub(crate) fn shapes(&self) -> impl Iterator<Item = &SmudShape> {
+        self.0.iter().map(|painted| &painted.shape)
+    }
+}
+
+struct PaintedShape {
+    shape: SmudShape,
+    transform: GlobalTransform,
+    params: Vec4,
+}
+
+pub(crate) fn clear_painted_shapes(mut painted_shapes: ResMut<PaintedShapes>) {
+    painted_shapes.0.clear();
+}
+
+pub(crate) fn extract_painted_shapes(
+    mut commands: Commands,
+    mut extracted_shapes: ResMut<ExtractedShapes>,
+    painted_shapes: Extract<Res<PaintedShapes>>,
+) {
+    for (index, painted) in painted_shapes.0.iter().enumerate() {
+        let Frame::Quad(frame) = painted.shape.frame;
+        // Each shape gets its own render world entity, so it can be sorted and batched
+        extracted_shapes.shapes.insert(
+            commands.spawn_empty().id(),
+            ExtractedShape {
+                color: painted.shape.color,
+                frame,
+                params: painted.params,
+                dash: Vec4::ZERO,
+                sdf_shader: painted.shape.sdf.clone_weak(),
+                fill_shader: painted.shape.fill.clone_weak(),
+                transform: painted.transform,
+                anchor: painted.shape.anchor.frame_offset(),
+                screen_space: false,
+                time: instance_time(None),
+                atlas: None,
+                three_d: None,
+                // Painted again every frame, so there's nothing to detect changes with, and the
+                // instance data is compared with last frame's instead
+                changed: true,
+                key: (Entity::PLACEHOLDER, index as u32),
+            },
+        );
+    }
+}
@@ -0,0 +1,558 @@
+use std::{num::NonZeroU64, sync::Once};
+
+use bevy::{
+    core_pipeline::core_2d::Transparent2d,
+    ecs::system::{
+        lifetimeless::{Read, SRes},
+        SystemParamItem,
+    },
+    prelude::*,
+    render::{
+        render_phase::{
+            AddRenderCommand, DrawFunctions, PhaseItem, RenderCommand, RenderCommandResult,
+            RenderPhase, SetItemPipeline, TrackedRenderPass,
+        },
+        render_resource::{
+            BindGroup, BindGroupEntries, BindGroupLayout, BindGroupLayoutDescriptor,
+            BindGroupLayoutEntry, BindingType, Buffer, BufferBindingType, BufferDescriptor,
+            BufferUsages, CachedComputePipelineId, CachedRenderPipelineId,
+            CommandEncoderDescriptor, ComputePassDescriptor, ComputePipeline,
+            ComputePipelineDescriptor, PipelineCache, PrimitiveTopology, ShaderStages,
+            SpecializedRenderPipelines,
+        },
+        renderer::{RenderDevice, RenderQueue},
+        view::ExtractedView,
+        Extract, ExtractSchedule, Render, RenderSet,
+    },
+    utils::{EntityHashMap, FloatOrd, HashSet},
+};
+use bytemuck::{Pod, Zeroable};
+
+use crate::{
+    shader_loading::PARTICLES_SHADER_HANDLE, PipelineKey, SetShapeViewBindGroup, ShapeVertex,
+    SmudParams, SmudPipeline, SmudPipelineKey, SmudPixelArt, SmudSettings, SmudShape,
+};
+
+/// Number of particles simulated by each compute workgroup, must match `particles.wgsl`
+const WORKGROUP_SIZE: u32 = 64;
+
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq)]
+#[reflect(Component)]
+/// Emits particles drawn with the [`SmudShape`] on the same entity
+///
+/// The particles are simulated in a compute shader, which writes their instance data straight
+/// into a buffer that's drawn with the sdf and fill of the shape, so tens of thousands of
+/// particles can be drawn without any entities. The color, frame and [`SmudParams`] of the shape
+/// apply to all particles, and `smud::time()` is the age of each particle.
+///
+/// Particles are spawned at the transform of the emitter, and keep moving in world space when
+/// the emitter moves. The emitter shape itself isn't drawn.
+///
+/// Only drawn with 2d cameras, and not supported on WebGL2, which lacks compute shaders, so
+/// emitters are skipped there with a warning.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_smud::prelude::*;
+/// fn setup(mut commands: Commands, mut shaders: ResMut<Assets<Shader>>) {
+///     commands.spawn((
+///         ShapeBundle {
+///             shape: SmudShape {
+///                 color: Color::ORANGE,
+///                 sdf: shaders.add_sdf_expr("smud::sd_circle(p, 4.)"),
+///                 fill: SIMPLE_FILL_HANDLE,
+///                 frame: Frame::Quad(6.),
+///                 ..default()
+///             },
+///             ..default()
+///         },
+///         SmudParticles {
+///             rate: 500.,
+///             gravity: Vec2::new(0., -200.),
+///             ..default()
+///         },
+///     ));
+/// }
+/// ```
+pub struct SmudParticles {
+    /// The maximum number of particles alive at the same time
+    ///
+    /// When more particles are emitted, the oldest ones are replaced.
+    pub capacity: u32,
+    /// Particles emitted per second
+    pub rate: f32,
+    /// How long each particle lives, in seconds
+    pub lifetime: f32,
+    /// The initial velocity of the particles, in the local space of the emitter
+    pub velocity: Vec2,
+    /// The angle in radians the direction of the initial velocity is randomized within
+    pub spread: f32,
+    /// Acceleration of the particles, in world space
+    pub gravity: Vec2,
+    /// Radius of the disc around the emitter the particles are spawned in
+    pub radius: f32,
+    /// The scale of the particles at the end of their life, relative to their initial scale
+    pub end_scale: f32,
+    /// The color the particles blend into over their life, if `None` they fade out instead
+    pub end_color: Option<Color>,
+}
+
+impl Default for SmudParticles {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            rate: 64.,
+            lifetime: 2.,
+            velocity: Vec2::new(0., 100.),
+            spread: 0.5,
+            gravity: Vec2::ZERO,
+            radius: 0.,
+            end_scale: 1.,
+            end_color: None,
+        }
+    }
+}
+
+/// Adds the simulation and drawing of particles to the render app
+///
+/// Simulating particles needs compute shaders and storage buffers, so when the device doesn't
+/// support them, e.g. on WebGL2, emitters are skipped with a warning instead.
+pub(crate) fn add_render_systems(render_app: &mut App) {
+    let limits = render_app.world.resource::<RenderDevice>().limits();
+    if limits.max_storage_buffers_per_shader_stage < 2
+        || limits.max_compute_invocations_per_workgroup < WORKGROUP_SIZE
+    {
+        render_app.add_systems(ExtractSchedule, warn_unsupported_particles);
+        return;
+    }
+
+    render_app
+        .add_render_command::<Transparent2d, DrawSmudParticles>()
+        .init_resource::<GpuEmitters>()
+        .init_resource::<ParticlePipeline>()
+        .add_systems(ExtractSchedule, extract_particles)
+        .add_systems(
+            Render,
+            (
+                queue_particles.in_set(RenderSet::Queue),
+                simulate_particles.in_set(RenderSet::PrepareResources),
+            ),
+        );
+}
+
+fn warn_unsupported_particles(emitters: Extract<Query<(), With<SmudParticles>>>) {
+    static WARNED: Once = Once::new();
+    if !emitters.is_empty() {
+        WARNED.call_once(|| {
+            warn!(
+                "Skipping SmudParticles, they need compute shaders and storage buffers, which \
+                this device doesn't support"
+            );
+        });
+    }
+}
+
+/// An emitter extracted to the render world, one entity per emitter and frame
+#[derive(Component)]
+pub(crate) struct ExtractedEmitter {
+    /// The main world entity, which the simulation state is kept for
+    main_entity: Entity,
+    particles: SmudParticles,
+    transform: GlobalTransform,
+    color: Color,
+    frame: f32,
+    params: Vec4,
+    sdf_shader: Handle<Shader>,
+    fill_shader: Handle<Shader>,
+    delta: f32,
+}
+
+#[allow(clippy::type_complexity)]
+fn extract_particles(
+    mut commands: Commands,
+    time: Extract<Res<Time>>,
+    emitters: Extract<
+        Query<(
+            Entity,
+            &InheritedVisibility,
+            &SmudShape,
+            &SmudParticles,
+            &GlobalTransform,
+            Option<&SmudParams>,
+        )>,
+    >,
+) {
+    for (main_entity, visibility, shape, particles, transform, params) in &emitters {
+        // Particles move away from the emitter, so its frame can't be used for culling
+        if !visibility.get() {
+            continue;
+        }
+        let crate::Frame::Quad(frame) = shape.frame;
+        commands.spawn(ExtractedEmitter {
+            main_entity,
+            particles: *particles,
+            transform: *transform,
+            color: shape.color,
+            frame,
+            params: params.map_or(Vec4::ZERO, |params| params.0),
+            sdf_shader: shape.sdf.clone_weak(),
+            fill_shader: shape.fill.clone_weak(),
+            delta: time.delta_seconds(),
+        });
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct ParticlePipeline {
+    layout: BindGroupLayout,
+    pipeline: CachedComputePipelineId,
+}
+
+impl FromWorld for ParticlePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let storage = |binding| BindGroupLayoutEntry {
+            binding,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let layout = render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: NonZeroU64::new(
+                            std::mem::size_of::<EmitterUniform>() as u64
+                        ),
+                    },
+                    count: None,
+                },
+                storage(1),
+                storage(2),
+            ],
+            label: Some("smud_particles_layout"),
+        });
+
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+            label: Some("smud_particles_pipeline".into()),
+            layout: vec![layout.clone()],
+            push_constant_ranges: Vec::new(),
+            shader: PARTICLES_SHADER_HANDLE,
+            // Instances are written with the generated `store` of `ShapeVertex`
+            shader_defs: vec!["SMUD_INSTANCE_WRITER".into()],
+            entry_point: "simulate".into(),
+        });
+
+        Self { layout, pipeline }
+    }
+}
+
+impl ParticlePipeline {
+    /// The simulation pipeline, once it's created
+    ///
+    /// Queued pipelines only enter the cache when its queue is processed during
+    /// [`RenderSet::Render`], so looking one up before that panics.
+    fn compute_pipeline<'a>(
+        &self,
+        pipeline_cache: &'a PipelineCache,
+    ) -> Option<&'a ComputePipeline> {
+        pipeline_cache.pipelines().nth(self.pipeline.id())?;
+        pipeline_cache.get_compute_pipeline(self.pipeline)
+    }
+}
+
+/// The `Emitter` struct in `particles.wgsl`
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+struct EmitterUniform {
+    transform: [f32; 16],
+    color: [f32; 4],
+    end_color: [f32; 4],
+    params: [f32; 4],
+    velocity: [f32; 2],
+    gravity: [f32; 2],
+    spawn_start: u32,
+    spawn_count: u32,
+    capacity: u32,
+    seed: u32,
+    delta: f32,
+    lifetime: f32,
+    spread: f32,
+    radius: f32,
+    frame: f32,
+    scale: f32,
+    end_scale: f32,
+    padding: f32,
+}
+
+/// Simulation state of an emitter on the gpu
+struct GpuEmitter {
+    capacity: u32,
+    /// Index of the next particle to respawn
+    next: u32,
+    /// Fractional particles left to emit
+    accumulator: f32,
+    /// Counts the simulated frames, to get new random numbers each frame
+    frame_count: u32,
+    uniform: Buffer,
+    instances: Buffer,
+    bind_group: BindGroup,
+    /// Reads the instances in the vertex shader, when they are drawn from storage buffers
+    instance_bind_group: Option<BindGroup>,
+}
+
+impl GpuEmitter {
+    fn new(
+        render_device: &RenderDevice,
+        layout: &BindGroupLayout,
+        smud_pipeline: &SmudPipeline,
+        capacity: u32,
+    ) -> Self {
+        let buffer = |label, size, usage| {
+            render_device.create_buffer(&BufferDescriptor {
+                label: Some(label),
+                size,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+        let uniform = buffer(
+            "smud_particles_uniform",
+            std::mem::size_of::<EmitterUniform>() as u64,
+            BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        );
+        // Zeroed particles have a lifetime of 0, so they start out dead
+        let particles = buffer(
+            "smud_particles_state",
+            capacity as u64 * 6 * 4,
+            BufferUsages::STORAGE,
+        );
+        let instances = buffer(
+            "smud_particles_instances",
+            capacity as u64 * std::mem::size_of::<ShapeVertex>() as u64,
+            BufferUsages::STORAGE | BufferUsages::VERTEX,
+        );
+        let bind_group = render_device.create_bind_group(
+            "smud_particles_bind_group",
+            layout,
+            &BindGroupEntries::sequential((
+                uniform.as_entire_binding(),
+                particles.as_entire_binding(),
+                instances.as_entire_binding(),
+            )),
+        );
+        let instance_bind_group = smud_pipeline
+            .instance_layout
+            .as_ref()
+            .map(|instance_layout| {
+                render_device.create_bind_group(
+                    "smud_particles_instance_bind_group",
+                    instance_layout,
+                    &BindGroupEntries::single(instances.as_entire_binding()),
+                )
+            });
+        Self {
+            capacity,
+            next: 0,
+            accumulator: 0.,
+            frame_count: 0,
+            uniform,
+            instances,
+            bind_group,
+            instance_bind_group,
+        }
+    }
+}
+
+/// The gpu state of all emitters, by main world entity
+#[derive(Resource, Default)]
+pub(crate) struct GpuEmitters(EntityHashMap<Entity, GpuEmitter>);
+
+/// Runs the simulation of all emitters, before the phases are drawn
+fn simulate_particles(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    pipeline_cache: Res<PipelineCache>,
+    particle_pipeline: Res<ParticlePipeline>,
+    smud_pipeline: Res<SmudPipeline>,
+    mut gpu_emitters: ResMut<GpuEmitters>,
+    emitters: Query<&ExtractedEmitter>,
+) {
+    let extracted: HashSet<Entity> = emitters.iter().map(|e| e.main_entity).collect();
+    gpu_emitters
+        .0
+        .retain(|entity, _| extracted.contains(entity));
+
+    let Some(compute_pipeline) = particle_pipeline.compute_pipeline(&pipeline_cache) else {
+        return;
+    };
+
+    let mut encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("smud_particles_encoder"),
+    });
+
+    for emitter in &emitters {
+        let settings = &emitter.particles;
+        let capacity = settings.capacity.max(1);
+        let gpu_emitter = gpu_emitters
+            .0
+            .entry(emitter.main_entity)
+            .or_insert_with(|| {
+                GpuEmitter::new(
+                    &render_device,
+                    &particle_pipeline.layout,
+                    &smud_pipeline,
+                    capacity,
+                )
+            });
+        if gpu_emitter.capacity != capacity {
+            *gpu_emitter = GpuEmitter::new(
+                &render_device,
+                &particle_pipeline.layout,
+                &smud_pipeline,
+                capacity,
+            );
+        }
+
+        gpu_emitter.accumulator += settings.rate.max(0.) * emitter.delta;
+        let spawn_count = (gpu_emitter.accumulator.floor() as u32).min(capacity);
+        gpu_emitter.accumulator -= gpu_emitter.accumulator.floor();
+
+        let color = emitter.color.as_linear_rgba_f32();
+        let end_color = match settings.end_color {
+            Some(end_color) => end_color.as_linear_rgba_f32(),
+            None => [color[0], color[1], color[2], 0.],
+        };
+        let scale = emitter
+            .transform
+            .affine()
+            .transform_vector3(Vec3::X)
+            .length();
+
+        let uniform = EmitterUniform {
+            transform: emitter.transform.compute_matrix().to_cols_array(),
+            color,
+            end_color,
+            params: emitter.params.into(),
+            velocity: settings.velocity.into(),
+            gravity: settings.gravity.into(),
+            spawn_start: gpu_emitter.next,
+            spawn_count,
+            capacity,
+            seed: gpu_emitter.frame_count,
+            delta: emitter.delta,
+            lifetime: settings.lifetime,
+            spread: settings.spread,
+            radius: settings.radius,
+            frame: emitter.frame,
+            scale,
+            end_scale: settings.end_scale,
+            padding: 0.,
+        };
+        render_queue.write_buffer(&gpu_emitter.uniform, 0, bytemuck::bytes_of(&uniform));
+
+        gpu_emitter.next = (gpu_emitter.next + spawn_count) % capacity;
+        gpu_emitter.frame_count = gpu_emitter.frame_count.wrapping_add(1);
+
+        let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("smud_particles_pass"),
+        });
+        pass.set_pipeline(compute_pipeline);
+        pass.set_bind_group(0, &gpu_emitter.bind_group, &[]);
+        pass.dispatch_workgroups(capacity.div_ceil(WORKGROUP_SIZE), 1, 1);
+    }
+
+    render_queue.submit([encoder.finish()]);
+}
+
+fn queue_particles(
+    draw_functions: Res<DrawFunctions<Transparent2d>>,
+    smud_pipeline: Res<SmudPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<SmudPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    msaa: Res<Msaa>,
+    settings: Res<SmudSettings>,
+    emitters: Query<(Entity, &ExtractedEmitter)>,
+    mut views: Query<(
+        &mut RenderPhase<Transparent2d>,
+        &ExtractedView,
+        Option<&SmudPixelArt>,
+    )>,
+) {
+    let draw_function = draw_functions.read().get_id::<DrawSmudParticles>().unwrap();
+
+    let mesh_key = PipelineKey::from_msaa_samples(msaa.samples())
+        | PipelineKey::from_primitive_topology(PrimitiveTopology::TriangleStrip);
+
+    for (mut transparent_phase, view, pixel_art) in &mut views {
+        for (entity, emitter) in &emitters {
+            let shader = (emitter.sdf_shader.id(), emitter.fill_shader.id());
+            if !smud_pipeline.shaders.0.contains_key(&shader) {
+                continue;
+            }
+            let key = SmudPipelineKey {
+                mesh: mesh_key,
+                shader,
+                hdr: view.hdr,
+                three_d: None,
+                screen_space: false,
+                anti_aliasing: settings.anti_aliasing,
+                pixel_art: pixel_art.map(|pixel_art| pixel_art.pixel_scale.max(1)),
+            };
+            let pipeline = pipelines.specialize(&pipeline_cache, &smud_pipeline, key);
+            if pipeline == CachedRenderPipelineId::INVALID {
+                continue;
+            }
+
+            transparent_phase.add(Transparent2d {
+                draw_function,
+                pipeline,
+                entity,
+                sort_key: FloatOrd(emitter.transform.translation().z),
+                // All particles of the emitter are drawn as one item
+                batch_range: 0..1,
+                dynamic_offset: None,
+            });
+        }
+    }
+}
+
+pub(crate) type DrawSmudParticles = (SetItemPipeline, SetShapeViewBindGroup<0>, DrawParticles);
+
+pub(crate) struct DrawParticles;
+impl<P: PhaseItem> RenderCommand<P> for DrawParticles {
+    type Param = (SRes<GpuEmitters>, SRes<SmudPipeline>);
+    type ViewWorldQuery = ();
+    type ItemWorldQuery = Read<ExtractedEmitter>;
+
+    fn render<'w>(
+        _item: &P,
+        _view: (),
+        emitter: &'_ ExtractedEmitter,
+        (gpu_emitters, pipeline): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(gpu_emitter) = gpu_emitters.into_inner().0.get(&emitter.main_entity) else {
+            return RenderCommandResult::Failure;
+        };
+        match (
+            pipeline.instance_group(emitter.sdf_shader.id()),
+            &gpu_emitter.instance_bind_group,
+        ) {
+            (Some(group), Some(bind_group)) => pass.set_bind_group(group as usize, bind_group, &[]),
+            _ => pass.set_vertex_buffer(0, gpu_emitter.instances.slice(..)),
+        }
+        pass.draw(0..4, 0..gpu_emitter.capacity);
+        RenderCommandResult::Success
+    }
+}
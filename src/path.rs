@@ -0,0 +1,413 @@
+use std::f32::consts::SQRT_2;
+
+use bevy::{prelude::*, render::Extract};
+
+use crate::{
+    shader_loading::PATH_SDF_HANDLE,
+    time::{instance_time, SmudTime},
+    ExtractedShape, ExtractedShapes, ShapeChanged, SmudDash, SmudShape,
+};
+
+/// Number of quadratic segments each cubic segment is approximated with
+const CUBIC_SUBDIVISIONS: usize = 4;
+
+/// Number of lines each quadratic segment is approximated with when measuring its length
+const LENGTH_SUBDIVISIONS: usize = 8;
+
+/// A drawing command of a [`SmudPath`]
+#[derive(Reflect, Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+    /// Start a new sub-path at the given point
+    MoveTo(Vec2),
+    /// Straight line to the given point
+    LineTo(Vec2),
+    /// Quadratic bezier curve
+    QuadTo {
+        /// The control point
+        control: Vec2,
+        /// The end point
+        to: Vec2,
+    },
+    /// Cubic bezier curve, approximated by several quadratic curves
+    CubicTo {
+        /// The first control point
+        control1: Vec2,
+        /// The second control point
+        control2: Vec2,
+        /// The end point
+        to: Vec2,
+    },
+    /// Close the current sub-path with a straight line to its start
+    Close,
+}
+
+/// The shape of the open ends of a [`SmudPath`]
+///
+/// The order needs to match the constants in `path.wgsl`
+#[derive(Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StrokeCap {
+    /// The stroke ends exactly at the end point
+    #[default]
+    Butt,
+    /// The stroke ends with a half circle
+    Round,
+    /// The stroke ends half its width beyond the end point
+    Square,
+}
+
+/// The shape of the corners between the segments of a [`SmudPath`]
+///
+/// The order needs to match the constants in `path.wgsl`
+#[derive(Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StrokeJoin {
+    /// Sharp corners, falling back to bevel beyond the miter limit
+    #[default]
+    Miter,
+    /// Rounded corners
+    Round,
+    /// Corners cut off by a straight line
+    Bevel,
+}
+
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+/// A stroked path of lines and curves, replacing the sdf of the [`SmudShape`] on the same entity
+///
+/// Each segment is drawn as its own instance with a tight frame, so long winding paths don't
+/// cover large areas of the screen. The color and fill of the `SmudShape` are used, but its frame
+/// is ignored.
+///
+/// Like [`SmudPolygon`](crate::SmudPolygon), the path data is stored in a storage buffer, which
+/// is not supported on WebGL2.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_smud::{SmudPath, StrokeCap};
+/// let path = SmudPath::new(4.)
+///     .move_to(Vec2::ZERO)
+///     .cubic_to(Vec2::new(100., 0.), Vec2::new(0., 100.), Vec2::new(100., 100.))
+///     .with_cap(StrokeCap::Round);
+/// ```
+pub struct SmudPath {
+    /// The commands making up the path
+    pub commands: Vec<PathCommand>,
+    /// Thickness of the stroke
+    pub width: f32,
+    /// Shape of the open ends of the path
+    pub cap: StrokeCap,
+    /// Shape of the corners
+    pub join: StrokeJoin,
+    /// Maximum ratio between the miter length and half the stroke width before miter joins
+    /// fall back to bevel joins
+    pub miter_limit: f32,
+    /// Extra space around the stroke covered by the frames of the segments
+    ///
+    /// Increase this for fills that extend beyond the edge of the shape.
+    pub padding: f32,
+}
+
+impl Default for SmudPath {
+    fn default() -> Self {
+        Self {
+            commands: Vec::new(),
+            width: 1.,
+            cap: default(),
+            join: default(),
+            miter_limit: 4.,
+            padding: 2.,
+        }
+    }
+}
+
+impl SmudPath {
+    /// Creates an empty path with the given stroke width
+    pub fn new(width: f32) -> Self {
+        Self { width, ..default() }
+    }
+
+    /// Starts a new sub-path
+    pub fn move_to(mut self, to: Vec2) -> Self {
+        self.commands.push(PathCommand::MoveTo(to));
+        self
+    }
+
+    /// Adds a straight line
+    pub fn line_to(mut self, to: Vec2) -> Self {
+        self.commands.push(PathCommand::LineTo(to));
+        self
+    }
+
+    /// Adds a quadratic bezier curve
+    pub fn quad_to(mut self, control: Vec2, to: Vec2) -> Self {
+        self.commands.push(PathCommand::QuadTo { control, to });
+        self
+    }
+
+    /// Adds a cubic bezier curve
+    pub fn cubic_to(mut self, control1: Vec2, control2: Vec2, to: Vec2) -> Self {
+        self.commands.push(PathCommand::CubicTo {
+            control1,
+            control2,
+            to,
+        });
+        self
+    }
+
+    /// Closes the current sub-path
+    pub fn close(mut self) -> Self {
+        self.commands.push(PathCommand::Close);
+        self
+    }
+
+    /// Sets the cap style
+    pub fn with_cap(mut self, cap: StrokeCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    /// Sets the join style
+    pub fn with_join(mut self, join: StrokeJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    /// Flattens the commands into linked line and quadratic segments
+    pub(crate) fn segments(&self) -> Vec<PathSegment> {
+        let mut segments = Vec::new();
+        let mut subpath_start = 0;
+        let mut start = Vec2::ZERO;
+        let mut pen = Vec2::ZERO;
+
+        for command in &self.commands {
+            match *command {
+                PathCommand::MoveTo(to) => {
+                    link_subpath(&mut segments[subpath_start..], subpath_start, false);
+                    subpath_start = segments.len();
+                    start = to;
+                    pen = to;
+                }
+                PathCommand::LineTo(to) => {
+                    if to != pen {
+                        segments.push(PathSegment::new(pen, pen, to));
+                    }
+                    pen = to;
+                }
+                PathCommand::QuadTo { control, to } => {
+                    segments.push(PathSegment::new(pen, control, to));
+                    pen = to;
+                }
+                PathCommand::CubicTo {
+                    control1,
+                    control2,
+                    to,
+                } => {
+                    segments.extend(cubic_to_quads(pen, control1, control2, to));
+                    pen = to;
+                }
+                PathCommand::Close => {
+                    if pen != start {
+                        segments.push(PathSegment::new(pen, pen, start));
+                    }
+                    link_subpath(&mut segments[subpath_start..], subpath_start, true);
+                    subpath_start = segments.len();
+                    pen = start;
+                }
+            }
+        }
+
+        link_subpath(&mut segments[subpath_start..], subpath_start, false);
+        segments
+    }
+}
+
+/// A line (control point equal to the start) or quadratic curve, linked to its neighbors
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PathSegment {
+    pub prev: i32,
+    pub next: i32,
+    pub start: Vec2,
+    pub control: Vec2,
+    pub end: Vec2,
+    /// Distance along the sub-path where the segment starts and ends
+    pub arc: Vec2,
+    /// Length of the whole sub-path
+    pub subpath_length: f32,
+}
+
+impl PathSegment {
+    fn new(start: Vec2, control: Vec2, end: Vec2) -> Self {
+        Self {
+            prev: -1,
+            next: -1,
+            start,
+            control,
+            end,
+            arc: Vec2::ZERO,
+            subpath_length: 0.,
+        }
+    }
+
+    /// Approximate length of the curve
+    fn length(&self) -> f32 {
+        let point = |t: f32| {
+            let s = 1. - t;
+            self.start * s * s + self.control * 2. * s * t + self.end * t * t
+        };
+        (0..LENGTH_SUBDIVISIONS)
+            .map(|i| {
+                let t0 = i as f32 / LENGTH_SUBDIVISIONS as f32;
+                let t1 = (i + 1) as f32 / LENGTH_SUBDIVISIONS as f32;
+                point(t0).distance(point(t1))
+            })
+            .sum()
+    }
+
+    /// Bounding box of the control polygon, which contains the curve
+    fn bounds(&self) -> Rect {
+        Rect::from_corners(self.start, self.end).union_point(self.control)
+    }
+}
+
+/// Links the segments of a sub-path to each other, the first one having index `first`, and
+/// measures where they are along it
+fn link_subpath(segments: &mut [PathSegment], first: usize, closed: bool) {
+    let n = segments.len();
+    let mut length = 0.;
+    for segment in segments.iter_mut() {
+        let start = length;
+        length += segment.length();
+        segment.arc = Vec2::new(start, length);
+    }
+    for (i, segment) in segments.iter_mut().enumerate() {
+        segment.subpath_length = length;
+        let index = |i: usize| (first + i) as i32;
+        segment.prev = match i {
+            0 if closed => index(n - 1),
+            0 => -1,
+            _ => index(i - 1),
+        };
+        segment.next = match i + 1 {
+            next if next < n => index(next),
+            _ if closed => index(0),
+            _ => -1,
+        };
+    }
+}
+
+fn cubic_to_quads(p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2) -> Vec<PathSegment> {
+    let point = |t: f32| {
+        let s = 1. - t;
+        p0 * s * s * s + p1 * 3. * s * s * t + p2 * 3. * s * t * t + p3 * t * t * t
+    };
+    let derivative = |t: f32| {
+        let s = 1. - t;
+        (p1 - p0) * 3. * s * s + (p2 - p1) * 6. * s * t + (p3 - p2) * 3. * t * t
+    };
+
+    (0..CUBIC_SUBDIVISIONS)
+        .map(|i| {
+            let t0 = i as f32 / CUBIC_SUBDIVISIONS as f32;
+            let t1 = (i + 1) as f32 / CUBIC_SUBDIVISIONS as f32;
+            let (a, b) = (point(t0), point(t1));
+            // Place the control point so the end tangents of the quad match the cubic
+            let dt = t1 - t0;
+            let q0 = a + derivative(t0) * dt / 3.;
+            let q1 = b - derivative(t1) * dt / 3.;
+            let control = (q0 * 3. - a + q1 * 3. - b) / 4.;
+            PathSegment::new(a, control, b)
+        })
+        .collect()
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn extract_paths(
+    mut commands: Commands,
+    mut extracted_shapes: ResMut<ExtractedShapes>,
+    path_query: Extract<
+        Query<(
+            Entity,
+            &ViewVisibility,
+            &SmudShape,
+            &SmudPath,
+            &GlobalTransform,
+            Option<&SmudDash>,
+            Option<&SmudTime>,
+        )>,
+    >,
+    changed_query: Extract<Query<(), Or<ShapeChanged>>>,
+) {
+    for (entity, view_visibility, shape, path, transform, dash, shape_time) in path_query.iter() {
+        if !view_visibility.get() {
+            continue;
+        }
+
+        let segments = path.segments();
+        if segments.is_empty() {
+            continue;
+        }
+
+        let header = [
+            Vec2::new(path.width, path.miter_limit),
+            Vec2::new(path.cap as u8 as f32, path.join as u8 as f32),
+        ];
+        let Some(offset) = extracted_shapes.push_points(
+            entity,
+            header.into_iter().chain(segments.iter().flat_map(|s| {
+                [
+                    Vec2::new(s.prev as f32, s.next as f32),
+                    s.start,
+                    s.control,
+                    s.end,
+                    s.arc,
+                ]
+            })),
+        ) else {
+            continue;
+        };
+
+        // How far the stroke can reach beyond the curve, including corners and dashes along its
+        // edges
+        let reach = path.width / 2.
+            * match path.join {
+                StrokeJoin::Miter => path.miter_limit.max(SQRT_2),
+                _ => SQRT_2,
+            }
+            + dash.map_or(0., |dash| dash.width / 2.);
+
+        let changed = changed_query.contains(entity) || extracted_shapes.points_moved(entity);
+
+        for (index, segment) in segments.iter().enumerate() {
+            let bounds = segment.bounds();
+            let center = bounds.center();
+            let frame = bounds.half_size().max_element() + reach + path.padding;
+            let segment_transform =
+                *transform * GlobalTransform::from_translation(center.extend(0.));
+
+            // Each segment gets its own render world entity, so it can be sorted and batched
+            extracted_shapes.shapes.insert(
+                commands.spawn_empty().id(),
+                ExtractedShape {
+                    color: shape.color,
+                    frame,
+                    params: Vec4::new(
+                        offset,
+                        segments.len() as f32,
+                        index as f32,
+                        segment.subpath_length,
+                    ),
+                    dash: dash.map_or(Vec4::ZERO, SmudDash::instance_data),
+                    sdf_shader: PATH_SDF_HANDLE,
+                    fill_shader: shape.fill.clone_weak(),
+                    transform: segment_transform,
+                    time: instance_time(shape_time),
+                    atlas: None,
+                    three_d: None,
+                    changed,
+                    key: (entity, index as u32),
+                    anchor: Vec2::ZERO,
+                    screen_space: false,
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use bevy::{
+    math::Affine3A,
+    prelude::*,
+    render::{extract_component::ExtractComponent, view::ExtractedView},
+};
+
+/// Renders shapes as pixel art with the 2d camera it's added to
+///
+/// Shapes are snapped to the pixel grid of the camera and get hard edges, with the sdf sampled
+/// once per art pixel, so they look consistent next to pixel-art sprites. Other cameras still
+/// render the same shapes smoothly.
+///
+/// This overrides the anti-aliasing mode in [`SmudSettings`](crate::SmudSettings) for the
+/// camera.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_smud::SmudPixelArt;
+/// fn setup(mut commands: Commands) {
+///     commands.spawn((
+///         Camera2dBundle {
+///             projection: OrthographicProjection {
+///                 scale: 0.25,
+///                 ..default()
+///             },
+///             ..default()
+///         },
+///         // Each world unit covers four screen pixels
+///         SmudPixelArt { pixel_scale: 4 },
+///     ));
+/// }
+/// ```
+#[derive(Component, ExtractComponent, Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct SmudPixelArt {
+    /// The size of an art pixel in screen pixels
+    ///
+    /// Use 1 when rendering to a low resolution target that is scaled up afterwards.
+    pub pixel_scale: u32,
+}
+
+impl Default for SmudPixelArt {
+    fn default() -> Self {
+        Self { pixel_scale: 1 }
+    }
+}
+
+/// The art pixel grid of a view, used for snapping the positions of shapes
+pub(crate) struct PixelGrid {
+    view: Affine3A,
+    inverse_view: Affine3A,
+    /// Size of a screen pixel in view space
+    pixel_size: Vec2,
+    /// Position of the center of the view on the render target, in pixels
+    origin: Vec2,
+    /// Size of an art pixel in screen pixels
+    scale: f32,
+}
+
+impl PixelGrid {
+    pub(crate) fn new(view: &ExtractedView, pixel_art: &SmudPixelArt) -> Self {
+        let viewport = view.viewport.as_vec4();
+        let view_affine = view.transform.affine();
+        Self {
+            view: view_affine,
+            inverse_view: view_affine.inverse(),
+            pixel_size: 2.
+                / (Vec2::new(view.projection.x_axis.x, view.projection.y_axis.y) * viewport.zw()),
+            origin: viewport.xy() + viewport.zw() / 2.,
+            scale: pixel_art.pixel_scale.max(1) as f32,
+        }
+    }
+
+    /// Moves a world position to the nearest corner of an art pixel
+    pub(crate) fn snap(&self, position: Vec3) -> Vec3 {
+        let p = self.inverse_view.transform_point3(position);
+        // Pixels on the render target go down
+        let pixel = self.origin + Vec2::new(p.x, -p.y) / self.pixel_size;
+        let snapped = (pixel / self.scale).round() * self.scale;
+        let offset = (snapped - self.origin) * self.pixel_size;
+        self.view
+            .transform_point3(Vec3::new(offset.x, -offset.y, p.z))
+    }
+}
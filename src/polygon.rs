@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+
+/// How the inside of a self-intersecting [`SmudPolygon`] is determined
+#[derive(Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    /// Points are inside if the polygon winds around them at least once
+    #[default]
+    NonZero,
+    /// Points are inside if a ray from them crosses the polygon an odd number of times
+    EvenOdd,
+}
+
+#[derive(Component, Reflect, Debug, Default, Clone)]
+#[reflect(Component)]
+/// A closed polygon, replacing the sdf of the [`SmudShape`](crate::SmudShape) on the same entity
+///
+/// The points can be split into several closed rings, for shapes with holes or several parts,
+/// see [`SmudPolygon::from_rings`].
+///
+/// The points are uploaded to a storage buffer shared by all polygons, so any number of
+/// different polygons can be drawn with a single shader. The color, fill and frame of the
+/// `SmudShape` are still used, so make sure the frame covers all the points. The buffer holds
+/// at most 2^24 points per frame, and shapes that don't fit are skipped.
+///
+/// Note: storage buffers are not supported on WebGL2, so polygons, along with polylines, paths and
+/// csg shapes, are skipped there with a warning.
+pub struct SmudPolygon {
+    /// The vertices of the polygon, in the shape's local space
+    pub points: Vec<Vec2>,
+    /// How to determine the inside of self-intersecting polygons
+    pub fill_rule: FillRule,
+    /// The number of points in each ring, if the points form several rings
+    ///
+    /// Empty for a single ring. The polygon isn't drawn if the lengths don't add up to the
+    /// number of points.
+    pub ring_lengths: Vec<usize>,
+}
+
+impl SmudPolygon {
+    /// Creates a polygon with the [`FillRule::NonZero`] fill rule
+    pub fn new(points: impl Into<Vec<Vec2>>) -> Self {
+        Self {
+            points: points.into(),
+            fill_rule: default(),
+            ring_lengths: Vec::new(),
+        }
+    }
+
+    /// Creates a polygon with several rings, using the [`FillRule::NonZero`] fill rule
+    ///
+    /// With the nonzero rule, rings winding the opposite way of the outer ring cut holes into
+    /// it, while the [`FillRule::EvenOdd`] rule cuts holes regardless of the direction.
+    pub fn from_rings(rings: impl IntoIterator<Item = Vec<Vec2>>) -> Self {
+        let mut polygon = Self::default();
+        for ring in rings {
+            polygon.ring_lengths.push(ring.len());
+            polygon.points.extend(ring);
+        }
+        polygon
+    }
+
+    /// Sets the fill rule of the polygon
+    pub fn with_fill_rule(mut self, fill_rule: FillRule) -> Self {
+        self.fill_rule = fill_rule;
+        self
+    }
+
+    /// The end of each ring in the points, or `None` if the ring lengths don't match the points
+    pub(crate) fn ring_ends(&self) -> Option<Vec<usize>> {
+        let ends: Vec<usize> = self
+            .ring_lengths
+            .iter()
+            .scan(0, |end, length| {
+                *end += length;
+                Some(*end)
+            })
+            .collect();
+        match ends.last() {
+            Some(&end) if end != self.points.len() => None,
+            _ => Some(ends),
+        }
+    }
+}
+
+impl From<Vec<Vec2>> for SmudPolygon {
+    fn from(points: Vec<Vec2>) -> Self {
+        Self::new(points)
+    }
+}
+
+#[derive(Component, Reflect, Debug, Default, Clone)]
+#[reflect(Component)]
+/// An open polyline with round caps and joins, replacing the sdf of the
+/// [`SmudShape`](crate::SmudShape) on the same entity
+///
+/// Like [`SmudPolygon`], the points are stored in a shared storage buffer. The frame is computed
+/// from the points, like for [`SmudLine`](crate::SmudLine), so the frame and anchor of the
+/// `SmudShape` are ignored.
+pub struct SmudPolyline {
+    /// The points of the line, in the shape's local space
+    pub points: Vec<Vec2>,
+    /// The thickness of the line
+    pub width: f32,
+}
+
+impl SmudPolyline {
+    /// Creates a polyline with the given thickness
+    pub fn new(points: impl Into<Vec<Vec2>>, width: f32) -> Self {
+        Self {
+            points: points.into(),
+            width,
+        }
+    }
+
+    /// The bounds of the points, not including the width
+    pub(crate) fn bounds(&self) -> Rect {
+        self.points.iter().fold(
+            Rect::from_center_size(self.points[0], Vec2::ZERO),
+            |rect, point| rect.union_point(*point),
+        )
+    }
+}
@@ -0,0 +1,302 @@
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    utils::HashMap,
+};
+use thiserror::Error;
+
+use crate::{Frame, SmudAntiAliasing, SmudShape, DEFAULT_FILL_HANDLE, SIMPLE_FILL_HANDLE};
+
+/// Cpu versions of the built-in fills
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuFill {
+    /// `fills/simple.wgsl`, used by [`SIMPLE_FILL_HANDLE`]
+    Simple,
+    /// `fills/outline.wgsl`
+    Outline,
+    /// `fills/cubic_falloff.wgsl`, used by [`DEFAULT_FILL_HANDLE`]
+    CubicFalloff,
+}
+
+impl CpuFill {
+    /// Evaluates the fill for a distance, where `dpd` is the change of the distance to the next
+    /// pixel in x and y, like the wgsl built-ins `dpdx` and `dpdy`
+    ///
+    /// The edges are anti-aliased like `smud::sd_fill_alpha` does with the given mode.
+    pub fn fill(self, d: f32, dpd: Vec2, color: Vec4, anti_aliasing: SmudAntiAliasing) -> Vec4 {
+        match self {
+            CpuFill::Simple => color
+                .truncate()
+                .extend(fill_alpha(d, dpd, anti_aliasing) * color.w),
+            CpuFill::Outline => {
+                let d_2 = (d - 1.).abs() - 1.;
+                color
+                    .truncate()
+                    .extend(fill_alpha(d_2, dpd, anti_aliasing) * color.w)
+            }
+            CpuFill::CubicFalloff => {
+                let d2 = 1. - (d * 0.13);
+                let alpha = (d2 * d2 * d2).clamp(0., 1.) * color.w;
+                let shadow_color = 0.2 * color.truncate();
+                // Doesn't go through `smud::sd_fill_alpha`, so it always uses `fwidth`
+                let aaf = 0.7 / (dpd.x.abs() + dpd.y.abs());
+                let c = color.truncate().lerp(shadow_color, (d * aaf).clamp(0., 1.));
+                c.extend(alpha)
+            }
+        }
+    }
+}
+
+/// `smoothstep` as defined by wgsl, which also works with reversed edges
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0., 1.);
+    t * t * (3. - 2. * t)
+}
+
+/// Cpu version of `smud::sd_fill_alpha`
+fn fill_alpha(distance: f32, dpd: Vec2, anti_aliasing: SmudAntiAliasing) -> f32 {
+    let aaf = match anti_aliasing {
+        SmudAntiAliasing::Fwidth => 0.71 * (dpd.x.abs() + dpd.y.abs()),
+        SmudAntiAliasing::Dpd => 0.71 * dpd.length(),
+        SmudAntiAliasing::Nearest | SmudAntiAliasing::None | SmudAntiAliasing::Supersampled => 0.,
+    };
+    if aaf <= 0. {
+        return if distance <= 0. { 1. } else { 0. };
+    }
+    smoothstep(aaf, -aaf, distance)
+}
+
+/// Rotated grid of samples within a pixel, in pixels, like the fragment shader uses for
+/// [`SmudAntiAliasing::Supersampled`]
+const SUPERSAMPLE_OFFSETS: [Vec2; 4] = [
+    Vec2::new(-0.125, -0.375),
+    Vec2::new(0.375, -0.125),
+    Vec2::new(0.125, 0.375),
+    Vec2::new(-0.375, 0.125),
+];
+
+/// A signed distance function evaluated on the cpu
+pub type CpuSdf = Box<dyn Fn(Vec2) -> f32 + Send + Sync>;
+
+/// Possible errors produced by [`SmudRasterizer`]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum RasterizeError {
+    /// A shape uses an sdf without a registered cpu version
+    #[error("No cpu sdf registered for shader {0:?}")]
+    MissingSdf(AssetId<Shader>),
+    /// A shape uses a fill without a registered cpu version
+    #[error("No cpu fill registered for shader {0:?}")]
+    MissingFill(AssetId<Shader>),
+}
+
+/// Renders [`SmudShape`]s on the cpu, without a gpu or even a running app
+///
+/// Since wgsl can't be evaluated on the cpu, each sdf shader needs a Rust version, which can be
+/// written using the primitives in [`sdf`](crate::sdf). The built-in fills are registered by
+/// default. The result closely matches what the gpu renders with a default 2d camera, which makes
+/// it useful for golden-image tests and thumbnails on headless machines.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_smud::{sdf, SmudRasterizer, SmudShape, Frame, SIMPLE_FILL_HANDLE};
+/// let circle = Handle::weak_from_u128(1);
+/// let mut rasterizer = SmudRasterizer::new(64, 64);
+/// rasterizer.register_sdf(&circle, |p| sdf::sd_circle(p, 20.));
+///
+/// let shape = SmudShape {
+///     sdf: circle,
+///     fill: SIMPLE_FILL_HANDLE,
+///     frame: Frame::Quad(25.),
+///     ..default()
+/// };
+/// let image = rasterizer.render([(&shape, &GlobalTransform::IDENTITY)]).unwrap();
+/// ```
+///
+/// The result is a regular bevy [`Image`], which can be saved with
+/// `image.try_into_dynamic()?.save("shape.png")`, given bevy's `png` feature.
+pub struct SmudRasterizer {
+    /// Width of the image in pixels
+    pub width: u32,
+    /// Height of the image in pixels
+    pub height: u32,
+    /// Color of the pixels not covered by any shape
+    pub clear_color: Color,
+    /// World position at the center of the image
+    pub center: Vec2,
+    /// Pixels per world unit
+    pub zoom: f32,
+    /// How the edges of shapes are anti-aliased, like [`SmudSettings`](crate::SmudSettings) does
+    /// on the gpu
+    pub anti_aliasing: SmudAntiAliasing,
+    sdfs: HashMap<AssetId<Shader>, CpuSdf>,
+    fills: HashMap<AssetId<Shader>, CpuFill>,
+}
+
+impl SmudRasterizer {
+    /// Creates a rasterizer with a transparent background, centered on the origin
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut fills = HashMap::default();
+        fills.insert(SIMPLE_FILL_HANDLE.id(), CpuFill::Simple);
+        fills.insert(DEFAULT_FILL_HANDLE.id(), CpuFill::CubicFalloff);
+        Self {
+            width,
+            height,
+            clear_color: Color::NONE,
+            center: Vec2::ZERO,
+            zoom: 1.,
+            anti_aliasing: default(),
+            sdfs: default(),
+            fills,
+        }
+    }
+
+    /// Registers the cpu version of an sdf shader
+    pub fn register_sdf(
+        &mut self,
+        sdf: &Handle<Shader>,
+        f: impl Fn(Vec2) -> f32 + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.sdfs.insert(sdf.id(), Box::new(f));
+        self
+    }
+
+    /// Registers the cpu version of a fill shader
+    pub fn register_fill(&mut self, fill: &Handle<Shader>, cpu_fill: CpuFill) -> &mut Self {
+        self.fills.insert(fill.id(), cpu_fill);
+        self
+    }
+
+    /// Renders the given shapes, sorted by depth like on the gpu
+    pub fn render<'a>(
+        &self,
+        shapes: impl IntoIterator<Item = (&'a SmudShape, &'a GlobalTransform)>,
+    ) -> Result<Image, RasterizeError> {
+        let mut shapes: Vec<_> = shapes.into_iter().collect();
+        shapes.sort_by(|(_, a), (_, b)| a.translation().z.total_cmp(&b.translation().z));
+
+        let clear_color = Vec4::from(self.clear_color.as_linear_rgba_f32());
+        let mut pixels = vec![clear_color; (self.width * self.height) as usize];
+
+        for (shape, transform) in shapes {
+            let sdf = self
+                .sdfs
+                .get(&shape.sdf.id())
+                .ok_or(RasterizeError::MissingSdf(shape.sdf.id()))?;
+            let fill = *self
+                .fills
+                .get(&shape.fill.id())
+                .ok_or(RasterizeError::MissingFill(shape.fill.id()))?;
+            self.draw_shape(&mut pixels, shape, transform, sdf, fill);
+        }
+
+        let data = pixels
+            .iter()
+            .flat_map(|c| {
+                Color::rgba_linear(c.x, c.y, c.z, c.w)
+                    .as_rgba_u8()
+                    .into_iter()
+            })
+            .collect();
+
+        Ok(Image::new(
+            Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+        ))
+    }
+
+    /// Renders all shapes in a world that are not hidden
+    pub fn render_world(&self, world: &mut World) -> Result<Image, RasterizeError> {
+        let mut query = world.query::<(&SmudShape, &GlobalTransform, Option<&Visibility>)>();
+        self.render(
+            query
+                .iter(world)
+                .filter(|(_, _, visibility)| visibility != &Some(&Visibility::Hidden))
+                .map(|(shape, transform, _)| (shape, transform)),
+        )
+    }
+
+    fn pixel_to_world(&self, x: f32, y: f32) -> Vec2 {
+        let size = Vec2::new(self.width as f32, self.height as f32);
+        self.center + (Vec2::new(x, size.y - y) - size / 2.) / self.zoom
+    }
+
+    fn draw_shape(
+        &self,
+        pixels: &mut [Vec4],
+        shape: &SmudShape,
+        transform: &GlobalTransform,
+        sdf: &CpuSdf,
+        fill: CpuFill,
+    ) {
+        let Frame::Quad(frame) = shape.frame;
+        let color = Vec4::from(shape.color.as_linear_rgba_f32());
+        let affine = shape.anchored(transform).affine();
+        let inverse = affine.inverse();
+
+        // Only visit the pixels covered by the quad of the shape
+        let corners = [(-1., -1.), (1., -1.), (-1., 1.), (1., 1.)].map(|(x, y)| {
+            let world = affine
+                .transform_point3(Vec3::new(x * frame, y * frame, 0.))
+                .truncate();
+            let size = Vec2::new(self.width as f32, self.height as f32);
+            let pixel = (world - self.center) * self.zoom + size / 2.;
+            Vec2::new(pixel.x, size.y - pixel.y)
+        });
+        let min = corners.iter().fold(Vec2::MAX, |a, b| a.min(*b)).floor();
+        let max = corners.iter().fold(Vec2::MIN, |a, b| a.max(*b)).ceil();
+        let x_range = (min.x.max(0.) as u32)..(max.x.min(self.width as f32).max(0.) as u32);
+        let y_range = (min.y.max(0.) as u32)..(max.y.min(self.height as f32).max(0.) as u32);
+
+        // Offsets of neighboring pixels in the local space of the shape, used for derivatives
+        let dx = inverse.transform_vector3(Vec3::X / self.zoom).truncate();
+        let dy = inverse.transform_vector3(-Vec3::Y / self.zoom).truncate();
+        let shade = |p: Vec2| {
+            let d = sdf(p);
+            let dpd = Vec2::new(sdf(p + dx) - d, sdf(p + dy) - d);
+            fill.fill(d, dpd, color, self.anti_aliasing)
+        };
+
+        for y in y_range {
+            for x in x_range.clone() {
+                let world = self.pixel_to_world(x as f32 + 0.5, y as f32 + 0.5);
+                let p = inverse.transform_point3(world.extend(0.)).truncate();
+                if p.x.abs() > frame || p.y.abs() > frame {
+                    continue;
+                }
+
+                // Sampled like the fragment shader does for each mode
+                let src = match self.anti_aliasing {
+                    SmudAntiAliasing::Nearest => shade(p.floor() + 0.5),
+                    SmudAntiAliasing::Supersampled => {
+                        let sum: Vec4 = SUPERSAMPLE_OFFSETS
+                            .iter()
+                            .map(|offset| {
+                                let sample = shade(p + offset.x * dx + offset.y * dy);
+                                (sample.truncate() * sample.w).extend(sample.w)
+                            })
+                            .sum();
+                        (sum.truncate() / sum.w.max(1e-5)).extend(sum.w / 4.)
+                    }
+                    _ => shade(p),
+                };
+                if !src.is_finite() {
+                    continue;
+                }
+                let src_alpha = src.w.clamp(0., 1.);
+
+                // Same as `BlendState::ALPHA_BLENDING`
+                let dst = &mut pixels[(y * self.width + x) as usize];
+                let rgb = src.truncate() * src_alpha + dst.truncate() * (1. - src_alpha);
+                let alpha = src_alpha + dst.w * (1. - src_alpha);
+                *dst = rgb.extend(alpha);
+            }
+        }
+    }
+}
@@ -0,0 +1,642 @@
+//! Rust versions of the sdf primitives and operators in `smud.wgsl`
+//!
+//! These mirror the wgsl functions as closely as possible, so shapes can be evaluated on the
+//! cpu, i.e. by [`SmudRasterizer`](crate::SmudRasterizer). Functions with a trailing underscore
+//! in wgsl (`sd_star_5_`) drop it here (`sd_star_5`).
+
+// Constants are kept identical to the wgsl versions, so both give the same results
+#![allow(clippy::excessive_precision, clippy::approx_constant)]
+
+use bevy::math::{Mat2, Vec2, Vec3, Vec4};
+
+/// `sign` as defined by wgsl, returning 0 for 0
+fn sign(x: f32) -> f32 {
+    if x > 0. {
+        1.
+    } else if x < 0. {
+        -1.
+    } else {
+        0.
+    }
+}
+
+fn sign2(v: Vec2) -> Vec2 {
+    Vec2::new(sign(v.x), sign(v.y))
+}
+
+/// `select(f, t, cond)` as defined by wgsl
+fn select<T>(f: T, t: T, cond: bool) -> T {
+    if cond {
+        t
+    } else {
+        f
+    }
+}
+
+fn mix(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// wgsl matrices are column-major, like the arguments of `mat2x2<f32>(...)`
+fn mat2(a: f32, b: f32, c: f32, d: f32) -> Mat2 {
+    Mat2::from_cols(Vec2::new(a, b), Vec2::new(c, d))
+}
+
+/// `a.x * b.x - a.y * b.y`
+pub fn ndot(a: Vec2, b: Vec2) -> f32 {
+    a.x * b.x - a.y * b.y
+}
+
+/// Squared length
+pub fn dot2(a: Vec2) -> f32 {
+    a.dot(a)
+}
+
+/// Circle with radius `r`
+pub fn sd_circle(p: Vec2, r: f32) -> f32 {
+    p.length() - r
+}
+
+/// Box with half-size `b` and corner radii `r` (top right, bottom right, top left, bottom left)
+pub fn sd_rounded_box(p: Vec2, b: Vec2, r: Vec4) -> f32 {
+    let tmp = select(Vec2::new(r.z, r.w), Vec2::new(r.x, r.y), p.x > 0.);
+    let r = select(tmp.y, tmp.x, p.y > 0.);
+    let q = p.abs() - b + r;
+    q.x.max(q.y).min(0.) + q.max(Vec2::ZERO).length() - r
+}
+
+/// Box with half-size `b`
+pub fn sd_box(p: Vec2, b: Vec2) -> f32 {
+    let d = p.abs() - b;
+    d.max(Vec2::ZERO).length() + d.x.max(d.y).min(0.)
+}
+
+/// Box from `a` to `b` with thickness `th`
+pub fn sd_oriented_box(p: Vec2, a: Vec2, b: Vec2, th: f32) -> f32 {
+    let l = (b - a).length();
+    let d = (b - a) / l;
+    let q = mat2(d.x, -d.y, d.y, d.x) * (p - (a + b) * 0.5);
+    let q = q.abs() - Vec2::new(l, th) * 0.5;
+    q.max(Vec2::ZERO).length() + q.x.max(q.y).min(0.)
+}
+
+/// Line segment from `a` to `b`
+pub fn sd_segment(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let pa = p - a;
+    let ba = b - a;
+    let h = (pa.dot(ba) / ba.dot(ba)).clamp(0., 1.);
+    (pa - ba * h).length()
+}
+
+/// Rhombus with half-size `b`
+pub fn sd_rhombus(p: Vec2, b: Vec2) -> f32 {
+    let p = p.abs();
+    let h = (ndot(b - 2. * p, b) / b.dot(b)).clamp(-1., 1.);
+    let d = (p - 0.5 * b * Vec2::new(1. - h, 1. + h)).length();
+    d * sign(p.x * b.y + p.y * b.x - b.x * b.y)
+}
+
+/// Trapezoid with bottom half-width `r1`, top half-width `r2` and half-height `he`
+pub fn sd_trapezoid(p: Vec2, r1: f32, r2: f32, he: f32) -> f32 {
+    let k1 = Vec2::new(r2, he);
+    let k2 = Vec2::new(r2 - r1, 2. * he);
+    let p = Vec2::new(p.x.abs(), p.y);
+    let r = select(r2, r1, p.y < 0.);
+    let ca = Vec2::new(p.x - p.x.min(r), p.y.abs() - he);
+    let cb = p - k1 + k2 * ((k1 - p).dot(k2) / dot2(k2)).clamp(0., 1.);
+    let s = select(1., -1., cb.x < 0. && ca.y < 0.);
+    s * dot2(ca).min(dot2(cb)).sqrt()
+}
+
+/// Parallelogram with half-width `wi`, half-height `he` and skew `sk`
+pub fn sd_parallelogram(p: Vec2, wi: f32, he: f32, sk: f32) -> f32 {
+    let e = Vec2::new(sk, he);
+    let mut p = select(p, -p, p.y < 0.);
+    let mut w = p - e;
+    w.x -= w.x.clamp(-wi, wi);
+    let mut d = Vec2::new(w.dot(w), -w.y);
+    let s = p.x * e.y - p.y * e.x;
+    p = select(p, -p, s < 0.);
+    let mut v = p - Vec2::new(wi, 0.);
+    v -= e * (v.dot(e) / e.dot(e)).clamp(-1., 1.);
+    d = d.min(Vec2::new(v.dot(v), wi * he - s.abs()));
+    d.x.sqrt() * sign(-d.y)
+}
+
+/// Equilateral triangle with "radius" `r`
+pub fn sd_equilateral_triangle(p: Vec2, r: f32) -> f32 {
+    let k = 3f32.sqrt();
+    let mut p = Vec2::new(p.x.abs() - r, p.y + r / k);
+    if p.x + k * p.y > 0. {
+        p = Vec2::new(p.x - k * p.y, -k * p.x - p.y) / 2.;
+    }
+    p.x -= p.x.clamp(-2. * r, 0.);
+    -p.length() * sign(p.y)
+}
+
+/// Isosceles triangle with its tip at the origin, and half-width and height `q`
+pub fn sd_triangle_isosceles(p: Vec2, q: Vec2) -> f32 {
+    let p = Vec2::new(p.x.abs(), p.y);
+    let a = p - q * (p.dot(q) / q.dot(q)).clamp(0., 1.);
+    let b = p - q * Vec2::new((p.x / q.x).clamp(0., 1.), 1.);
+    let s = -sign(q.y);
+    let d =
+        Vec2::new(a.dot(a), s * (p.x * q.y - p.y * q.x)).min(Vec2::new(b.dot(b), s * (p.y - q.y)));
+    -d.x.sqrt() * sign(d.y)
+}
+
+/// Triangle with the given corners
+pub fn sd_triangle(p: Vec2, p0: Vec2, p1: Vec2, p2: Vec2) -> f32 {
+    let e0 = p1 - p0;
+    let e1 = p2 - p1;
+    let e2 = p0 - p2;
+
+    let v0 = p - p0;
+    let v1 = p - p1;
+    let v2 = p - p2;
+
+    let pq0 = v0 - e0 * (v0.dot(e0) / e0.dot(e0)).clamp(0., 1.);
+    let pq1 = v1 - e1 * (v1.dot(e1) / e1.dot(e1)).clamp(0., 1.);
+    let pq2 = v2 - e2 * (v2.dot(e2) / e2.dot(e2)).clamp(0., 1.);
+
+    let s = sign(e0.x * e2.y - e0.y * e2.x);
+    let d = Vec2::new(pq0.dot(pq0), s * (v0.x * e0.y - v0.y * e0.x))
+        .min(Vec2::new(pq1.dot(pq1), s * (v1.x * e1.y - v1.y * e1.x)))
+        .min(Vec2::new(pq2.dot(pq2), s * (v2.x * e2.y - v2.y * e2.x)));
+    -d.x.sqrt() * sign(d.y)
+}
+
+/// Capsule with radius `r1` at the origin and `r2` at height `h`
+pub fn sd_uneven_capsule(p: Vec2, r1: f32, r2: f32, h: f32) -> f32 {
+    let p = Vec2::new(p.x.abs(), p.y);
+    let b = (r1 - r2) / h;
+    let a = (1. - b * b).sqrt();
+    let k = p.dot(Vec2::new(-b, a));
+    if k < 0. {
+        return p.length() - r1;
+    }
+    if k > a * h {
+        return (p - Vec2::new(0., h)).length() - r2;
+    }
+    p.dot(Vec2::new(a, b)) - r1
+}
+
+/// Regular pentagon with inner radius `r`
+pub fn sd_pentagon(p: Vec2, r: f32) -> f32 {
+    let k = Vec3::new(0.809016994, 0.587785252, 0.726542528);
+    let mut p = Vec2::new(p.x.abs(), p.y);
+    p -= 2. * Vec2::new(-k.x, k.y).dot(p).min(0.) * Vec2::new(-k.x, k.y);
+    p -= 2. * Vec2::new(k.x, k.y).dot(p).min(0.) * Vec2::new(k.x, k.y);
+    p -= Vec2::new(p.x.clamp(-r * k.z, r * k.z), r);
+    p.length() * sign(p.y)
+}
+
+/// Regular hexagon with inner radius `r`
+pub fn sd_hexagon(p: Vec2, r: f32) -> f32 {
+    let k = Vec3::new(-0.866025404, 0.5, 0.577350269);
+    let mut p = p.abs();
+    p -= 2. * k.truncate().dot(p).min(0.) * k.truncate();
+    p -= Vec2::new(p.x.clamp(-k.z * r, k.z * r), r);
+    p.length() * sign(p.y)
+}
+
+/// Regular octagon with inner radius `r`
+pub fn sd_octagon(p: Vec2, r: f32) -> f32 {
+    let k = Vec3::new(-0.9238795325, 0.3826834323, 0.4142135623);
+    let mut p = p.abs();
+    p -= 2. * Vec2::new(k.x, k.y).dot(p).min(0.) * Vec2::new(k.x, k.y);
+    p -= 2. * Vec2::new(-k.x, k.y).dot(p).min(0.) * Vec2::new(-k.x, k.y);
+    p -= Vec2::new(p.x.clamp(-k.z * r, k.z * r), r);
+    p.length() * sign(p.y)
+}
+
+/// Six-pointed star
+pub fn sd_hexagram(p: Vec2, r: f32) -> f32 {
+    let k = Vec4::new(-0.5, 0.8660254038, 0.5773502692, 1.7320508076);
+    let kxy = Vec2::new(k.x, k.y);
+    let kyx = Vec2::new(k.y, k.x);
+    let mut p = p.abs();
+    p -= 2. * kxy.dot(p).min(0.) * kxy;
+    p -= 2. * kyx.dot(p).min(0.) * kyx;
+    p -= Vec2::new(p.x.clamp(r * k.z, r * k.w), r);
+    p.length() * sign(p.y)
+}
+
+/// Five-pointed star with radius `r` and inner radius factor `rf`
+pub fn sd_star_5(p: Vec2, r: f32, rf: f32) -> f32 {
+    let k1 = Vec2::new(0.809016994375, -0.587785252292);
+    let k2 = Vec2::new(-k1.x, k1.y);
+    let mut p = Vec2::new(p.x.abs(), p.y);
+    p -= 2. * k1.dot(p).max(0.) * k1;
+    p -= 2. * k2.dot(p).max(0.) * k2;
+    p.x = p.x.abs();
+    p.y -= r;
+    let ba = rf * Vec2::new(-k1.y, k1.x) - Vec2::new(0., 1.);
+    let h = (p.dot(ba) / ba.dot(ba)).clamp(0., r);
+    (p - ba * h).length() * sign(p.y * ba.x - p.x * ba.y)
+}
+
+/// Glsl-style `mod`, which always has the sign of `y`
+pub fn modulo(x: f32, y: f32) -> f32 {
+    x - y * (x / y).floor()
+}
+
+/// Star with `n` points, where `m` (between 2 and n) controls the sharpness
+pub fn sd_star(p: Vec2, r: f32, n: i32, m: f32) -> f32 {
+    let an = 3.141593 / n as f32;
+    let en = 3.141593 / m;
+    let acs = Vec2::new(an.cos(), an.sin());
+    let ecs = Vec2::new(en.cos(), en.sin());
+
+    let bn = modulo(p.x.atan2(p.y), 2. * an) - an;
+    let mut q = p.length() * Vec2::new(bn.cos(), bn.sin().abs());
+    q -= r * acs;
+    q += ecs * (-q.dot(ecs)).clamp(0., r * acs.y / ecs.y);
+    q.length() * sign(q.x)
+}
+
+/// Pie slice with radius `r`, where `c` is the sin/cos of the aperture
+pub fn sd_pie(p: Vec2, c: Vec2, r: f32) -> f32 {
+    let p = Vec2::new(p.x.abs(), p.y);
+    let l = p.length() - r;
+    let m = (p - c * p.dot(c).clamp(0., r)).length();
+    l.max(m * sign(c.y * p.x - c.x * p.y))
+}
+
+/// Disk with radius `r`, cut off at height `h`
+pub fn sd_cut_disk(p: Vec2, r: f32, h: f32) -> f32 {
+    let w = (r * r - h * h).sqrt();
+    let p = Vec2::new(p.x.abs(), p.y);
+    let s = ((h - r) * p.x * p.x + w * w * (h + r - 2. * p.y)).max(h * p.x - w * p.y);
+    select(
+        select((p - Vec2::new(w, h)).length(), h - p.y, p.x < w),
+        p.length() - r,
+        s < 0.,
+    )
+}
+
+/// Arc with radius `ra` and thickness `rb`, where `sc` is the sin/cos of the aperture
+pub fn sd_arc(p: Vec2, sc: Vec2, ra: f32, rb: f32) -> f32 {
+    let p = Vec2::new(p.x.abs(), p.y);
+    select(
+        (p.length() - ra).abs(),
+        (p - sc * ra).length(),
+        sc.y * p.x > sc.x * p.y,
+    ) - rb
+}
+
+/// Arc like [`sd_arc`], rotated by the sin/cos `sc_orientation`
+pub fn sd_arc_oriented(p: Vec2, sc_orientation: Vec2, sc_aperture: Vec2, ra: f32, rb: f32) -> f32 {
+    // `p * mat` in wgsl is the transposed matrix times `p`
+    let m = mat2(
+        sc_orientation.x,
+        sc_orientation.y,
+        -sc_orientation.y,
+        sc_orientation.x,
+    );
+    let p = m.transpose() * p;
+    let p = Vec2::new(p.x.abs(), p.y);
+    let k = select(
+        p.length(),
+        p.dot(sc_aperture),
+        sc_aperture.y * p.x > sc_aperture.x * p.y,
+    );
+    (p.dot(p) + ra * ra - 2. * ra * k).sqrt() - rb
+}
+
+/// Horseshoe with aperture sin/cos `c`, radius `r` and thickness/length `w`
+pub fn sd_horseshoe(p: Vec2, c: Vec2, r: f32, w: Vec2) -> f32 {
+    let p = Vec2::new(p.x.abs(), p.y);
+    let l = p.length();
+    let p = mat2(-c.x, c.y, c.y, c.x) * p;
+    let p = Vec2::new(
+        select(l * sign(-c.x), p.x, p.y > 0. || p.x > 0.),
+        select(l, p.y, p.x > 0.),
+    );
+    let p = Vec2::new(p.x, (p.y - r).abs()) - w;
+    p.max(Vec2::ZERO).length() + p.x.max(p.y).min(0.)
+}
+
+/// Cross with rounded inner corners
+pub fn sd_rounded_cross(p: Vec2, h: f32) -> f32 {
+    let k = 0.5 * (h + 1. / h);
+    let p = p.abs();
+    select(
+        dot2(p - Vec2::new(0., h))
+            .min(dot2(p - Vec2::new(1., 0.)))
+            .sqrt(),
+        k - dot2(p - Vec2::new(1., k)).sqrt(),
+        p.x < 1. && p.y < p.x * (k - h) + h,
+    )
+}
+
+/// Egg with bottom radius `ra` and top radius `rb`
+pub fn sd_egg(p: Vec2, ra: f32, rb: f32) -> f32 {
+    let k = 3f32.sqrt();
+    let p = Vec2::new(p.x.abs(), p.y);
+    let r = ra - rb;
+    select(
+        select(
+            Vec2::new(p.x + r, p.y).length() - 2. * r,
+            Vec2::new(p.x, p.y - k * r).length(),
+            k * (p.x + r) < p.y,
+        ),
+        p.length() - r,
+        p.y < 0.,
+    ) - rb
+}
+
+/// Heart with its tip at the origin and a height of about 1
+pub fn sd_heart(p: Vec2) -> f32 {
+    let p = Vec2::new(p.x.abs(), p.y);
+
+    if p.y + p.x > 1. {
+        return dot2(p - Vec2::new(0.25, 0.75)).sqrt() - 2f32.sqrt() / 4.;
+    }
+
+    dot2(p - Vec2::new(0., 1.))
+        .min(dot2(p - 0.5 * (p.x + p.y).max(0.)))
+        .sqrt()
+        * sign(p.x - p.y)
+}
+
+/// Cross with arm half-size `b` and corner rounding `r`
+pub fn sd_cross(p: Vec2, b: Vec2, r: f32) -> f32 {
+    let p = p.abs();
+    let p = select(p, Vec2::new(p.y, p.x), p.y > p.x);
+    let q = p - b;
+    let k = q.y.max(q.x);
+    let w = select(Vec2::new(b.y - p.x, -k), q, k > 0.);
+    sign(k) * w.max(Vec2::ZERO).length() + r
+}
+
+/// Rounded X with width `w` and radius `r`
+pub fn sd_rounded_x(p: Vec2, w: f32, r: f32) -> f32 {
+    let p = p.abs();
+    (p - (p.x + p.y).min(w) * 0.5).length() - r
+}
+
+/// Ellipse with radii `a` and `b`
+pub fn sd_ellipse(p: Vec2, a: f32, b: f32) -> f32 {
+    let mut p = p.abs();
+    let mut ab = Vec2::new(a, b);
+    if p.x > p.y {
+        p = Vec2::new(p.y, p.x);
+        ab = Vec2::new(ab.y, ab.x);
+    }
+    let l = ab.y * ab.y - ab.x * ab.x;
+    let m = ab.x * p.x / l;
+    let m2 = m * m;
+    let n = ab.y * p.y / l;
+    let n2 = n * n;
+    let c = (m2 + n2 - 1.) / 3.;
+    let c3 = c * c * c;
+    let q = c3 + m2 * n2 * 2.;
+    let d = c3 + m2 * n2;
+    let g = m + m * n2;
+    let co = if d < 0. {
+        let h = (q / c3).acos() / 3.;
+        let s = h.cos();
+        let t = h.sin() * 3f32.sqrt();
+        let rx = (-c * (s + t + 2.) + m2).sqrt();
+        let ry = (-c * (s - t + 2.) + m2).sqrt();
+        (ry + sign(l) * rx + g.abs() / (rx * ry) - m) / 2.
+    } else {
+        let h = 2. * m * n * d.sqrt();
+        let s = sign(q + h) * (q + h).abs().powf(1. / 3.);
+        let u = sign(q - h) * (q - h).abs().powf(1. / 3.);
+        let rx = -s - u - c * 4. + 2. * m2;
+        let ry = (s - u) * 3f32.sqrt();
+        let rm = (rx * rx + ry * ry).sqrt();
+        (ry / (rm - rx).sqrt() + 2. * g / rm - m) / 2.
+    };
+    let r = ab * Vec2::new(co, (1. - co * co).sqrt());
+    (r - p).length() * sign(p.y - r.y)
+}
+
+/// Parabola `y = k * x^2`
+pub fn sd_parabola(p: Vec2, k: f32) -> f32 {
+    let pos = Vec2::new(p.x.abs(), p.y);
+    let ik = 1. / k;
+    let p = ik * (pos.y - 0.5 * ik) / 3.;
+    let q = 0.25 * ik * ik * pos.x;
+    let h = q * q - p * p * p;
+    let r = h.abs().sqrt();
+    let x = select(
+        2. * (r.atan2(q) / 3.).cos() * p.sqrt(),
+        (q + r).powf(1. / 3.) - (q - r).abs().powf(1. / 3.) * sign(r - q),
+        h > 0.,
+    );
+    (pos - Vec2::new(x, k * x * x)).length() * sign(pos.x - x)
+}
+
+/// Parabola segment with half-width `wi` and height `he`
+pub fn sd_parabola_segment(p: Vec2, wi: f32, he: f32) -> f32 {
+    let pos = Vec2::new(p.x.abs(), p.y);
+    let ik = wi * wi / he;
+    let p = ik * (he - pos.y - 0.5 * ik) / 3.;
+    let q = pos.x * ik * ik * 0.25;
+    let h = q * q - p * p * p;
+    let r = h.abs().sqrt();
+    let x = select(
+        2. * ((r / q).atan() / 3.).cos() * p.sqrt(),
+        (q + r).powf(1. / 3.) - (q - r).abs().powf(1. / 3.) * sign(r - q),
+        h > 0.,
+    );
+    let x = x.min(wi);
+    (pos - Vec2::new(x, he - x * x / ik)).length() * sign(ik * (pos.y - he) + pos.x * pos.x)
+}
+
+/// Quadratic bezier curve from `a` to `c` with control point `b`
+#[allow(non_snake_case)]
+pub fn sd_bezier(pos: Vec2, A: Vec2, B: Vec2, C: Vec2) -> f32 {
+    let a = B - A;
+    let b = A - 2. * B + C;
+    let c = a * 2.;
+    let d = A - pos;
+    let kk = 1. / b.dot(b);
+    let kx = kk * a.dot(b);
+    let ky = kk * (2. * a.dot(a) + d.dot(b)) / 3.;
+    let kz = kk * d.dot(a);
+    let p = ky - kx * kx;
+    let p3 = p * p * p;
+    let q = kx * (2. * kx * kx - 3. * ky) + kz;
+    let h = q * q + 4. * p3;
+    let res = if h >= 0. {
+        let h = h.sqrt();
+        let x = (Vec2::new(h, -h) - q) / 2.;
+        let uv = sign2(x) * Vec2::new(x.x.abs().powf(1. / 3.), x.y.abs().powf(1. / 3.));
+        let t = (uv.x + uv.y - kx).clamp(0., 1.);
+        dot2(d + (c + b * t) * t)
+    } else {
+        let z = (-p).sqrt();
+        let v = (q / (p * z * 2.)).acos() / 3.;
+        let m = v.cos();
+        let n = v.sin() * 1.732050808;
+        let u = Vec3::new(m + m, -n - m, n - m) * z - Vec3::splat(kx);
+        let t = u.clamp(Vec3::ZERO, Vec3::ONE);
+        // the third root cannot be the closest
+        dot2(d + (c + b * t.x) * t.x).min(dot2(d + (c + b * t.y) * t.y))
+    };
+    res.sqrt()
+}
+
+/// Cross with blobby arms
+pub fn sd_blobby_cross(p: Vec2, he: f32) -> f32 {
+    let pos = p.abs();
+    let pos = Vec2::new((pos.x - pos.y).abs(), 1. - pos.x - pos.y) / 2f32.sqrt();
+
+    let p = (he - pos.y - 0.25 / he) / (6. * he);
+    let q = pos.x / (he * he * 16.);
+    let h = q * q - p * p * p;
+
+    let x = if h > 0. {
+        let r = h.sqrt();
+        (q + r).powf(1. / 3.) - (q - r).abs().powf(1. / 3.) * sign(r - q)
+    } else {
+        let r = p.sqrt();
+        2. * r * ((q / (p * r)).acos() / 3.).cos()
+    };
+    let x = x.min(2f32.sqrt() / 2.);
+
+    let z = Vec2::new(x, he * (1. - 2. * x * x)) - pos;
+    z.length() * sign(z.y)
+}
+
+/// Tunnel (arch) with half-width and height `wh`
+pub fn sd_tunnel(p: Vec2, wh: Vec2) -> f32 {
+    let p = Vec2::new(p.x.abs(), -p.y);
+    let mut q = p - wh;
+
+    let d1 = dot2(Vec2::new(q.x.max(0.), q.y));
+    q.x = select(p.length() - wh.x, q.x, p.y > 0.);
+    let d2 = dot2(Vec2::new(q.x, q.y.max(0.)));
+    let d = d1.min(d2).sqrt();
+
+    select(d, -d, q.x.max(q.y) < 0.)
+}
+
+/// `n` stairs with step size `wh`
+pub fn sd_stairs(p: Vec2, wh: Vec2, n: f32) -> f32 {
+    let mut p = p;
+    let ba = wh * n;
+    let mut d = dot2(p - Vec2::new(p.x.clamp(0., ba.x), 0.))
+        .min(dot2(p - Vec2::new(ba.x, p.y.clamp(0., ba.y))));
+    let mut s = sign((-p.y).max(p.x - ba.x));
+
+    let dia = wh.length();
+    p = mat2(wh.x, -wh.y, wh.y, wh.x) * p / dia;
+    // wgsl's round rounds half to even
+    let id = (p.x / dia).round_ties_even().clamp(0., n - 1.);
+    p.x -= id * dia;
+    p = mat2(wh.x, wh.y, -wh.y, wh.x) * p / dia;
+
+    let hh = wh.y / 2.;
+    p.y -= hh;
+    if p.y > hh * sign(p.x) {
+        s = 1.;
+    }
+    p = select(-p, p, id < 0.5 || p.x > 0.);
+    d = d.min(dot2(p - Vec2::new(0., p.y.clamp(-hh, hh))));
+    d = d.min(dot2(p - Vec2::new(p.x.clamp(0., wh.x), hh)));
+
+    d.sqrt() * s
+}
+
+/// Vesica (intersection of two circles with radius `r` at distance `d` from the center)
+pub fn sd_vesica(p: Vec2, r: f32, d: f32) -> f32 {
+    let p = p.abs();
+    let b = (r * r - d * d).sqrt();
+    select(
+        (p - Vec2::new(-d, 0.)).length() - r,
+        (p - Vec2::new(0., b)).length(),
+        (p.y - b) * d > p.x * b,
+    )
+}
+
+/// Moon (circle with radius `ra` minus circle with radius `rb` at distance `d`)
+pub fn sd_moon(p: Vec2, d: f32, ra: f32, rb: f32) -> f32 {
+    let p = Vec2::new(p.x, p.y.abs());
+    let a = (ra * ra - rb * rb + d * d) / (2. * d);
+    let b = (ra * ra - a * a).max(0.).sqrt();
+
+    if d * (p.x * b - p.y * a) > d * d * (b - p.y).max(0.) {
+        return (p - Vec2::new(a, b)).length();
+    }
+
+    (p.length() - ra).max(-((p - Vec2::new(d, 0.)).length() - rb))
+}
+
+/// Maps uvs from 0..1 to -1..1
+pub fn renormalize_uv(uv: Vec2) -> Vec2 {
+    uv * 2. - Vec2::ONE
+}
+
+/// Falls off from 1 at the edge to 0 at distance `size`
+pub fn exponential_falloff(d: f32, size: f32, power: f32) -> f32 {
+    ((size - d) / size).clamp(0., 1.).powf(power)
+}
+
+/// [`exponential_falloff`] with a power of 3
+pub fn exponential_falloff_3(d: f32, size: f32) -> f32 {
+    let a = ((size - d) / size).clamp(0., 1.);
+    a * a * a
+}
+
+/// Union of two shapes
+pub fn op_union(distance_1: f32, distance_2: f32) -> f32 {
+    distance_1.min(distance_2)
+}
+
+/// The second shape minus the first
+pub fn op_subtract(distance_1: f32, distance_2: f32) -> f32 {
+    (-distance_1).max(distance_2)
+}
+
+/// Intersection of two shapes
+pub fn op_intersect(distance_1: f32, distance_2: f32) -> f32 {
+    distance_1.max(distance_2)
+}
+
+/// The sine and cosine of an angle
+pub fn sin_cos(a: f32) -> Vec2 {
+    Vec2::new(a.sin(), a.cos())
+}
+
+/// Rotation given sin cos vector
+pub fn rotate(p: Vec2, sc: Vec2) -> Vec2 {
+    let s = sc.x;
+    let c = sc.y;
+    Vec2::new(p.x * c - p.y * s, p.x * s + p.y * c)
+}
+
+/// Rotation by an angle in radians
+pub fn rotate_rad(p: Vec2, a: f32) -> Vec2 {
+    rotate(p, sin_cos(a))
+}
+
+/// Rotation by 45 degrees
+pub fn rotate_45(p: Vec2) -> Vec2 {
+    let c = 0.70710678118;
+    let xc = p.x * c;
+    let yc = p.y * c;
+    Vec2::new(xc - yc, xc + yc)
+}
+
+/// The second shape minus the first, with a smooth transition of size `k`
+pub fn op_smooth_subtract(d1: f32, d2: f32, k: f32) -> f32 {
+    let h = (0.5 - 0.5 * (d2 + d1) / k).clamp(0., 1.);
+    mix(d2, -d1, h) + k * h * (1. - h)
+}
+
+/// Union of two shapes, with a smooth transition of size `k`
+pub fn op_smooth_union(d1: f32, d2: f32, k: f32) -> f32 {
+    let h = (0.5 + 0.5 * (d2 - d1) / k).clamp(0., 1.);
+    mix(d2, d1, h) - k * h * (1. - h)
+}
+
+/// Intersection of two shapes, with a smooth transition of size `k`
+pub fn op_smooth_intersect(d1: f32, d2: f32, k: f32) -> f32 {
+    let h = (0.5 - 0.5 * (d2 - d1) / k).clamp(0., 1.);
+    mix(d2, d1, h) + k * h * (1. - h)
+}
@@ -1,4 +1,9 @@
-use bevy::prelude::*;
+use bevy::{
+    asset::{io::Reader, AssetLoader, AssetPath, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use thiserror::Error;
 
 use crate::util::generate_shader_id;
 
@@ -16,12 +21,33 @@ pub trait SdfAssets {
 
 impl SdfAssets for Assets<Shader> {
     fn add_sdf_body<T: Into<String>>(&mut self, sdf: T) -> Handle<Shader> {
-        let body = sdf.into();
         let id = generate_shader_id();
-        let shader = Shader::from_wgsl(
-            format!(
-                r#"
-#define_import_path smud::sdf{id}
+        let source = sdf_source(&format!("smud::sdf{id}"), &sdf.into());
+        self.add(Shader::from_wgsl(source, file!()))
+    }
+
+    fn add_fill_body<T: Into<String>>(&mut self, fill: T) -> Handle<Shader> {
+        let id = generate_shader_id();
+        let source = fill_source(&format!("smud::fill{id}"), &fill.into());
+        self.add(Shader::from_wgsl(source, file!()))
+    }
+
+    fn add_sdf_expr<T: Into<String>>(&mut self, sdf: T) -> Handle<Shader> {
+        let e = sdf.into();
+        self.add_sdf_body(format!("return {e};"))
+    }
+
+    fn add_fill_expr<T: Into<String>>(&mut self, fill: T) -> Handle<Shader> {
+        let e = fill.into();
+        self.add_fill_body(format!("return {e};"))
+    }
+}
+
+/// Wraps a wgsl body in a sdf shader module
+pub(crate) fn sdf_source(import_path: &str, body: &str) -> String {
+    format!(
+        r#"
+#define_import_path {import_path}
 
 #import smud
 
@@ -29,19 +55,14 @@ fn sdf(p: vec2<f32>) -> f32 {{
     {body}
 }}
 "#
-            ),
-            file!(),
-        );
-        self.add(shader)
-    }
+    )
+}
 
-    fn add_fill_body<T: Into<String>>(&mut self, fill: T) -> Handle<Shader> {
-        let body = fill.into();
-        let id = generate_shader_id();
-        let shader = Shader::from_wgsl(
-            format!(
-                r#"
-#define_import_path smud::fill{id}
+/// Wraps a wgsl body in a fill shader module
+pub(crate) fn fill_source(import_path: &str, body: &str) -> String {
+    format!(
+        r#"
+#define_import_path {import_path}
 
 #import smud
 
@@ -49,19 +70,94 @@ fn fill(d: f32, color: vec4<f32>) -> vec4<f32> {{
     {body}
 }}
 "#
-            ),
-            file!(),
-        );
-        self.add(shader)
+    )
+}
+
+/// An import path for a shader loaded from the given asset path
+///
+/// Generated fragment shaders refer to sdfs and fills by import path, so it needs to stay the
+/// same when the asset is reloaded. Other characters than letters and digits, including `_`, are
+/// escaped as their hex code between underscores, so different paths never collide.
+pub(crate) fn asset_import_path(path: &AssetPath) -> String {
+    let mut name = String::new();
+    for c in path.to_string().chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c);
+        } else {
+            name.push_str(&format!("_{:x}_", c as u32));
+        }
     }
+    format!("smud::asset_{name}")
+}
 
-    fn add_sdf_expr<T: Into<String>>(&mut self, sdf: T) -> Handle<Shader> {
-        let e = sdf.into();
-        self.add_sdf_body(format!("return {e};"))
+/// Returns the given wgsl as a function body, treating it as an expression if it doesn't return
+fn body_or_expr(wgsl: &str) -> String {
+    let returns = wgsl
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .any(|word| word == "return");
+    if returns {
+        wgsl.to_string()
+    } else {
+        // On separate lines, so a trailing comment doesn't swallow the semicolon
+        format!("return {wgsl}\n;")
     }
+}
 
-    fn add_fill_expr<T: Into<String>>(&mut self, fill: T) -> Handle<Shader> {
-        let e = fill.into();
-        self.add_fill_body(format!("return {e};"))
+/// Possible errors produced by [`SdfBodyLoader`]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SdfBodyLoaderError {
+    /// An [IO](std::io) Error
+    #[error("Could not read shader: {0}")]
+    Io(#[from] std::io::Error),
+    /// The file is not valid utf-8
+    #[error("Could not read shader: {0}")]
+    Utf8(#[from] std::string::FromUtf8Error),
+}
+
+/// Loads `.sdf.wgsl` and `.fill.wgsl` files containing just the body of the `sdf` or `fill`
+/// function
+///
+/// The body is wrapped like [`SdfAssets::add_sdf_body`] and [`SdfAssets::add_fill_body`] do,
+/// so `p`, or `d` and `color`, are in scope and everything in `smud` can be used. Files without
+/// a `return` statement are treated as a single expression, like [`SdfAssets::add_sdf_expr`].
+///
+/// ```wgsl
+/// // assets/ring.sdf.wgsl
+/// abs(smud::sd_circle(p, 50.)) - 5.
+/// ```
+#[derive(Default)]
+pub struct SdfBodyLoader;
+
+impl AssetLoader for SdfBodyLoader {
+    type Asset = Shader;
+    type Settings = ();
+    type Error = SdfBodyLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Shader, SdfBodyLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let body = body_or_expr(&String::from_utf8(bytes)?);
+
+            let asset_path = load_context.asset_path();
+            let import_path = asset_import_path(asset_path);
+            let source = if asset_path.to_string().ends_with(".fill.wgsl") {
+                fill_source(&import_path, &body)
+            } else {
+                sdf_source(&import_path, &body)
+            };
+
+            Ok(Shader::from_wgsl(source, asset_path.to_string()))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sdf.wgsl", "fill.wgsl"]
     }
 }
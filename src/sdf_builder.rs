@@ -0,0 +1,513 @@
+use bevy::prelude::*;
+
+use crate::{prelude::SdfAssets, sdf, Frame};
+
+/// How [`SmudSdf::Csg`] combines two shapes
+#[derive(Reflect, Debug, Clone, Copy, PartialEq)]
+pub enum CsgOp {
+    /// Covers both shapes
+    Union,
+    /// The first shape minus the second
+    Subtract,
+    /// Covers only where the shapes overlap
+    Intersect,
+    /// Union, with a smooth transition of the given size
+    SmoothUnion(f32),
+    /// Subtraction, with a smooth transition of the given size
+    SmoothSubtract(f32),
+    /// Intersection, with a smooth transition of the given size
+    SmoothIntersect(f32),
+}
+
+impl CsgOp {
+    /// Combines the distances to two shapes
+    pub fn apply(self, a: f32, b: f32) -> f32 {
+        // The smud operators subtract the first shape from the second
+        match self {
+            CsgOp::Union => sdf::op_union(a, b),
+            CsgOp::Subtract => sdf::op_subtract(b, a),
+            CsgOp::Intersect => sdf::op_intersect(a, b),
+            CsgOp::SmoothUnion(k) => sdf::op_smooth_union(a, b, k),
+            CsgOp::SmoothSubtract(k) => sdf::op_smooth_subtract(b, a, k),
+            CsgOp::SmoothIntersect(k) => sdf::op_smooth_intersect(a, b, k),
+        }
+    }
+
+    /// The wgsl expression combining the distance expressions `a` and `b`
+    pub fn wgsl(self, a: &str, b: &str) -> String {
+        self.wgsl_with_k(a, b, &format!("{:?}", self.smoothness()))
+    }
+
+    /// Like [`CsgOp::wgsl`], with the transition size of smooth operators as an expression
+    pub(crate) fn wgsl_with_k(self, a: &str, b: &str, k: &str) -> String {
+        match self {
+            CsgOp::Union => format!("smud::op_union({a}, {b})"),
+            CsgOp::Subtract => format!("smud::op_subtract({b}, {a})"),
+            CsgOp::Intersect => format!("smud::op_intersect({a}, {b})"),
+            CsgOp::SmoothUnion(_) => format!("smud::op_smooth_union({a}, {b}, {k})"),
+            CsgOp::SmoothSubtract(_) => format!("smud::op_smooth_subtract({b}, {a}, {k})"),
+            CsgOp::SmoothIntersect(_) => format!("smud::op_smooth_intersect({a}, {b}, {k})"),
+        }
+    }
+
+    /// The transition size of smooth operators, zero for the others
+    pub(crate) fn smoothness(self) -> f32 {
+        match self {
+            CsgOp::SmoothUnion(k) | CsgOp::SmoothSubtract(k) | CsgOp::SmoothIntersect(k) => k,
+            _ => 0.,
+        }
+    }
+
+    /// The bounds of the combined shape, given the bounds of both shapes
+    fn bounds(self, a: Rect, b: Rect) -> Rect {
+        match self {
+            CsgOp::Union => a.union(b),
+            CsgOp::Subtract => a,
+            CsgOp::Intersect | CsgOp::SmoothIntersect(_) => a.intersect(b),
+            // Smooth unions bulge out by at most a quarter of the transition size
+            CsgOp::SmoothUnion(k) => a.union(b).inset(k.abs() / 4.),
+            CsgOp::SmoothSubtract(k) => a.inset(k.abs() / 4.),
+        }
+    }
+}
+
+/// A signed distance function built from the primitives and operators in `smud.wgsl`
+///
+/// The same shape can be turned into an sdf shader, evaluated on the cpu, i.e. for
+/// [`SmudRasterizer`](crate::SmudRasterizer), and exported as svg with
+/// [`SmudSdf::to_svg`], without writing any wgsl by hand.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_smud::prelude::*;
+/// # use bevy_smud::SmudSdf;
+/// fn setup(mut commands: Commands, mut shaders: ResMut<Assets<Shader>>) {
+///     let sdf = SmudSdf::circle(40.)
+///         .smooth_union(SmudSdf::rect(Vec2::new(60., 10.)).rotate(0.3), 10.)
+///         .subtract(SmudSdf::circle(15.).translate(Vec2::new(20., 0.)));
+///
+///     commands.spawn(ShapeBundle {
+///         shape: SmudShape {
+///             color: Color::TEAL,
+///             sdf: sdf.shader(&mut shaders),
+///             frame: sdf.frame(),
+///             ..default()
+///         },
+///         ..default()
+///     });
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum SmudSdf {
+    /// `smud::sd_circle`
+    Circle {
+        /// The radius
+        radius: f32,
+    },
+    /// `smud::sd_box`
+    Rect {
+        /// Half the width and height
+        half_size: Vec2,
+    },
+    /// `smud::sd_rounded_box`
+    RoundedRect {
+        /// Half the width and height
+        half_size: Vec2,
+        /// Corner radii: top right, bottom right, top left, bottom left
+        radii: Vec4,
+    },
+    /// `smud::sd_ellipse`
+    Ellipse {
+        /// Half the width and height
+        radii: Vec2,
+    },
+    /// `smud::sd_segment`, which has no thickness unless [rounded](SmudSdf::round)
+    Segment {
+        /// The start point
+        start: Vec2,
+        /// The end point
+        end: Vec2,
+    },
+    /// `smud::sd_triangle`
+    Triangle {
+        /// The corners
+        points: [Vec2; 3],
+    },
+    /// `smud::sd_equilateral_triangle`, pointing up
+    EquilateralTriangle {
+        /// Half the length of the sides
+        radius: f32,
+    },
+    /// `smud::sd_hexagon`, with flat top and bottom sides
+    Hexagon {
+        /// Distance from the center to the sides
+        radius: f32,
+    },
+    /// `smud::sd_star_5_`, pointing up
+    Star5 {
+        /// Distance from the center to the points
+        radius: f32,
+        /// How far in the inner corners are, from 0 to 1
+        inner_factor: f32,
+    },
+    /// Another shape, moved and rotated
+    Transform {
+        /// The shape being transformed
+        shape: Box<SmudSdf>,
+        /// Offset of the shape
+        translation: Vec2,
+        /// Counter-clockwise rotation around the origin of the shape, in radians
+        rotation: f32,
+    },
+    /// Another shape, scaled uniformly
+    Scale {
+        /// The shape being scaled
+        shape: Box<SmudSdf>,
+        /// The scale factor
+        scale: f32,
+    },
+    /// Another shape, grown by a distance, which also rounds its corners
+    Round {
+        /// The shape being grown
+        shape: Box<SmudSdf>,
+        /// How much to grow the shape
+        radius: f32,
+    },
+    /// The outline of another shape
+    Annular {
+        /// The shape being outlined
+        shape: Box<SmudSdf>,
+        /// Half the thickness of the outline
+        thickness: f32,
+    },
+    /// Two shapes combined by a [`CsgOp`]
+    Csg {
+        /// How the shapes are combined
+        op: CsgOp,
+        /// The first shape
+        a: Box<SmudSdf>,
+        /// The second shape
+        b: Box<SmudSdf>,
+    },
+}
+
+impl SmudSdf {
+    /// A circle with the given radius
+    pub fn circle(radius: f32) -> Self {
+        Self::Circle { radius }
+    }
+
+    /// A rectangle with the given half width and height
+    pub fn rect(half_size: Vec2) -> Self {
+        Self::Rect { half_size }
+    }
+
+    /// A rectangle with all corners rounded by the same radius
+    pub fn rounded_rect(half_size: Vec2, radius: f32) -> Self {
+        Self::RoundedRect {
+            half_size,
+            radii: Vec4::splat(radius),
+        }
+    }
+
+    /// An ellipse with the given half width and height
+    pub fn ellipse(radii: Vec2) -> Self {
+        Self::Ellipse { radii }
+    }
+
+    /// A line between two points with rounded ends
+    pub fn line(start: Vec2, end: Vec2, width: f32) -> Self {
+        Self::Segment { start, end }.round(width / 2.)
+    }
+
+    /// A triangle with the given corners
+    pub fn triangle(points: [Vec2; 3]) -> Self {
+        Self::Triangle { points }
+    }
+
+    /// An equilateral triangle pointing up, with sides of length `2 * radius`
+    pub fn equilateral_triangle(radius: f32) -> Self {
+        Self::EquilateralTriangle { radius }
+    }
+
+    /// A hexagon with the given distance from its center to its sides
+    pub fn hexagon(radius: f32) -> Self {
+        Self::Hexagon { radius }
+    }
+
+    /// A five-pointed star, `inner_factor` goes from 0 for a pentagon to 1 for thin points
+    pub fn star_5(radius: f32, inner_factor: f32) -> Self {
+        Self::Star5 {
+            radius,
+            inner_factor,
+        }
+    }
+
+    /// Moves the shape
+    pub fn translate(self, translation: Vec2) -> Self {
+        match self {
+            Self::Transform {
+                shape,
+                translation: t,
+                rotation,
+            } => Self::Transform {
+                shape,
+                translation: t + translation,
+                rotation,
+            },
+            shape => Self::Transform {
+                shape: Box::new(shape),
+                translation,
+                rotation: 0.,
+            },
+        }
+    }
+
+    /// Rotates the shape counter-clockwise around the origin, in radians
+    pub fn rotate(self, angle: f32) -> Self {
+        match self {
+            Self::Transform {
+                shape,
+                translation,
+                rotation,
+            } => Self::Transform {
+                shape,
+                translation: Vec2::from_angle(angle).rotate(translation),
+                rotation: rotation + angle,
+            },
+            shape => Self::Transform {
+                shape: Box::new(shape),
+                translation: Vec2::ZERO,
+                rotation: angle,
+            },
+        }
+    }
+
+    /// Scales the shape uniformly around the origin
+    pub fn scale(self, scale: f32) -> Self {
+        Self::Scale {
+            shape: Box::new(self),
+            scale,
+        }
+    }
+
+    /// Grows the shape by `radius`, rounding its corners
+    pub fn round(self, radius: f32) -> Self {
+        Self::Round {
+            shape: Box::new(self),
+            radius,
+        }
+    }
+
+    /// Turns the shape into an outline of width `2 * thickness`, centered on its edge
+    pub fn annular(self, thickness: f32) -> Self {
+        Self::Annular {
+            shape: Box::new(self),
+            thickness,
+        }
+    }
+
+    /// Combines this shape with another one
+    pub fn csg(self, op: CsgOp, other: SmudSdf) -> Self {
+        Self::Csg {
+            op,
+            a: Box::new(self),
+            b: Box::new(other),
+        }
+    }
+
+    /// The union of this shape and another one
+    pub fn union(self, other: SmudSdf) -> Self {
+        self.csg(CsgOp::Union, other)
+    }
+
+    /// This shape minus another one
+    pub fn subtract(self, other: SmudSdf) -> Self {
+        self.csg(CsgOp::Subtract, other)
+    }
+
+    /// The intersection of this shape and another one
+    pub fn intersect(self, other: SmudSdf) -> Self {
+        self.csg(CsgOp::Intersect, other)
+    }
+
+    /// The union of this shape and another one, blended over a distance of `k`
+    pub fn smooth_union(self, other: SmudSdf, k: f32) -> Self {
+        self.csg(CsgOp::SmoothUnion(k), other)
+    }
+
+    /// This shape minus another one, blended over a distance of `k`
+    pub fn smooth_subtract(self, other: SmudSdf, k: f32) -> Self {
+        self.csg(CsgOp::SmoothSubtract(k), other)
+    }
+
+    /// The intersection of this shape and another one, blended over a distance of `k`
+    pub fn smooth_intersect(self, other: SmudSdf, k: f32) -> Self {
+        self.csg(CsgOp::SmoothIntersect(k), other)
+    }
+
+    /// Evaluates the sdf on the cpu
+    pub fn distance(&self, p: Vec2) -> f32 {
+        match self {
+            Self::Circle { radius } => sdf::sd_circle(p, *radius),
+            Self::Rect { half_size } => sdf::sd_box(p, *half_size),
+            Self::RoundedRect { half_size, radii } => sdf::sd_rounded_box(p, *half_size, *radii),
+            Self::Ellipse { radii } => sdf::sd_ellipse(p, radii.x, radii.y),
+            Self::Segment { start, end } => sdf::sd_segment(p, *start, *end),
+            Self::Triangle { points: [a, b, c] } => sdf::sd_triangle(p, *a, *b, *c),
+            Self::EquilateralTriangle { radius } => sdf::sd_equilateral_triangle(p, *radius),
+            Self::Hexagon { radius } => sdf::sd_hexagon(p, *radius),
+            Self::Star5 {
+                radius,
+                inner_factor,
+            } => sdf::sd_star_5(p, *radius, *inner_factor),
+            Self::Transform {
+                shape,
+                translation,
+                rotation,
+            } => shape.distance(sdf::rotate_rad(p - *translation, -rotation)),
+            Self::Scale { shape, scale } => shape.distance(p / *scale) * scale,
+            Self::Round { shape, radius } => shape.distance(p) - radius,
+            Self::Annular { shape, thickness } => shape.distance(p).abs() - thickness,
+            Self::Csg { op, a, b } => op.apply(a.distance(p), b.distance(p)),
+        }
+    }
+
+    /// The sdf as a wgsl expression of `p`, the position in the shape's local space
+    pub fn wgsl(&self) -> String {
+        self.wgsl_at("p")
+    }
+
+    fn wgsl_at(&self, p: &str) -> String {
+        match self {
+            Self::Circle { radius } => format!("smud::sd_circle({p}, {radius:?})"),
+            Self::Rect { half_size } => format!("smud::sd_box({p}, {})", vec2(*half_size)),
+            Self::RoundedRect { half_size, radii } => format!(
+                "smud::sd_rounded_box({p}, {}, vec4<f32>({:?}, {:?}, {:?}, {:?}))",
+                vec2(*half_size),
+                radii.x,
+                radii.y,
+                radii.z,
+                radii.w
+            ),
+            Self::Ellipse { radii } => {
+                format!("smud::sd_ellipse({p}, {:?}, {:?})", radii.x, radii.y)
+            }
+            Self::Segment { start, end } => {
+                format!("smud::sd_segment({p}, {}, {})", vec2(*start), vec2(*end))
+            }
+            Self::Triangle { points: [a, b, c] } => format!(
+                "smud::sd_triangle({p}, {}, {}, {})",
+                vec2(*a),
+                vec2(*b),
+                vec2(*c)
+            ),
+            Self::EquilateralTriangle { radius } => {
+                format!("smud::sd_equilateral_triangle({p}, {radius:?})")
+            }
+            Self::Hexagon { radius } => format!("smud::sd_hexagon({p}, {radius:?})"),
+            Self::Star5 {
+                radius,
+                inner_factor,
+            } => format!("smud::sd_star_5_({p}, {radius:?}, {inner_factor:?})"),
+            Self::Transform {
+                shape,
+                translation,
+                rotation,
+            } => {
+                let mut local = format!("({p} - {})", vec2(*translation));
+                if *rotation != 0. {
+                    local = format!("smud::rotate_rad({local}, {:?})", -rotation);
+                }
+                shape.wgsl_at(&local)
+            }
+            Self::Scale { shape, scale } => {
+                format!(
+                    "({} * {scale:?})",
+                    shape.wgsl_at(&format!("({p} / {scale:?})"))
+                )
+            }
+            Self::Round { shape, radius } => format!("({} - {radius:?})", shape.wgsl_at(p)),
+            Self::Annular { shape, thickness } => {
+                format!("(abs({}) - {thickness:?})", shape.wgsl_at(p))
+            }
+            Self::Csg { op, a, b } => op.wgsl(&a.wgsl_at(p), &b.wgsl_at(p)),
+        }
+    }
+
+    /// Adds an sdf shader for the shape
+    pub fn shader(&self, shaders: &mut Assets<Shader>) -> Handle<Shader> {
+        shaders.add_sdf_expr(self.wgsl())
+    }
+
+    /// A rectangle containing the shape, not necessarily the tightest one
+    pub fn bounds(&self) -> Rect {
+        match self {
+            Self::Circle { radius } => {
+                Rect::from_center_half_size(Vec2::ZERO, Vec2::splat(*radius))
+            }
+            Self::Rect { half_size }
+            | Self::RoundedRect { half_size, .. }
+            | Self::Ellipse { radii: half_size } => {
+                Rect::from_center_half_size(Vec2::ZERO, *half_size)
+            }
+            Self::Segment { start, end } => Rect::from_corners(*start, *end),
+            Self::Triangle { points: [a, b, c] } => {
+                Rect::from_corners(a.min(*b).min(*c), a.max(*b).max(*c))
+            }
+            Self::EquilateralTriangle { radius } => Rect::new(
+                -radius,
+                -radius / 3f32.sqrt(),
+                *radius,
+                2. * radius / 3f32.sqrt(),
+            ),
+            Self::Hexagon { radius } => Rect::from_center_half_size(
+                Vec2::ZERO,
+                Vec2::new(2. * radius / 3f32.sqrt(), *radius),
+            ),
+            Self::Star5 { radius, .. } => {
+                Rect::from_center_half_size(Vec2::ZERO, Vec2::splat(*radius))
+            }
+            Self::Transform {
+                shape,
+                translation,
+                rotation,
+            } => {
+                let bounds = shape.bounds();
+                let rotation = Vec2::from_angle(*rotation);
+                let corners = [
+                    bounds.min,
+                    bounds.max,
+                    Vec2::new(bounds.min.x, bounds.max.y),
+                    Vec2::new(bounds.max.x, bounds.min.y),
+                ]
+                .map(|corner| rotation.rotate(corner) + *translation);
+                corners[1..]
+                    .iter()
+                    .fold(Rect::from_corners(corners[0], corners[0]), |rect, p| {
+                        rect.union_point(*p)
+                    })
+            }
+            Self::Scale { shape, scale } => {
+                let bounds = shape.bounds();
+                Rect::from_corners(bounds.min * *scale, bounds.max * *scale)
+            }
+            Self::Round { shape, radius: d }
+            | Self::Annular {
+                shape,
+                thickness: d,
+            } => shape.bounds().inset(d.abs()),
+            Self::Csg { op, a, b } => op.bounds(a.bounds(), b.bounds()),
+        }
+    }
+
+    /// A frame covering the shape, with some room for anti-aliasing
+    pub fn frame(&self) -> Frame {
+        let bounds = self.bounds();
+        Frame::Quad(bounds.min.abs().max(bounds.max.abs()).max_element() + 2.)
+    }
+}
+
+fn vec2(v: Vec2) -> String {
+    format!("vec2<f32>({:?}, {:?})", v.x, v.y)
+}
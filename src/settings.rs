@@ -0,0 +1,75 @@
+use bevy::{prelude::*, render::extract_resource::ExtractResource};
+
+/// Global rendering settings for all shapes
+///
+/// Changing the anti-aliasing specializes new pipelines for all shapes, so it's meant to be set
+/// once, or rarely, e.g. from a graphics menu.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_smud::{SmudAntiAliasing, SmudSettings};
+/// App::new().insert_resource(SmudSettings {
+///     anti_aliasing: SmudAntiAliasing::Nearest,
+///     ..default()
+/// });
+/// ```
+#[derive(Resource, ExtractResource, Reflect, Debug, Default, Clone, PartialEq)]
+#[reflect(Resource)]
+pub struct SmudSettings {
+    /// How the edges of shapes are anti-aliased
+    pub anti_aliasing: SmudAntiAliasing,
+    /// How the instance data of shapes is uploaded to the gpu
+    pub instance_upload: SmudInstanceUpload,
+}
+
+/// How the instance data of shapes is uploaded to the gpu
+#[derive(Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SmudInstanceUpload {
+    /// Keeps the instance data on the gpu between frames, and only uploads shapes that changed
+    ///
+    /// Mostly static scenes upload little or nothing.
+    #[default]
+    Retained,
+    /// Rebuilds the instance data of all shapes, and uploads all of it, every frame
+    ///
+    /// Skips comparing the shapes with last frame, which can be a little faster when almost all
+    /// shapes change every frame anyway.
+    Rebuild,
+}
+
+/// How the edges of shapes are anti-aliased
+///
+/// This is applied through `smud::sd_fill_alpha`, which the built-in fills use, so custom fills
+/// should use it too in order to follow the setting.
+#[derive(Reflect, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SmudAntiAliasing {
+    /// Smooths the edges over the width of a pixel, using `fwidth`
+    #[default]
+    Fwidth,
+    /// Like [`SmudAntiAliasing::Fwidth`], but uses the length of the gradient, which is more
+    /// accurate for rotated edges
+    Dpd,
+    /// Hard edges, with the sdf sampled at the center of each sdf unit
+    ///
+    /// Shapes look like pixel art scaled up with nearest sampling, where one unit of the sdf is
+    /// one pixel of the art.
+    Nearest,
+    /// Hard edges, with the sdf sampled at the center of each screen pixel
+    None,
+    /// Averages four samples per pixel with hard edges, which is slower but also smooths
+    /// the insides of fills that don't use `smud::sd_fill_alpha`
+    Supersampled,
+}
+
+impl SmudAntiAliasing {
+    /// The shader def enabling the mode in `smud.wgsl` and the generated fragment shaders
+    pub(crate) fn shader_def(&self) -> Option<&'static str> {
+        match self {
+            Self::Fwidth => None,
+            Self::Dpd => Some("SMUD_AA_DPD"),
+            Self::Nearest => Some("SMUD_AA_NEAREST"),
+            Self::None => Some("SMUD_AA_NONE"),
+            Self::Supersampled => Some("SMUD_AA_SUPERSAMPLED"),
+        }
+    }
+}
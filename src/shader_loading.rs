@@ -1,21 +1,68 @@
 use bevy::{asset::load_internal_asset, prelude::*};
 
+use crate::{instance_layout::instance_module, shape_3d::ShapeVertex3d, ShapeVertex};
+
 const PRELUDE_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(11291576006157771079);
 
 const SMUD_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(10055894596049459186);
 
 const VIEW_BINDINGS_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(11792080578571156967);
 
+const INSTANCE_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(13862467934562937810);
+
+/// Instance data of 2d shapes, generated from `ShapeVertex`
+const SHAPE_INSTANCE_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(7204836475016193485);
+
+/// Instance data of 3d shapes, generated from `ShapeVertex3d`
+const SHAPE_INSTANCE_3D_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(15833270968435101226);
+
 pub const VERTEX_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(16846632126033267571);
 
+/// Vertex shader of shapes drawn with 3d cameras
+pub const VERTEX_3D_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(12920188377162480483);
+
 pub const FRAGMENT_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(10370213491934870425);
 
+/// Sdf sampling the glyph atlas, used by `SmudText`
+pub const TEXT_SDF_HANDLE: Handle<Shader> = Handle::weak_from_u128(9071528264912785132);
+
+/// Sdf reading its points from the point buffer, used by `SmudPolygon`
+pub const POLYGON_SDF_HANDLE: Handle<Shader> = Handle::weak_from_u128(15528310262587036185);
+
+/// Sdf reading its points from the point buffer, used by `SmudPolyline`
+pub const POLYLINE_SDF_HANDLE: Handle<Shader> = Handle::weak_from_u128(12370865234078214097);
+
+/// Sdf of a straight line along the x axis, used by `SmudLine`
+pub const LINE_SDF_HANDLE: Handle<Shader> = Handle::weak_from_u128(6931991499538378292);
+
+/// Sdf reading its segments from the point buffer, used by `SmudPath`
+pub const PATH_SDF_HANDLE: Handle<Shader> = Handle::weak_from_u128(17406123599203519474);
+
+/// Sdf of the circles, rectangles and lines drawn by `SmudPainter`
+pub const PAINTER_SDF_HANDLE: Handle<Shader> = Handle::weak_from_u128(14207538160693052967);
+
 /// The default fill used by `SmudShape`
 pub const DEFAULT_FILL_HANDLE: Handle<Shader> = Handle::weak_from_u128(18184663565780163454);
 
 /// Simple single-colored filled fill
 pub const SIMPLE_FILL_HANDLE: Handle<Shader> = Handle::weak_from_u128(16286090377316294491);
 
+/// Dashed stroke along the contour of the sdf
+///
+/// The dashes are set by the [`SmudDash`](crate::SmudDash) of the shape. Dashes have round caps,
+/// so a dash length of 0 draws dots, and animating the phase gives marching ants.
+///
+/// Lines, polylines and paths measure the dashes along their center, so with a width of 0 the
+/// dashes follow the line itself. Other sdfs can set `instance::contour` to their arc length and
+/// perimeter, otherwise the dashes follow the angle around the center of the shape, sized for a
+/// shape reaching the edge of its frame. The number of dashes is rounded, so the gaps are
+/// stretched slightly to close the contour.
+pub const DASHED_FILL_HANDLE: Handle<Shader> = Handle::weak_from_u128(4195971226592749250);
+
+/// Compute shader simulating `SmudParticles`
+pub const PARTICLES_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(1037800877470687969);
+
 pub struct ShaderLoadingPlugin;
 
 impl Plugin for ShaderLoadingPlugin {
@@ -41,6 +88,78 @@ impl Plugin for ShaderLoadingPlugin {
             Shader::from_wgsl
         );
 
+        load_internal_asset!(
+            app,
+            INSTANCE_SHADER_HANDLE,
+            "../assets/instance.wgsl",
+            Shader::from_wgsl
+        );
+
+        let mut shaders = app.world.resource_mut::<Assets<Shader>>();
+        shaders.insert(
+            SHAPE_INSTANCE_SHADER_HANDLE,
+            Shader::from_wgsl(
+                instance_module::<ShapeVertex>("smud::shape_instance"),
+                "smud/shape_instance.wgsl",
+            ),
+        );
+        shaders.insert(
+            SHAPE_INSTANCE_3D_SHADER_HANDLE,
+            Shader::from_wgsl(
+                instance_module::<ShapeVertex3d>("smud::shape_instance_3d"),
+                "smud/shape_instance_3d.wgsl",
+            ),
+        );
+
+        load_internal_asset!(
+            app,
+            TEXT_SDF_HANDLE,
+            "../assets/text.wgsl",
+            Shader::from_wgsl
+        );
+
+        load_internal_asset!(
+            app,
+            POLYGON_SDF_HANDLE,
+            "../assets/polygon.wgsl",
+            Shader::from_wgsl
+        );
+
+        load_internal_asset!(
+            app,
+            POLYLINE_SDF_HANDLE,
+            "../assets/polyline.wgsl",
+            Shader::from_wgsl
+        );
+
+        load_internal_asset!(
+            app,
+            LINE_SDF_HANDLE,
+            "../assets/line.wgsl",
+            Shader::from_wgsl
+        );
+
+        load_internal_asset!(
+            app,
+            PARTICLES_SHADER_HANDLE,
+            "../assets/particles.wgsl",
+            Shader::from_wgsl
+        );
+
+        load_internal_asset!(
+            app,
+            PATH_SDF_HANDLE,
+            "../assets/path.wgsl",
+            Shader::from_wgsl
+        );
+
+        load_internal_asset!(
+            app,
+            PAINTER_SDF_HANDLE,
+            "../assets/painter.wgsl",
+            Shader::from_wgsl
+        );
+
         load_internal_asset!(
             app,
             VERTEX_SHADER_HANDLE,
@@ -48,6 +167,13 @@ impl Plugin for ShaderLoadingPlugin {
             Shader::from_wgsl
         );
 
+        load_internal_asset!(
+            app,
+            VERTEX_3D_SHADER_HANDLE,
+            "../assets/vertex_3d.wgsl",
+            Shader::from_wgsl
+        );
+
         load_internal_asset!(
             app,
             FRAGMENT_SHADER_HANDLE,
@@ -68,5 +194,12 @@ impl Plugin for ShaderLoadingPlugin {
             "../assets/fills/simple.wgsl",
             Shader::from_wgsl
         );
+
+        load_internal_asset!(
+            app,
+            DASHED_FILL_HANDLE,
+            "../assets/fills/dashed.wgsl",
+            Shader::from_wgsl
+        );
     }
 }
@@ -0,0 +1,171 @@
+use crate::{
+    shader_loading::VERTEX_3D_SHADER_HANDLE, DrawSmudShape, ExtractedShape, ExtractedShapes,
+    PipelineKey, SmudPipeline, SmudPipelineKey, SmudSettings,
+};
+use bevy::{
+    core_pipeline::core_3d::Transparent3d,
+    prelude::*,
+    render::{
+        render_phase::{DrawFunctions, RenderPhase},
+        render_resource::{
+            CachedRenderPipelineId, PipelineCache, PrimitiveTopology, ShaderDefVal,
+            SpecializedRenderPipelines, VertexState,
+        },
+        view::ExtractedView,
+    },
+};
+
+/// Draws the [`SmudShape`](crate::SmudShape) on the same entity with 3d cameras, instead of 2d
+/// ones
+///
+/// The shapes are drawn in the transparent pass of `Camera3d`s, sorted by their distance to the
+/// camera. They are depth tested against opaque geometry, so they can be hidden behind it, but
+/// don't write depth themselves.
+///
+/// The [`Anchor`](crate::Anchor) of a billboard moves it along the axes of the camera, so
+/// [`Anchor::BottomCenter`](crate::Anchor::BottomCenter) keeps it above its position.
+///
+/// Not supported for paths and text.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_smud::prelude::*;
+/// fn spawn_marker(mut commands: Commands, mut shaders: ResMut<Assets<Shader>>) {
+///     commands.spawn((
+///         ShapeBundle {
+///             shape: SmudShape {
+///                 color: Color::YELLOW,
+///                 sdf: shaders.add_sdf_expr("abs(smud::sd_circle(p, 40.)) - 5."),
+///                 frame: Frame::Quad(50.),
+///                 ..default()
+///             },
+///             // Shapes are still measured in world units
+///             transform: Transform::from_xyz(0., 2., 0.).with_scale(Vec3::splat(0.01)),
+///             ..default()
+///         },
+///         SmudShape3d::Billboard,
+///     ));
+/// }
+/// ```
+#[derive(Component, Reflect, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[reflect(Component)]
+pub enum SmudShape3d {
+    /// Always faces the camera, only the translation and scale of the transform are used
+    #[default]
+    Billboard,
+    /// Lies in the xy plane of the transform, and is visible from both sides
+    Plane,
+}
+
+crate::instance_layout::instance_data! {
+    /// Instance data of shapes drawn with 3d cameras
+    pub(crate) struct ShapeVertex3d {
+        /// Offset of the quad from the position, in half frames
+        anchor: [f32; 2],
+        /// The x axis of the shape, scaled
+        axis_x: [f32; 3],
+        /// The y axis of the shape, scaled
+        axis_y: [f32; 3],
+        color: [f32; 4],
+        dash: [f32; 4],
+        frame: f32,
+        params: [f32; 4],
+        position: [f32; 3],
+        time: [f32; 2],
+    }
+}
+
+impl ShapeVertex3d {
+    pub(crate) fn new(shape: &ExtractedShape) -> Self {
+        let affine = shape.transform.affine();
+        Self {
+            anchor: shape.anchor.into(),
+            axis_x: affine.matrix3.x_axis.into(),
+            axis_y: affine.matrix3.y_axis.into(),
+            color: shape.color.as_linear_rgba_f32(),
+            dash: shape.dash.into(),
+            frame: shape.frame,
+            params: shape.params.into(),
+            position: affine.translation.into(),
+            time: crate::time::vertex_time(shape.time),
+        }
+    }
+}
+
+/// The vertex stage of shapes drawn with 3d cameras
+pub(crate) fn vertex_state(
+    three_d: SmudShape3d,
+    mut shader_defs: Vec<ShaderDefVal>,
+    pipeline: &SmudPipeline,
+) -> VertexState {
+    if three_d == SmudShape3d::Billboard {
+        shader_defs.push("BILLBOARD".into());
+    }
+
+    VertexState {
+        shader: VERTEX_3D_SHADER_HANDLE,
+        entry_point: "vertex".into(),
+        shader_defs,
+        buffers: pipeline.instance_buffers::<ShapeVertex3d>(),
+    }
+}
+
+pub(crate) fn queue_shapes_3d(
+    draw_functions: Res<DrawFunctions<Transparent3d>>,
+    smud_pipeline: Res<SmudPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<SmudPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    msaa: Res<Msaa>,
+    settings: Res<SmudSettings>,
+    extracted_shapes: Res<ExtractedShapes>,
+    mut views: Query<(&mut RenderPhase<Transparent3d>, &ExtractedView)>,
+) {
+    let draw_smud_shape_function = draw_functions.read().get_id::<DrawSmudShape>().unwrap();
+
+    for (mut transparent_phase, view) in &mut views {
+        let mesh_key = PipelineKey::from_msaa_samples(msaa.samples())
+            | PipelineKey::from_primitive_topology(PrimitiveTopology::TriangleStrip);
+        let rangefinder = view.rangefinder3d();
+
+        for (entity, extracted_shape) in extracted_shapes.shapes.iter() {
+            let Some(three_d) = extracted_shape.three_d else {
+                continue;
+            };
+            let shader = (
+                extracted_shape.sdf_shader.id(),
+                extracted_shape.fill_shader.id(),
+            );
+            if !smud_pipeline.shaders.0.contains_key(&shader) {
+                continue;
+            }
+
+            let pipeline = pipelines.specialize(
+                &pipeline_cache,
+                &smud_pipeline,
+                SmudPipelineKey {
+                    mesh: mesh_key,
+                    shader,
+                    hdr: view.hdr,
+                    three_d: Some(three_d),
+                    screen_space: extracted_shape.screen_space,
+                    anti_aliasing: settings.anti_aliasing,
+                    pixel_art: None,
+                },
+            );
+            if pipeline == CachedRenderPipelineId::INVALID {
+                continue;
+            }
+
+            transparent_phase.add(Transparent3d {
+                distance: rangefinder
+                    .distance_translation(&extracted_shape.transform.translation()),
+                pipeline,
+                entity: *entity,
+                draw_function: draw_smud_shape_function,
+                // batch_range and dynamic_offset will be calculated in prepare_shapes
+                batch_range: 0..0,
+                dynamic_offset: None,
+            });
+        }
+    }
+}
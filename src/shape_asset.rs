@@ -0,0 +1,219 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::{BoxedFuture, HashSet},
+};
+use ron::extensions::Extensions;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    sdf_assets::{asset_import_path, fill_source, sdf_source},
+    Anchor, Frame, SmudParams, SmudShaderSource, SmudShape, DEFAULT_FILL_HANDLE,
+};
+
+/// A complete shape description, usually loaded from a `.smud.ron` file
+///
+/// Add a `Handle<SmudShapeAsset>` to an entity, and its [`SmudShape`] and [`SmudParams`] are
+/// kept in sync with the asset, including when it's hot reloaded. Any changes made to those
+/// components in code are overwritten when the asset changes.
+///
+/// ```ron
+/// (
+///     sdf: Expr("smud::sd_circle(p, 50.)"),
+///     // optional, defaults to `DEFAULT_FILL_HANDLE`
+///     fill: Path("fills/outline.wgsl"),
+///     frame: Quad(55.),
+///     // optional
+///     anchor: BottomLeft,
+///     // optional
+///     color: Rgba(red: 0.36, green: 0.41, blue: 0.45, alpha: 1.0),
+///     // optional, read with `smud::params()`
+///     params: (2., 0., 0., 0.),
+/// )
+/// ```
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct SmudShapeAsset {
+    /// Shader containing the sdf of the shape
+    #[dependency]
+    pub sdf: Handle<Shader>,
+    /// Shader containing the fill of the shape
+    #[dependency]
+    pub fill: Handle<Shader>,
+    /// The outer bounds of the shape
+    pub frame: Frame,
+    /// Which point of the frame is placed at the transform of the shape
+    pub anchor: Anchor,
+    /// The color of shapes using this asset
+    pub color: Color,
+    /// The parameters of shapes using this asset, see [`SmudParams`]
+    pub params: Vec4,
+}
+
+impl SmudShapeAsset {
+    /// The shape component described by this asset
+    pub fn shape(&self) -> SmudShape {
+        SmudShape {
+            color: self.color,
+            sdf: self.sdf.clone(),
+            fill: self.fill.clone(),
+            frame: self.frame,
+            anchor: self.anchor,
+        }
+    }
+}
+
+/// The contents of a `.smud.ron` file
+#[derive(Deserialize)]
+struct SmudShapeFile {
+    sdf: SmudShaderSource,
+    #[serde(default)]
+    fill: Option<SmudShaderSource>,
+    frame: Frame,
+    #[serde(default)]
+    anchor: Anchor,
+    #[serde(default = "default_color")]
+    color: Color,
+    #[serde(default)]
+    params: Vec4,
+}
+
+fn default_color() -> Color {
+    SmudShape::default().color
+}
+
+/// Possible errors produced by [`SmudShapeLoader`]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SmudShapeLoaderError {
+    /// An [IO](std::io) Error
+    #[error("Could not read shape: {0}")]
+    Io(#[from] std::io::Error),
+    /// The file is not a valid shape description
+    #[error("Could not parse shape: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+/// Loads [`SmudShapeAsset`]s from `.smud.ron` files
+#[derive(Default)]
+pub struct SmudShapeLoader;
+
+impl AssetLoader for SmudShapeLoader {
+    type Asset = SmudShapeAsset;
+    type Settings = ();
+    type Error = SmudShapeLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<SmudShapeAsset, SmudShapeLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let file: SmudShapeFile = ron::Options::default()
+                .with_default_extension(Extensions::IMPLICIT_SOME)
+                .from_bytes(&bytes)?;
+
+            let sdf = load_shader(load_context, "sdf", file.sdf);
+            let fill = match file.fill {
+                Some(fill) => load_shader(load_context, "fill", fill),
+                None => DEFAULT_FILL_HANDLE,
+            };
+
+            Ok(SmudShapeAsset {
+                sdf,
+                fill,
+                frame: file.frame,
+                anchor: file.anchor,
+                color: file.color,
+                params: file.params,
+            })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["smud.ron"]
+    }
+}
+
+/// Loads the sdf or fill shader of a shape, shaders defined in the file become labeled assets
+fn load_shader(
+    load_context: &mut LoadContext,
+    label: &str,
+    source: SmudShaderSource,
+) -> Handle<Shader> {
+    let path = load_context
+        .asset_path()
+        .clone()
+        .with_label(label.to_string());
+    // Stays the same when the file is reloaded
+    let import_path = asset_import_path(&path);
+    let wrap = |body: &str| match label {
+        "fill" => fill_source(&import_path, body),
+        _ => sdf_source(&import_path, body),
+    };
+
+    let shader = match source {
+        SmudShaderSource::Path(path) => return load_context.load(path),
+        SmudShaderSource::Internal(uuid) => return Handle::weak_from_u128(uuid.as_u128()),
+        SmudShaderSource::Wgsl(wgsl) => {
+            let mut shader = Shader::from_wgsl(wgsl, path.to_string());
+            shader.set_import_path(&import_path);
+            shader
+        }
+        SmudShaderSource::Body(body) => Shader::from_wgsl(wrap(&body), path.to_string()),
+        SmudShaderSource::Expr(e) => {
+            Shader::from_wgsl(wrap(&format!("return {e};")), path.to_string())
+        }
+    };
+    load_context.add_labeled_asset(label.to_string(), shader)
+}
+
+/// Copies shape assets into the [`SmudShape`] and [`SmudParams`] of entities using them
+#[allow(clippy::type_complexity)]
+pub(crate) fn resolve_shape_assets(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<SmudShapeAsset>>,
+    assets: Res<Assets<SmudShapeAsset>>,
+    mut query: Query<(
+        Entity,
+        Ref<Handle<SmudShapeAsset>>,
+        Option<&mut SmudShape>,
+        Option<&mut SmudParams>,
+    )>,
+) {
+    let changed: HashSet<_> = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id }
+            | AssetEvent::Modified { id }
+            | AssetEvent::LoadedWithDependencies { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (entity, handle, shape, params) in &mut query {
+        if !handle.is_changed() && !changed.contains(&handle.id()) {
+            continue;
+        }
+        let Some(asset) = assets.get(&*handle) else {
+            // Resolved once the asset is loaded
+            continue;
+        };
+
+        match shape {
+            Some(mut shape) => *shape = asset.shape(),
+            None => {
+                commands.entity(entity).insert(asset.shape());
+            }
+        }
+        match params {
+            Some(mut params) => params.0 = asset.params,
+            None => {
+                commands.entity(entity).insert(SmudParams(asset.params));
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use bevy::{
+    prelude::*,
+    render::render_resource::Source,
+    utils::{HashMap, Uuid},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    prelude::SdfAssets, util::generate_shader_id, Anchor, Frame, SmudShape, DEFAULT_FILL_HANDLE,
+};
+
+/// Where the wgsl code of an sdf or fill shader comes from
+#[derive(Reflect, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub enum SmudShaderSource {
+    /// A complete shader file, relative to the assets folder
+    Path(String),
+    /// A shader with a fixed id, like [`DEFAULT_FILL_HANDLE`] or shaders loaded with
+    /// [`load_internal_asset!`](bevy::asset::load_internal_asset)
+    Internal(Uuid),
+    /// The source of a complete shader module
+    Wgsl(String),
+    /// A function body, wrapped like [`SdfAssets::add_sdf_body`](crate::prelude::SdfAssets::add_sdf_body)
+    Body(String),
+    /// A single expression, wrapped like [`SdfAssets::add_sdf_expr`](crate::prelude::SdfAssets::add_sdf_expr)
+    Expr(String),
+}
+
+impl SmudShaderSource {
+    /// Describes where the shader of a handle comes from
+    ///
+    /// Loaded shaders are described by their path, and shaders added in code by their source.
+    /// Returns `None` if the shader isn't loaded yet, or isn't written in wgsl.
+    pub fn from_handle(handle: &Handle<Shader>, shaders: &Assets<Shader>) -> Option<Self> {
+        if let Some(path) = handle.path() {
+            return Some(Self::Path(path.to_string()));
+        }
+        if let AssetId::Uuid { uuid } = handle.id() {
+            return Some(Self::Internal(uuid));
+        }
+        match &shaders.get(handle)?.source {
+            Source::Wgsl(wgsl) => Some(Self::Wgsl(wgsl.to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// A serializable version of [`SmudShape`], for saving shapes in scenes
+///
+/// Shader handles can't be saved, so this refers to shaders by asset path or source instead.
+/// Entities with this component get a [`SmudShape`] created from it, and updated whenever
+/// it changes. Shaders created from source are shared by all shapes with the same source.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_smud::prelude::*;
+/// # use bevy_smud::SmudShapeSource;
+/// fn add_shape_sources(
+///     mut commands: Commands,
+///     shapes: Query<(Entity, &SmudShape)>,
+///     shaders: Res<Assets<Shader>>,
+/// ) {
+///     for (entity, shape) in &shapes {
+///         if let Some(source) = SmudShapeSource::from_shape(shape, &shaders) {
+///             commands.entity(entity).insert(source);
+///         }
+///     }
+/// }
+/// ```
+///
+/// The entities can then be saved with a `DynamicScene` that leaves out the [`SmudShape`].
+#[derive(Component, Reflect, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct SmudShapeSource {
+    /// The color used by the fill shader
+    pub color: Color,
+    /// Where the sdf shader comes from
+    pub sdf: SmudShaderSource,
+    /// Where the fill shader comes from
+    pub fill: SmudShaderSource,
+    /// The outer bounds for the shape
+    pub frame: Frame,
+    /// Which point of the frame is placed at the transform of the shape
+    #[serde(default)]
+    pub anchor: Anchor,
+}
+
+impl Default for SmudShapeSource {
+    fn default() -> Self {
+        let shape = SmudShape::default();
+        Self {
+            color: shape.color,
+            sdf: SmudShaderSource::Internal(AssetId::<Shader>::DEFAULT_UUID),
+            fill: SmudShaderSource::Internal(internal_uuid(&DEFAULT_FILL_HANDLE)),
+            frame: shape.frame,
+            anchor: shape.anchor,
+        }
+    }
+}
+
+impl SmudShapeSource {
+    /// Describes a shape by where its shaders come from
+    ///
+    /// Returns `None` if any of the shaders can't be described, see
+    /// [`SmudShaderSource::from_handle`].
+    pub fn from_shape(shape: &SmudShape, shaders: &Assets<Shader>) -> Option<Self> {
+        Some(Self {
+            color: shape.color,
+            sdf: SmudShaderSource::from_handle(&shape.sdf, shaders)?,
+            fill: SmudShaderSource::from_handle(&shape.fill, shaders)?,
+            frame: shape.frame,
+            anchor: shape.anchor,
+        })
+    }
+}
+
+fn internal_uuid(handle: &Handle<Shader>) -> Uuid {
+    match handle.id() {
+        AssetId::Uuid { uuid } => uuid,
+        AssetId::Index { .. } => unreachable!("internal shaders have fixed ids"),
+    }
+}
+
+/// Shaders created from sources, so shapes with the same source share them
+#[derive(Resource, Default)]
+pub(crate) struct SourceShaders(HashMap<(ShaderKind, SmudShaderSource), Handle<Shader>>);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum ShaderKind {
+    Sdf,
+    Fill,
+}
+
+impl SourceShaders {
+    fn get_or_add(
+        &mut self,
+        kind: ShaderKind,
+        source: &SmudShaderSource,
+        asset_server: &AssetServer,
+        shaders: &mut Assets<Shader>,
+    ) -> Handle<Shader> {
+        match source {
+            SmudShaderSource::Path(path) => return asset_server.load(path.clone()),
+            SmudShaderSource::Internal(uuid) => return Handle::weak_from_u128(uuid.as_u128()),
+            _ => {}
+        }
+
+        self.0
+            .entry((kind, source.clone()))
+            .or_insert_with(|| match (kind, source) {
+                (_, SmudShaderSource::Wgsl(wgsl)) => {
+                    let mut shader = Shader::from_wgsl(wgsl.clone(), file!());
+                    // The original shader may still be around, with the import path in the source
+                    let id = generate_shader_id();
+                    shader.set_import_path(format!("smud::source{id}"));
+                    shaders.add(shader)
+                }
+                (ShaderKind::Sdf, SmudShaderSource::Body(body)) => shaders.add_sdf_body(body),
+                (ShaderKind::Sdf, SmudShaderSource::Expr(e)) => shaders.add_sdf_expr(e),
+                (ShaderKind::Fill, SmudShaderSource::Body(body)) => shaders.add_fill_body(body),
+                (ShaderKind::Fill, SmudShaderSource::Expr(e)) => shaders.add_fill_expr(e),
+                (_, SmudShaderSource::Path(_) | SmudShaderSource::Internal(_)) => unreachable!(),
+            })
+            .clone()
+    }
+}
+
+/// Creates or updates the [`SmudShape`] of entities with a changed [`SmudShapeSource`]
+pub(crate) fn resolve_shape_sources(
+    mut commands: Commands,
+    mut source_shaders: ResMut<SourceShaders>,
+    asset_server: Res<AssetServer>,
+    mut shaders: ResMut<Assets<Shader>>,
+    mut query: Query<(Entity, &SmudShapeSource, Option<&mut SmudShape>), Changed<SmudShapeSource>>,
+) {
+    for (entity, source, shape) in &mut query {
+        let sdf =
+            source_shaders.get_or_add(ShaderKind::Sdf, &source.sdf, &asset_server, &mut shaders);
+        let fill =
+            source_shaders.get_or_add(ShaderKind::Fill, &source.fill, &asset_server, &mut shaders);
+
+        match shape {
+            Some(mut shape) => {
+                // Don't replace strong handles with weak ones of the same shader
+                if shape.sdf.id() != sdf.id() {
+                    shape.sdf = sdf;
+                }
+                if shape.fill.id() != fill.id() {
+                    shape.fill = fill;
+                }
+                shape.color = source.color;
+                shape.frame = source.frame;
+                shape.anchor = source.anchor;
+            }
+            None => {
+                commands.entity(entity).insert(SmudShape {
+                    color: source.color,
+                    sdf,
+                    fill,
+                    frame: source.frame,
+                    anchor: source.anchor,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,1131 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use bevy::{math::Affine2, prelude::*, utils::HashMap};
+use thiserror::Error;
+use xml::reader::{EventReader, XmlEvent};
+
+use crate::{
+    prelude::SdfAssets, FillRule, Frame, PathCommand, ShapeBundle, SmudPath, SmudPolygon,
+    SmudShape, StrokeCap, StrokeJoin, SIMPLE_FILL_HANDLE,
+};
+
+/// Number of line segments each curve is flattened into for filled paths
+const CURVE_SUBDIVISIONS: usize = 16;
+
+/// Number of points ellipses are approximated with when they're skewed
+const ELLIPSE_POINTS: usize = 64;
+
+/// Extra space around shapes covered by their frames, for anti-aliasing
+const FRAME_PADDING: f32 = 2.;
+
+/// Depth between the shapes spawned by [`SmudSvg::spawn`], so they're drawn in document order
+const SHAPE_DEPTH: f32 = 0.001;
+
+/// Elements whose contents aren't drawn
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "defs",
+    "clipPath",
+    "mask",
+    "symbol",
+    "pattern",
+    "marker",
+    "linearGradient",
+    "radialGradient",
+    "style",
+    "title",
+    "desc",
+    "metadata",
+];
+
+/// Elements that are drawn, but not supported
+const UNSUPPORTED_ELEMENTS: &[&str] = &["text", "image", "use", "foreignObject", "switch"];
+
+/// Possible errors produced by [`SmudSvg::parse`]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SmudSvgError {
+    /// The document is not valid xml
+    #[error("Could not parse svg: {0}")]
+    Xml(#[from] xml::reader::Error),
+    /// An attribute has a value that couldn't be parsed
+    #[error("Invalid value for {attribute} of <{element}>: {value:?}")]
+    Attribute {
+        /// The element with the attribute
+        element: String,
+        /// The name of the attribute
+        attribute: String,
+        /// The value that couldn't be parsed
+        value: String,
+    },
+}
+
+/// An svg document converted into smud shapes
+///
+/// Supports `<path>`, `<rect>`, `<circle>`, `<ellipse>`, `<line>`, `<polyline>` and `<polygon>`
+/// elements in nested groups, with transforms and solid fill and stroke colors. Gradients,
+/// text, clipping and masks are not supported.
+///
+/// Rectangles, circles and ellipses become sdf shapes using the primitives in `smud.wgsl`,
+/// polygons become [`SmudPolygon`]s, and strokes become [`SmudPath`]s. Filled paths become
+/// polygons as well, with a ring per sub-path for shapes with holes.
+///
+/// The document is centered on the origin, with the y axis flipped to point up.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_smud::SmudSvg;
+/// fn setup(mut commands: Commands, mut shaders: ResMut<Assets<Shader>>) {
+///     let svg = r#"<svg viewBox="0 0 24 24"><circle cx="12" cy="12" r="10" fill="teal"/></svg>"#;
+///     let icon = SmudSvg::parse(svg, &mut shaders).unwrap();
+///     let entity = icon.spawn(&mut commands);
+///     commands.entity(entity).insert(Transform::from_scale(Vec3::splat(10.)));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SmudSvg {
+    /// The size of the document, in svg units
+    pub size: Vec2,
+    /// The shapes, in drawing order
+    pub shapes: Vec<SvgShape>,
+}
+
+/// A single shape of a [`SmudSvg`]
+#[derive(Debug, Clone)]
+pub struct SvgShape {
+    /// The color, sdf, fill and frame of the shape
+    pub shape: SmudShape,
+    /// Position and orientation of the shape relative to the center of the document
+    pub transform: Transform,
+    /// Where the geometry of the shape comes from
+    pub geometry: SvgGeometry,
+}
+
+/// How the geometry of an [`SvgShape`] is described
+#[derive(Debug, Clone)]
+pub enum SvgGeometry {
+    /// The sdf of the [`SmudShape`]
+    Sdf,
+    /// A polygon, replacing the sdf
+    Polygon(SmudPolygon),
+    /// A stroked path, replacing the sdf
+    Path(SmudPath),
+}
+
+impl SmudSvg {
+    /// Converts an svg document into smud shapes, adding the sdf shaders they need
+    pub fn parse(svg: &str, shaders: &mut Assets<Shader>) -> Result<Self, SmudSvgError> {
+        let mut converter = Converter {
+            shaders,
+            sdfs: default(),
+            shapes: Vec::new(),
+        };
+        let mut size = Vec2::ZERO;
+        let mut styles = vec![Style::default()];
+        let mut skip_depth = 0;
+
+        for event in EventReader::from_str(svg) {
+            match event? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => {
+                    let element = name.local_name.as_str();
+                    if skip_depth > 0 || SKIPPED_ELEMENTS.contains(&element) {
+                        skip_depth += 1;
+                        continue;
+                    }
+                    if UNSUPPORTED_ELEMENTS.contains(&element) {
+                        warn!("Skipping unsupported svg element <{element}>");
+                        skip_depth += 1;
+                        continue;
+                    }
+
+                    let attributes = Attributes {
+                        element,
+                        values: attributes
+                            .iter()
+                            .filter(|a| a.name.prefix.is_none())
+                            .map(|a| (a.name.local_name.as_str(), a.value.as_str()))
+                            .collect(),
+                    };
+
+                    let mut style = styles.last().unwrap().clone();
+                    if element == "svg" && styles.len() == 1 {
+                        let (document_size, transform) = document_transform(&attributes)?;
+                        size = document_size;
+                        style.transform = transform;
+                    }
+                    style.apply(&attributes)?;
+                    converter.element(&attributes, &style)?;
+                    styles.push(style);
+                }
+                XmlEvent::EndElement { .. } => {
+                    if skip_depth > 0 {
+                        skip_depth -= 1;
+                    } else {
+                        styles.pop();
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            size,
+            shapes: converter.shapes,
+        })
+    }
+
+    /// Spawns the shapes as children of a new entity, and returns it
+    pub fn spawn(&self, commands: &mut Commands) -> Entity {
+        commands
+            .spawn(SpatialBundle::default())
+            .with_children(|parent| {
+                for (i, svg_shape) in self.shapes.iter().enumerate() {
+                    let mut transform = svg_shape.transform;
+                    transform.translation.z += i as f32 * SHAPE_DEPTH;
+                    let mut entity = parent.spawn(ShapeBundle {
+                        shape: svg_shape.shape.clone(),
+                        transform,
+                        ..default()
+                    });
+                    match &svg_shape.geometry {
+                        SvgGeometry::Sdf => {}
+                        SvgGeometry::Polygon(polygon) => {
+                            entity.insert(polygon.clone());
+                        }
+                        SvgGeometry::Path(path) => {
+                            entity.insert(path.clone());
+                        }
+                    }
+                }
+            })
+            .id()
+    }
+}
+
+/// The attributes of an element
+struct Attributes<'a> {
+    element: &'a str,
+    values: HashMap<&'a str, &'a str>,
+}
+
+impl Attributes<'_> {
+    fn error(&self, attribute: &str, value: &str) -> SmudSvgError {
+        SmudSvgError::Attribute {
+            element: self.element.to_string(),
+            attribute: attribute.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    /// Parses a length attribute, in svg units
+    fn length(&self, attribute: &str) -> Result<Option<f32>, SmudSvgError> {
+        self.values
+            .get(attribute)
+            .map(|value| parse_length(value).ok_or_else(|| self.error(attribute, value)))
+            .transpose()
+    }
+
+    fn length_or_zero(&self, attribute: &str) -> Result<f32, SmudSvgError> {
+        Ok(self.length(attribute)?.unwrap_or(0.))
+    }
+
+    fn points(&self, attribute: &str) -> Result<Vec<Vec2>, SmudSvgError> {
+        let value = self.values.get(attribute).copied().unwrap_or_default();
+        let numbers = parse_numbers(value).ok_or_else(|| self.error(attribute, value))?;
+        Ok(numbers
+            .chunks_exact(2)
+            .map(|xy| Vec2::new(xy[0], xy[1]))
+            .collect())
+    }
+}
+
+/// The inherited presentation attributes of an element
+#[derive(Clone)]
+struct Style {
+    fill: Option<Color>,
+    stroke: Option<Color>,
+    stroke_width: f32,
+    opacity: f32,
+    fill_opacity: f32,
+    stroke_opacity: f32,
+    fill_rule: FillRule,
+    cap: StrokeCap,
+    join: StrokeJoin,
+    miter_limit: f32,
+    /// From svg user space to the space of the document
+    transform: Affine2,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            fill: Some(Color::BLACK),
+            stroke: None,
+            stroke_width: 1.,
+            opacity: 1.,
+            fill_opacity: 1.,
+            stroke_opacity: 1.,
+            fill_rule: FillRule::NonZero,
+            cap: StrokeCap::Butt,
+            join: StrokeJoin::Miter,
+            miter_limit: 4.,
+            transform: Affine2::IDENTITY,
+        }
+    }
+}
+
+impl Style {
+    /// Applies the presentation attributes and `style` of an element
+    fn apply(&mut self, attributes: &Attributes) -> Result<(), SmudSvgError> {
+        for (&name, &value) in &attributes.values {
+            if name == "transform" {
+                let transform =
+                    parse_transform(value).ok_or_else(|| attributes.error(name, value))?;
+                self.transform *= transform;
+            } else if name != "style" {
+                self.set(name, value)
+                    .ok_or_else(|| attributes.error(name, value))?;
+            }
+        }
+        // Declarations in `style` take precedence over presentation attributes
+        if let Some(style) = attributes.values.get("style") {
+            for declaration in style.split(';') {
+                let Some((name, value)) = declaration.split_once(':') else {
+                    continue;
+                };
+                let (name, value) = (name.trim(), value.trim());
+                self.set(name, value)
+                    .ok_or_else(|| attributes.error(name, value))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets a single property, returns `None` if the value is invalid
+    fn set(&mut self, name: &str, value: &str) -> Option<()> {
+        match name {
+            "fill" => self.fill = parse_paint(value)?,
+            "stroke" => self.stroke = parse_paint(value)?,
+            "stroke-width" => self.stroke_width = parse_length(value)?,
+            "opacity" => self.opacity *= value.parse::<f32>().ok()?,
+            "fill-opacity" => self.fill_opacity = value.parse().ok()?,
+            "stroke-opacity" => self.stroke_opacity = value.parse().ok()?,
+            "fill-rule" => {
+                self.fill_rule = match value {
+                    "nonzero" => FillRule::NonZero,
+                    "evenodd" => FillRule::EvenOdd,
+                    _ => return None,
+                }
+            }
+            "stroke-linecap" => {
+                self.cap = match value {
+                    "butt" => StrokeCap::Butt,
+                    "round" => StrokeCap::Round,
+                    "square" => StrokeCap::Square,
+                    _ => return None,
+                }
+            }
+            "stroke-linejoin" => {
+                self.join = match value {
+                    "miter" | "miter-clip" | "arcs" => StrokeJoin::Miter,
+                    "round" => StrokeJoin::Round,
+                    "bevel" => StrokeJoin::Bevel,
+                    _ => return None,
+                }
+            }
+            "stroke-miterlimit" => self.miter_limit = value.parse().ok()?,
+            // Other attributes don't affect the shapes
+            _ => {}
+        }
+        Some(())
+    }
+
+    fn fill_color(&self) -> Option<Color> {
+        let color = self.fill?;
+        Some(color.with_a(color.a() * self.fill_opacity * self.opacity))
+    }
+
+    fn stroke_color(&self) -> Option<Color> {
+        let color = self.stroke.filter(|_| self.stroke_width > 0.)?;
+        Some(color.with_a(color.a() * self.stroke_opacity * self.opacity))
+    }
+
+    /// The stroke width in document space
+    fn scaled_stroke_width(&self) -> f32 {
+        self.stroke_width * self.transform.matrix2.determinant().abs().sqrt()
+    }
+}
+
+/// Geometry of an element, in svg user space
+enum Element {
+    Rect { min: Vec2, size: Vec2, radius: f32 },
+    Ellipse { center: Vec2, radii: Vec2 },
+    Points { points: Vec<Vec2>, closed: bool },
+    Path(Vec<PathCommand>),
+}
+
+/// Collects the shapes of the document
+struct Converter<'a> {
+    shaders: &'a mut Assets<Shader>,
+    /// Sdf shaders by expression, so identical shapes share them
+    sdfs: HashMap<String, Handle<Shader>>,
+    shapes: Vec<SvgShape>,
+}
+
+impl Converter<'_> {
+    fn element(&mut self, attributes: &Attributes, style: &Style) -> Result<(), SmudSvgError> {
+        let element = match attributes.element {
+            "rect" => {
+                let size = Vec2::new(
+                    attributes.length_or_zero("width")?,
+                    attributes.length_or_zero("height")?,
+                );
+                let rx = attributes.length("rx")?;
+                let ry = attributes.length("ry")?;
+                // Elliptical corners aren't supported, so use the smaller radius
+                let radius = match (rx, ry) {
+                    (Some(rx), Some(ry)) => rx.min(ry),
+                    (Some(r), None) | (None, Some(r)) => r,
+                    (None, None) => 0.,
+                };
+                Element::Rect {
+                    min: Vec2::new(
+                        attributes.length_or_zero("x")?,
+                        attributes.length_or_zero("y")?,
+                    ),
+                    size,
+                    radius: radius.min(size.min_element() / 2.),
+                }
+            }
+            "circle" => {
+                let r = attributes.length_or_zero("r")?;
+                Element::Ellipse {
+                    center: Vec2::new(
+                        attributes.length_or_zero("cx")?,
+                        attributes.length_or_zero("cy")?,
+                    ),
+                    radii: Vec2::splat(r),
+                }
+            }
+            "ellipse" => Element::Ellipse {
+                center: Vec2::new(
+                    attributes.length_or_zero("cx")?,
+                    attributes.length_or_zero("cy")?,
+                ),
+                radii: Vec2::new(
+                    attributes.length_or_zero("rx")?,
+                    attributes.length_or_zero("ry")?,
+                ),
+            },
+            "line" => Element::Points {
+                points: vec![
+                    Vec2::new(
+                        attributes.length_or_zero("x1")?,
+                        attributes.length_or_zero("y1")?,
+                    ),
+                    Vec2::new(
+                        attributes.length_or_zero("x2")?,
+                        attributes.length_or_zero("y2")?,
+                    ),
+                ],
+                closed: false,
+            },
+            "polyline" => Element::Points {
+                points: attributes.points("points")?,
+                closed: false,
+            },
+            "polygon" => Element::Points {
+                points: attributes.points("points")?,
+                closed: true,
+            },
+            "path" => {
+                let d = attributes.values.get("d").copied().unwrap_or_default();
+                Element::Path(parse_path_data(d).ok_or_else(|| attributes.error("d", d))?)
+            }
+            _ => return Ok(()),
+        };
+
+        self.add_element(element, style);
+        Ok(())
+    }
+
+    fn add_element(&mut self, element: Element, style: &Style) {
+        let transform = style.transform;
+        match element {
+            Element::Rect { size, .. } | Element::Ellipse { radii: size, .. }
+                if size.min_element() <= 0. => {}
+            Element::Rect { min, size, radius } => match decompose(transform) {
+                Some((_, rotation, scale)) => {
+                    let half_size = size / 2. * scale;
+                    let radius = radius * scale.min_element();
+                    let sdf = if radius > 0. {
+                        format!(
+                            "smud::sd_rounded_box(p, vec2<f32>({:?}, {:?}), vec4<f32>({radius:?}))",
+                            half_size.x, half_size.y
+                        )
+                    } else {
+                        format!(
+                            "smud::sd_box(p, vec2<f32>({:?}, {:?}))",
+                            half_size.x, half_size.y
+                        )
+                    };
+                    let center = transform.transform_point2(min + size / 2.);
+                    self.add_sdf(&sdf, center, rotation, half_size.max_element(), style);
+                }
+                None => {
+                    let points = vec![
+                        min,
+                        min + Vec2::new(size.x, 0.),
+                        min + size,
+                        min + Vec2::new(0., size.y),
+                    ];
+                    self.add_element(
+                        Element::Points {
+                            points,
+                            closed: true,
+                        },
+                        style,
+                    );
+                }
+            },
+            Element::Ellipse { center, radii } => match decompose(transform) {
+                Some((_, rotation, scale)) => {
+                    let radii = radii * scale;
+                    let sdf = if (radii.x - radii.y).abs() < 1e-3 {
+                        format!("smud::sd_circle(p, {:?})", radii.x)
+                    } else {
+                        format!("smud::sd_ellipse(p, {:?}, {:?})", radii.x, radii.y)
+                    };
+                    let center = transform.transform_point2(center);
+                    self.add_sdf(&sdf, center, rotation, radii.max_element(), style);
+                }
+                None => {
+                    let points = (0..ELLIPSE_POINTS)
+                        .map(|i| {
+                            let angle = i as f32 / ELLIPSE_POINTS as f32 * TAU;
+                            center + Vec2::from_angle(angle) * radii
+                        })
+                        .collect();
+                    self.add_element(
+                        Element::Points {
+                            points,
+                            closed: true,
+                        },
+                        style,
+                    );
+                }
+            },
+            Element::Points { points, closed } => {
+                if points.len() < 2 {
+                    return;
+                }
+                let points: Vec<Vec2> = points
+                    .iter()
+                    .map(|p| transform.transform_point2(*p))
+                    .collect();
+                if let Some(color) = style.fill_color().filter(|_| points.len() >= 3) {
+                    self.add_polygon(std::slice::from_ref(&points), color, style.fill_rule);
+                }
+                let mut commands = vec![PathCommand::MoveTo(points[0])];
+                commands.extend(points[1..].iter().map(|p| PathCommand::LineTo(*p)));
+                if closed {
+                    commands.push(PathCommand::Close);
+                }
+                self.add_stroke(commands, style);
+            }
+            Element::Path(commands) => {
+                let commands: Vec<PathCommand> = commands
+                    .into_iter()
+                    .map(|command| transform_command(command, transform))
+                    .collect();
+                if let Some(color) = style.fill_color() {
+                    let rings = flatten(&commands);
+                    if !rings.is_empty() {
+                        self.add_polygon(&rings, color, style.fill_rule);
+                    }
+                }
+                self.add_stroke(commands, style);
+            }
+        }
+    }
+
+    /// Adds the fill and stroke of an sdf primitive centered on the origin
+    fn add_sdf(&mut self, sdf: &str, center: Vec2, rotation: f32, extent: f32, style: &Style) {
+        let transform = Transform::from_translation(center.extend(0.))
+            .with_rotation(Quat::from_rotation_z(rotation));
+
+        if let Some(color) = style.fill_color() {
+            let sdf = self.sdf(sdf.to_string());
+            self.push(sdf, color, extent, transform, SvgGeometry::Sdf);
+        }
+        if let Some(color) = style.stroke_color() {
+            let half_width = style.scaled_stroke_width() / 2.;
+            let sdf = self.sdf(format!("abs({sdf}) - {half_width:?}"));
+            self.push(sdf, color, extent + half_width, transform, SvgGeometry::Sdf);
+        }
+    }
+
+    /// Adds a filled polygon, with several rings for sub-paths
+    fn add_polygon(&mut self, rings: &[Vec<Vec2>], color: Color, fill_rule: FillRule) {
+        let (min, max) = rings.iter().flatten().fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), p| (min.min(*p), max.max(*p)),
+        );
+        let center = (min + max) / 2.;
+        let extent = ((max - min) / 2.).max_element();
+        let transform = Transform::from_translation(center.extend(0.));
+
+        let mut polygon = SmudPolygon::from_rings(
+            rings
+                .iter()
+                .map(|ring| ring.iter().map(|p| *p - center).collect()),
+        )
+        .with_fill_rule(fill_rule);
+        if rings.len() == 1 {
+            polygon.ring_lengths.clear();
+        }
+        self.push(
+            default(),
+            color,
+            extent,
+            transform,
+            SvgGeometry::Polygon(polygon),
+        );
+    }
+
+    fn add_stroke(&mut self, commands: Vec<PathCommand>, style: &Style) {
+        let Some(color) = style.stroke_color() else {
+            return;
+        };
+        let path = SmudPath {
+            commands,
+            width: style.scaled_stroke_width(),
+            cap: style.cap,
+            join: style.join,
+            miter_limit: style.miter_limit,
+            ..default()
+        };
+        // Paths have a frame per segment
+        self.push(default(), color, 0., default(), SvgGeometry::Path(path));
+    }
+
+    fn push(
+        &mut self,
+        sdf: Handle<Shader>,
+        color: Color,
+        extent: f32,
+        transform: Transform,
+        geometry: SvgGeometry,
+    ) {
+        self.shapes.push(SvgShape {
+            shape: SmudShape {
+                color,
+                sdf,
+                fill: SIMPLE_FILL_HANDLE,
+                frame: Frame::Quad(extent + FRAME_PADDING),
+                anchor: default(),
+            },
+            transform,
+            geometry,
+        });
+    }
+
+    /// Gets or creates an sdf shader from an expression
+    fn sdf(&mut self, sdf: String) -> Handle<Shader> {
+        let shaders = &mut self.shaders;
+        self.sdfs
+            .entry(sdf)
+            .or_insert_with_key(|sdf| shaders.add_sdf_expr(sdf.clone()))
+            .clone()
+    }
+}
+
+/// Splits a transform into translation, rotation and scale, if it doesn't skew
+fn decompose(transform: Affine2) -> Option<(Vec2, f32, Vec2)> {
+    let x = transform.matrix2.x_axis;
+    let y = transform.matrix2.y_axis;
+    let scale = Vec2::new(x.length(), y.length());
+    if scale.min_element() <= 0. || x.dot(y).abs() > 1e-4 * scale.x * scale.y {
+        return None;
+    }
+    // Mirroring doesn't matter, since the primitives are symmetric
+    Some((transform.translation, x.y.atan2(x.x), scale))
+}
+
+/// The size of the document and the transform centering it on the origin with y pointing up
+fn document_transform(attributes: &Attributes) -> Result<(Vec2, Affine2), SmudSvgError> {
+    let (min, size) = match attributes.values.get("viewBox") {
+        Some(view_box) => {
+            let numbers = parse_numbers(view_box)
+                .filter(|numbers| numbers.len() == 4)
+                .ok_or_else(|| attributes.error("viewBox", view_box))?;
+            (
+                Vec2::new(numbers[0], numbers[1]),
+                Vec2::new(numbers[2], numbers[3]),
+            )
+        }
+        None => {
+            // Relative sizes depend on the viewport, so they're ignored
+            let length = |name| {
+                attributes
+                    .values
+                    .get(name)
+                    .and_then(|value| parse_length(value))
+                    .unwrap_or(0.)
+            };
+            (Vec2::ZERO, Vec2::new(length("width"), length("height")))
+        }
+    };
+    let transform =
+        Affine2::from_scale(Vec2::new(1., -1.)) * Affine2::from_translation(-(min + size / 2.));
+    Ok((size, transform))
+}
+
+fn transform_command(command: PathCommand, transform: Affine2) -> PathCommand {
+    let t = |p: Vec2| transform.transform_point2(p);
+    match command {
+        PathCommand::MoveTo(to) => PathCommand::MoveTo(t(to)),
+        PathCommand::LineTo(to) => PathCommand::LineTo(t(to)),
+        PathCommand::QuadTo { control, to } => PathCommand::QuadTo {
+            control: t(control),
+            to: t(to),
+        },
+        PathCommand::CubicTo {
+            control1,
+            control2,
+            to,
+        } => PathCommand::CubicTo {
+            control1: t(control1),
+            control2: t(control2),
+            to: t(to),
+        },
+        PathCommand::Close => PathCommand::Close,
+    }
+}
+
+/// Flattens path commands into closed rings of points
+fn flatten(commands: &[PathCommand]) -> Vec<Vec<Vec2>> {
+    let mut rings = Vec::new();
+    let mut ring: Vec<Vec2> = Vec::new();
+    let mut pen = Vec2::ZERO;
+
+    let mut finish = |ring: &mut Vec<Vec2>| {
+        if ring.len() >= 3 {
+            rings.push(std::mem::take(ring));
+        }
+        ring.clear();
+    };
+
+    for command in commands {
+        match *command {
+            PathCommand::MoveTo(to) => {
+                finish(&mut ring);
+                ring.push(to);
+                pen = to;
+            }
+            PathCommand::LineTo(to) => {
+                ring.push(to);
+                pen = to;
+            }
+            PathCommand::QuadTo { control, to } => {
+                ring.extend((1..=CURVE_SUBDIVISIONS).map(|i| {
+                    let t = i as f32 / CURVE_SUBDIVISIONS as f32;
+                    pen.lerp(control, t).lerp(control.lerp(to, t), t)
+                }));
+                pen = to;
+            }
+            PathCommand::CubicTo {
+                control1,
+                control2,
+                to,
+            } => {
+                ring.extend((1..=CURVE_SUBDIVISIONS).map(|i| {
+                    let t = i as f32 / CURVE_SUBDIVISIONS as f32;
+                    let s = 1. - t;
+                    pen * s * s * s
+                        + control1 * 3. * s * s * t
+                        + control2 * 3. * s * t * t
+                        + to * t * t * t
+                }));
+                pen = to;
+            }
+            PathCommand::Close => {
+                // Further commands continue from the start of the closed sub-path
+                pen = ring.first().copied().unwrap_or(pen);
+                finish(&mut ring);
+                ring.push(pen);
+            }
+        }
+    }
+    finish(&mut ring);
+    rings
+}
+
+fn parse_length(value: &str) -> Option<f32> {
+    let value = value.trim();
+    value
+        .strip_suffix("px")
+        .unwrap_or(value)
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Parses a color, `None` for `none`
+fn parse_paint(value: &str) -> Option<Option<Color>> {
+    let value = value.trim();
+    let color = match value {
+        "none" | "transparent" => return Some(None),
+        // `color` isn't supported, so this is the initial value
+        "currentColor" | "black" => Color::BLACK,
+        "white" => Color::WHITE,
+        "red" => Color::rgb_u8(255, 0, 0),
+        "lime" => Color::rgb_u8(0, 255, 0),
+        "green" => Color::rgb_u8(0, 128, 0),
+        "blue" => Color::rgb_u8(0, 0, 255),
+        "yellow" => Color::rgb_u8(255, 255, 0),
+        "cyan" | "aqua" => Color::rgb_u8(0, 255, 255),
+        "magenta" | "fuchsia" => Color::rgb_u8(255, 0, 255),
+        "gray" | "grey" => Color::rgb_u8(128, 128, 128),
+        "silver" => Color::rgb_u8(192, 192, 192),
+        "maroon" => Color::rgb_u8(128, 0, 0),
+        "olive" => Color::rgb_u8(128, 128, 0),
+        "navy" => Color::rgb_u8(0, 0, 128),
+        "purple" => Color::rgb_u8(128, 0, 128),
+        "teal" => Color::rgb_u8(0, 128, 128),
+        "orange" => Color::rgb_u8(255, 165, 0),
+        _ if value.starts_with('#') => Color::hex(value).ok()?,
+        _ if value.starts_with("rgb") => {
+            let args = value
+                .trim_start_matches("rgba")
+                .trim_start_matches("rgb")
+                .trim()
+                .strip_prefix('(')?
+                .strip_suffix(')')?;
+            let mut channels = args.split(',').map(|channel| {
+                let channel = channel.trim();
+                match channel.strip_suffix('%') {
+                    Some(percent) => percent.trim().parse::<f32>().ok().map(|p| p / 100.),
+                    None => channel.parse::<f32>().ok().map(|c| c / 255.),
+                }
+            });
+            let r = channels.next()??;
+            let g = channels.next()??;
+            let b = channels.next()??;
+            // Alpha is given as a fraction, not out of 255
+            let a = channels.next().map_or(Some(1.), |a| a.map(|a| a * 255.))?;
+            Color::rgba(r, g, b, a)
+        }
+        // Gradients and patterns aren't supported
+        _ if value.starts_with("url(") => return Some(None),
+        _ => return None,
+    };
+    Some(Some(color))
+}
+
+/// Parses a list of numbers separated by whitespace and/or commas
+fn parse_numbers(value: &str) -> Option<Vec<f32>> {
+    let mut tokens = Tokens::new(value);
+    let mut numbers = Vec::new();
+    while !tokens.at_end() {
+        numbers.push(tokens.number()?);
+    }
+    Some(numbers)
+}
+
+fn parse_transform(value: &str) -> Option<Affine2> {
+    let mut transform = Affine2::IDENTITY;
+    let mut rest = value.trim();
+    while !rest.is_empty() {
+        let (name, after_name) = rest.split_once('(')?;
+        let (args, after_args) = after_name.split_once(')')?;
+        let args = parse_numbers(args)?;
+        let arg = |i: usize| args.get(i).copied();
+        let name = name.trim().trim_start_matches(',').trim();
+
+        transform *= match (name, args.len()) {
+            ("matrix", 6) => {
+                Affine2::from_cols_array(&[args[0], args[1], args[2], args[3], args[4], args[5]])
+            }
+            ("translate", 1 | 2) => {
+                Affine2::from_translation(Vec2::new(args[0], arg(1).unwrap_or(0.)))
+            }
+            ("scale", 1 | 2) => Affine2::from_scale(Vec2::new(args[0], arg(1).unwrap_or(args[0]))),
+            ("rotate", 1) => Affine2::from_angle(args[0].to_radians()),
+            ("rotate", 3) => {
+                let center = Vec2::new(args[1], args[2]);
+                Affine2::from_translation(center)
+                    * Affine2::from_angle(args[0].to_radians())
+                    * Affine2::from_translation(-center)
+            }
+            ("skewX", 1) => Affine2::from_mat2(Mat2::from_cols(
+                Vec2::X,
+                Vec2::new(args[0].to_radians().tan(), 1.),
+            )),
+            ("skewY", 1) => Affine2::from_mat2(Mat2::from_cols(
+                Vec2::new(1., args[0].to_radians().tan()),
+                Vec2::Y,
+            )),
+            _ => return None,
+        };
+        rest = after_args.trim_start();
+    }
+    Some(transform)
+}
+
+/// Parses path data into absolute commands
+fn parse_path_data(d: &str) -> Option<Vec<PathCommand>> {
+    let mut tokens = Tokens::new(d);
+    let mut commands = Vec::new();
+    let mut pen = Vec2::ZERO;
+    let mut subpath_start = Vec2::ZERO;
+    // The control point of the previous curve, for smooth curves
+    let mut last_control: Option<(char, Vec2)> = None;
+    let mut command = None;
+
+    while !tokens.at_end() {
+        if let Some(c) = tokens.command() {
+            command = Some(c);
+        }
+        // Commands can be repeated by just continuing with numbers
+        let c = command?;
+        let relative = c.is_ascii_lowercase();
+        let origin = if relative { pen } else { Vec2::ZERO };
+        let point = |tokens: &mut Tokens| -> Option<Vec2> {
+            Some(origin + Vec2::new(tokens.number()?, tokens.number()?))
+        };
+
+        let mut control = None;
+        match c.to_ascii_uppercase() {
+            'M' => {
+                pen = point(&mut tokens)?;
+                subpath_start = pen;
+                commands.push(PathCommand::MoveTo(pen));
+                // Following pairs are lines
+                command = Some(if relative { 'l' } else { 'L' });
+            }
+            'L' => {
+                pen = point(&mut tokens)?;
+                commands.push(PathCommand::LineTo(pen));
+            }
+            'H' => {
+                pen.x = origin.x + tokens.number()?;
+                commands.push(PathCommand::LineTo(pen));
+            }
+            'V' => {
+                pen.y = origin.y + tokens.number()?;
+                commands.push(PathCommand::LineTo(pen));
+            }
+            'C' => {
+                let control1 = point(&mut tokens)?;
+                let control2 = point(&mut tokens)?;
+                pen = point(&mut tokens)?;
+                commands.push(PathCommand::CubicTo {
+                    control1,
+                    control2,
+                    to: pen,
+                });
+                control = Some(('C', control2));
+            }
+            'S' => {
+                let control1 = match last_control {
+                    Some(('C', last)) => pen * 2. - last,
+                    _ => pen,
+                };
+                let control2 = point(&mut tokens)?;
+                pen = point(&mut tokens)?;
+                commands.push(PathCommand::CubicTo {
+                    control1,
+                    control2,
+                    to: pen,
+                });
+                control = Some(('C', control2));
+            }
+            'Q' => {
+                let quad_control = point(&mut tokens)?;
+                pen = point(&mut tokens)?;
+                commands.push(PathCommand::QuadTo {
+                    control: quad_control,
+                    to: pen,
+                });
+                control = Some(('Q', quad_control));
+            }
+            'T' => {
+                let quad_control = match last_control {
+                    Some(('Q', last)) => pen * 2. - last,
+                    _ => pen,
+                };
+                pen = point(&mut tokens)?;
+                commands.push(PathCommand::QuadTo {
+                    control: quad_control,
+                    to: pen,
+                });
+                control = Some(('Q', quad_control));
+            }
+            'A' => {
+                let radii = Vec2::new(tokens.number()?.abs(), tokens.number()?.abs());
+                let angle = tokens.number()?.to_radians();
+                let large_arc = tokens.flag()?;
+                let sweep = tokens.flag()?;
+                let to = point(&mut tokens)?;
+                commands.extend(arc_to_cubics(pen, radii, angle, large_arc, sweep, to));
+                pen = to;
+            }
+            'Z' => {
+                commands.push(PathCommand::Close);
+                pen = subpath_start;
+                // Z takes no arguments, so don't repeat it
+                command = None;
+            }
+            _ => return None,
+        }
+        last_control = control;
+    }
+
+    Some(commands)
+}
+
+/// Converts an svg elliptical arc to cubic curves, following the svg implementation notes
+fn arc_to_cubics(
+    from: Vec2,
+    radii: Vec2,
+    angle: f32,
+    large_arc: bool,
+    sweep: bool,
+    to: Vec2,
+) -> Vec<PathCommand> {
+    if from == to {
+        return Vec::new();
+    }
+    if radii.x == 0. || radii.y == 0. {
+        return vec![PathCommand::LineTo(to)];
+    }
+
+    let rotation = Mat2::from_angle(angle);
+    let p = rotation.transpose() * ((from - to) / 2.);
+
+    // Scale up radii that are too small to reach the end point
+    let lambda = (p / radii).length_squared();
+    let radii = if lambda > 1. {
+        radii * lambda.sqrt()
+    } else {
+        radii
+    };
+
+    let (rx2, ry2) = (radii.x * radii.x, radii.y * radii.y);
+    let numerator = rx2 * ry2 - rx2 * p.y * p.y - ry2 * p.x * p.x;
+    let denominator = rx2 * p.y * p.y + ry2 * p.x * p.x;
+    let mut coefficient = (numerator / denominator).max(0.).sqrt();
+    if large_arc == sweep {
+        coefficient = -coefficient;
+    }
+    let center_prime = coefficient * Vec2::new(radii.x * p.y / radii.y, -radii.y * p.x / radii.x);
+    let center = rotation * center_prime + (from + to) / 2.;
+
+    let start_vector = (p - center_prime) / radii;
+    let end_vector = (-p - center_prime) / radii;
+    let start_angle = start_vector.y.atan2(start_vector.x);
+    let mut sweep_angle = start_vector.angle_between(end_vector);
+    if !sweep && sweep_angle > 0. {
+        sweep_angle -= TAU;
+    } else if sweep && sweep_angle < 0. {
+        sweep_angle += TAU;
+    }
+
+    // Quarter circles or less are approximated well by a single cubic
+    let segments = (sweep_angle.abs() / FRAC_PI_2).ceil().max(1.) as usize;
+    let delta = sweep_angle / segments as f32;
+    let k = 4. / 3. * (delta / 4.).tan();
+    let point = |a: f32| center + rotation * (Vec2::from_angle(a) * radii);
+    let tangent = |a: f32| rotation * (Vec2::new(-a.sin(), a.cos()) * radii);
+
+    (0..segments)
+        .map(|i| {
+            let a0 = start_angle + delta * i as f32;
+            let a1 = a0 + delta;
+            PathCommand::CubicTo {
+                control1: point(a0) + tangent(a0) * k,
+                control2: point(a1) - tangent(a1) * k,
+                to: if i == segments - 1 { to } else { point(a1) },
+            }
+        })
+        .collect()
+}
+
+/// Splits svg number lists and path data into tokens
+struct Tokens<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(value: &'a str) -> Self {
+        let mut tokens = Self { rest: value };
+        tokens.skip_separators();
+        tokens
+    }
+
+    fn skip_separators(&mut self) {
+        self.rest = self
+            .rest
+            .trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+
+    fn at_end(&self) -> bool {
+        self.rest.is_empty()
+    }
+
+    fn command(&mut self) -> Option<char> {
+        let c = self.rest.chars().next()?;
+        if !c.is_ascii_alphabetic() || c == 'e' || c == 'E' {
+            return None;
+        }
+        self.rest = &self.rest[1..];
+        self.skip_separators();
+        Some(c)
+    }
+
+    /// Arc flags don't need separators, like in `a1 1 0 00 1 1`
+    fn flag(&mut self) -> Option<bool> {
+        let flag = match self.rest.chars().next()? {
+            '0' => false,
+            '1' => true,
+            _ => return None,
+        };
+        self.rest = &self.rest[1..];
+        self.skip_separators();
+        Some(flag)
+    }
+
+    fn number(&mut self) -> Option<f32> {
+        let bytes = self.rest.as_bytes();
+        let mut end = 0;
+        if matches!(bytes.first(), Some(b'+' | b'-')) {
+            end += 1;
+        }
+        let mut seen_dot = false;
+        while let Some(&b) = bytes.get(end) {
+            match b {
+                b'0'..=b'9' => {}
+                // A second dot starts the next number, like in `1.5.5`
+                b'.' if !seen_dot => seen_dot = true,
+                b'e' | b'E' if end > 0 => {
+                    end += 1;
+                    if matches!(bytes.get(end), Some(b'+' | b'-')) {
+                        end += 1;
+                    }
+                    while bytes.get(end).is_some_and(u8::is_ascii_digit) {
+                        end += 1;
+                    }
+                    break;
+                }
+                _ => break,
+            }
+            end += 1;
+        }
+        let number = self.rest[..end].parse().ok()?;
+        self.rest = &self.rest[end..];
+        self.skip_separators();
+        Some(number)
+    }
+}
@@ -0,0 +1,251 @@
+use std::fmt::Write;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{CsgOp, SmudSdf};
+
+/// Number of samples along the longer side of a shape when tracing its contour
+const CONTOUR_SAMPLES: f32 = 128.;
+
+impl SmudSdf {
+    /// Exports the shape as a standalone svg document, filled with the given color
+    ///
+    /// Primitives with an svg equivalent are exported exactly, as are transforms and unions of
+    /// them. Everything else, like the smooth operators, is traced into a path by sampling the
+    /// sdf, so it's only as accurate as the sampling grid.
+    ///
+    /// The document covers the [bounds](SmudSdf::bounds) of the shape, with y pointing up like in
+    /// bevy. The alpha of the color is applied to the group of all elements, so overlapping
+    /// parts of a union aren't blended twice.
+    pub fn to_svg(&self, color: Color) -> String {
+        let bounds = self.bounds().inset(1.);
+        let [r, g, b, a] = color.as_rgba_u8();
+        format!(
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+                "\n",
+                r##"<g transform="scale(1 -1)" fill="#{:02x}{:02x}{:02x}" opacity="{}">"##,
+                "\n{}</g>\n</svg>\n"
+            ),
+            num(bounds.min.x),
+            num(-bounds.max.y),
+            num(bounds.width()),
+            num(bounds.height()),
+            num(bounds.width()),
+            num(bounds.height()),
+            r,
+            g,
+            b,
+            num(a as f32 / 255.),
+            self.svg_elements(),
+        )
+    }
+
+    /// The svg elements making up the shape, in its local space with y pointing up, and without
+    /// any fill
+    ///
+    /// See [`SmudSdf::to_svg`] for how shapes are exported.
+    pub fn svg_elements(&self) -> String {
+        let exact = match self {
+            Self::Circle { radius } => Some(format!(r#"<circle r="{}"/>"#, num(*radius))),
+            Self::Rect { half_size } => Some(rect_element(*half_size, 0.)),
+            Self::RoundedRect { half_size, radii }
+                if radii.min_element() == radii.max_element() =>
+            {
+                Some(rect_element(*half_size, radii.x))
+            }
+            Self::Ellipse { radii } => Some(format!(
+                r#"<ellipse rx="{}" ry="{}"/>"#,
+                num(radii.x),
+                num(radii.y)
+            )),
+            Self::Triangle { points } => Some(polygon_element(points)),
+            Self::EquilateralTriangle { radius } => {
+                let k = 3f32.sqrt();
+                Some(polygon_element(&[
+                    Vec2::new(-radius, -radius / k),
+                    Vec2::new(*radius, -radius / k),
+                    Vec2::new(0., 2. * radius / k),
+                ]))
+            }
+            Self::Hexagon { radius } => {
+                let corner = 2. * radius / 3f32.sqrt();
+                Some(polygon_element(
+                    &(0..6)
+                        .map(|i| Vec2::from_angle(i as f32 * std::f32::consts::FRAC_PI_3) * corner)
+                        .collect::<Vec<_>>(),
+                ))
+            }
+            Self::Transform {
+                shape,
+                translation,
+                rotation,
+            } => Some(format!(
+                r#"<g transform="translate({} {}) rotate({})">{}</g>"#,
+                num(translation.x),
+                num(translation.y),
+                num(rotation.to_degrees()),
+                shape.svg_elements()
+            )),
+            Self::Scale { shape, scale } => Some(format!(
+                r#"<g transform="scale({})">{}</g>"#,
+                num(*scale),
+                shape.svg_elements()
+            )),
+            // Drawing both shapes in the same color covers their union
+            Self::Csg {
+                op: CsgOp::Union,
+                a,
+                b,
+            } => Some(a.svg_elements() + &b.svg_elements()),
+            _ => None,
+        };
+        exact.unwrap_or_else(|| self.contour_element())
+    }
+
+    /// Traces the contour of the sdf into a path, using marching squares
+    fn contour_element(&self) -> String {
+        self.svg_contour(self.bounds())
+    }
+
+    /// Traces the contour of the sdf within `bounds` into a path element, in the local space of
+    /// the shape
+    ///
+    /// Outlines leaving the bounds are cut off there and left open, so the path is only
+    /// meaningful as a fill.
+    pub fn svg_contour(&self, bounds: Rect) -> String {
+        let step = bounds.size().max_element().max(1e-3) / CONTOUR_SAMPLES;
+        // Sample one step beyond the bounds, so contours of shapes within them are closed
+        let min = bounds.min - step;
+        let cells = ((bounds.size() / step).ceil().as_uvec2() + 2).max(UVec2::ONE);
+
+        let values: Vec<f32> = (0..=cells.y)
+            .flat_map(|y| (0..=cells.x).map(move |x| (x, y)))
+            .map(|(x, y)| self.distance(min + Vec2::new(x as f32, y as f32) * step))
+            .collect();
+        let value = |x: u32, y: u32| values[(y * (cells.x + 1) + x) as usize];
+        let corner = |x: u32, y: u32| min + Vec2::new(x as f32, y as f32) * step;
+
+        // Each crossed edge is connected to exactly two others, one in each adjacent cell
+        let mut neighbors: HashMap<Edge, [Option<Edge>; 2]> = HashMap::default();
+        let mut connect = |a: Edge, b: Edge| {
+            for (from, to) in [(a, b), (b, a)] {
+                let slots = neighbors.entry(from).or_default();
+                let slot = if slots[0].is_none() { 0 } else { 1 };
+                slots[slot] = Some(to);
+            }
+        };
+
+        for y in 0..cells.y {
+            for x in 0..cells.x {
+                let inside = [
+                    value(x, y) < 0.,
+                    value(x + 1, y) < 0.,
+                    value(x + 1, y + 1) < 0.,
+                    value(x, y + 1) < 0.,
+                ];
+                // Bottom, right, top and left edges, each between two corners
+                let edges = [
+                    Edge::Horizontal(x, y),
+                    Edge::Vertical(x + 1, y),
+                    Edge::Horizontal(x, y + 1),
+                    Edge::Vertical(x, y),
+                ];
+                let crossed: Vec<usize> = (0..4)
+                    .filter(|&i| inside[i] != inside[(i + 1) % 4])
+                    .collect();
+                match crossed[..] {
+                    [a, b] => connect(edges[a], edges[b]),
+                    [_, _, _, _] => {
+                        let center = self.distance(corner(x, y) + step / 2.) < 0.;
+                        if center == inside[0] {
+                            // The first and third corners are connected through the center
+                            connect(edges[0], edges[1]);
+                            connect(edges[2], edges[3]);
+                        } else {
+                            connect(edges[3], edges[0]);
+                            connect(edges[1], edges[2]);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // Where the sdf crosses zero along an edge
+        let crossing = |edge: Edge| {
+            let (a, b) = match edge {
+                Edge::Horizontal(x, y) => ((x, y), (x + 1, y)),
+                Edge::Vertical(x, y) => ((x, y), (x, y + 1)),
+            };
+            let (da, db) = (value(a.0, a.1), value(b.0, b.1));
+            let t = (da / (da - db)).clamp(0., 1.);
+            corner(a.0, a.1).lerp(corner(b.0, b.1), t)
+        };
+
+        let mut d = String::new();
+        let mut visited = bevy::utils::HashSet::default();
+        let mut starts: Vec<Edge> = neighbors.keys().copied().collect();
+        // Open chains, cut off at the sampled area, have to be traced from one of their ends.
+        // Sorting also keeps the output stable.
+        starts.sort_by_key(|edge| (neighbors[edge][1].is_some(), *edge));
+        for start in starts {
+            if !visited.insert(start) {
+                continue;
+            }
+            let p = crossing(start);
+            write!(d, "M{} {}", num(p.x), num(p.y)).unwrap();
+            let mut previous = start;
+            let mut current = neighbors[&start][0];
+            while let Some(edge) = current.filter(|edge| visited.insert(*edge)) {
+                let p = crossing(edge);
+                write!(d, "L{} {}", num(p.x), num(p.y)).unwrap();
+                let [a, b] = neighbors[&edge];
+                current = if a == Some(previous) { b } else { a };
+                previous = edge;
+            }
+            // Closed chains end where they started
+            if current.is_some() {
+                d.push('Z');
+            }
+        }
+
+        format!(r#"<path fill-rule="evenodd" d="{d}"/>"#)
+    }
+}
+
+/// An edge of the sampling grid, by its lower left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Edge {
+    Horizontal(u32, u32),
+    Vertical(u32, u32),
+}
+
+fn rect_element(half_size: Vec2, radius: f32) -> String {
+    let rounding = if radius > 0. {
+        format!(r#" rx="{}""#, num(radius))
+    } else {
+        String::new()
+    };
+    format!(
+        r#"<rect x="{}" y="{}" width="{}" height="{}"{rounding}/>"#,
+        num(-half_size.x),
+        num(-half_size.y),
+        num(half_size.x * 2.),
+        num(half_size.y * 2.)
+    )
+}
+
+fn polygon_element(points: &[Vec2]) -> String {
+    let points: Vec<String> = points
+        .iter()
+        .map(|p| format!("{},{}", num(p.x), num(p.y)))
+        .collect();
+    format!(r#"<polygon points="{}"/>"#, points.join(" "))
+}
+
+/// Formats a number with at most three decimals
+fn num(x: f32) -> String {
+    // Adding zero turns -0 into 0
+    format!("{}", (x * 1000.).round() / 1000. + 0.)
+}
@@ -0,0 +1,427 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+        Extract,
+    },
+    utils::{BoxedFuture, HashMap},
+};
+use thiserror::Error;
+
+use crate::{
+    msdf::{Outline, MSDF_RANGE},
+    shader_loading::TEXT_SDF_HANDLE,
+    time::{instance_time, SmudTime},
+    ExtractedShape, ExtractedShapes, SIMPLE_FILL_HANDLE,
+};
+
+/// Characters included in the glyph atlas of a [`SmudFont`]
+const ATLAS_CHARACTERS: std::ops::RangeInclusive<char> = ' '..='~';
+
+/// Resolution of the glyph atlas, in pixels per em
+const ATLAS_PIXELS_PER_EM: f32 = 32.;
+
+/// Maximum width of the glyph atlas texture
+const ATLAS_WIDTH: usize = 512;
+
+/// Empty pixels between glyphs in the atlas, to avoid bleeding when sampling
+const ATLAS_GUTTER: usize = 1;
+
+/// A font with a pre-generated multi-channel sdf glyph atlas, used by [`SmudText`]
+///
+/// Loaded from `.ttf` and `.otf` files. The atlas is generated on the cpu at load time, and
+/// contains the printable ascii characters.
+#[derive(Asset, TypePath, Debug)]
+pub struct SmudFont {
+    /// The msdf atlas texture, with one square cell per glyph
+    pub atlas: Handle<Image>,
+    glyphs: HashMap<char, SmudGlyph>,
+    /// Distance between baselines, in em
+    line_height: f32,
+    /// Height of the tallest glyphs above the baseline, in em
+    ascender: f32,
+}
+
+/// Metrics and atlas location of a single glyph, all distances are in em
+#[derive(Debug, Clone, Copy)]
+struct SmudGlyph {
+    advance: f32,
+    /// Center of the atlas cell relative to the pen position on the baseline
+    center: Vec2,
+    /// Half-size of the (square) atlas cell
+    half_size: f32,
+    /// Atlas rect as (min_u, min_v, max_u, max_v)
+    uv: Vec4,
+}
+
+impl SmudFont {
+    /// Computes the glyph quads for a line of text, relative to the text origin
+    ///
+    /// Returns the center, half-size and atlas rect of each visible glyph, in em.
+    fn layout(&self, text: &str, alignment: SmudTextAlignment) -> Vec<(Vec2, f32, Vec4)> {
+        let mut quads = Vec::new();
+        let lines: Vec<&str> = text.lines().collect();
+        let total_height = self.line_height * lines.len() as f32;
+        // Vertically center the block of text on the origin
+        let mut baseline = total_height / 2. - self.ascender;
+
+        for line in lines {
+            let width: f32 = line
+                .chars()
+                .filter_map(|c| self.glyphs.get(&c))
+                .map(|g| g.advance)
+                .sum();
+
+            let mut pen = match alignment {
+                SmudTextAlignment::Left => 0.,
+                SmudTextAlignment::Center => -width / 2.,
+                SmudTextAlignment::Right => -width,
+            };
+
+            for c in line.chars() {
+                let Some(glyph) = self.glyphs.get(&c) else {
+                    continue;
+                };
+                if glyph.half_size > 0. {
+                    quads.push((
+                        Vec2::new(pen, baseline) + glyph.center,
+                        glyph.half_size,
+                        glyph.uv,
+                    ));
+                }
+                pen += glyph.advance;
+            }
+
+            baseline -= self.line_height;
+        }
+
+        quads
+    }
+}
+
+/// Possible errors produced by [`SmudFontLoader`]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SmudFontLoaderError {
+    /// An [IO](std::io) Error
+    #[error("Could not read font: {0}")]
+    Io(#[from] std::io::Error),
+    /// The font file could not be parsed
+    #[error("Could not parse font: {0}")]
+    Parse(#[from] ttf_parser::FaceParsingError),
+}
+
+/// Loads [`SmudFont`]s and generates their glyph atlas
+#[derive(Default)]
+pub struct SmudFontLoader;
+
+impl AssetLoader for SmudFontLoader {
+    type Asset = SmudFont;
+    type Settings = ();
+    type Error = SmudFontLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<SmudFont, SmudFontLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let face = ttf_parser::Face::parse(&bytes, 0)?;
+            let (font, atlas) = build_font(&face);
+            let atlas = load_context.add_labeled_asset("atlas".to_string(), atlas);
+            Ok(SmudFont { atlas, ..font })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ttf", "otf"]
+    }
+}
+
+struct RasterizedGlyph {
+    c: char,
+    size: usize,
+    data: Vec<u8>,
+    glyph: SmudGlyph,
+}
+
+impl ttf_parser::OutlineBuilder for Outline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        Outline::move_to(self, Vec2::new(x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        Outline::line_to(self, Vec2::new(x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        Outline::quad_to(self, Vec2::new(x1, y1), Vec2::new(x, y));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        Outline::cubic_to(self, Vec2::new(x1, y1), Vec2::new(x2, y2), Vec2::new(x, y));
+    }
+
+    fn close(&mut self) {
+        Outline::close(self);
+    }
+}
+
+fn build_font(face: &ttf_parser::Face) -> (SmudFont, Image) {
+    let units_per_em = face.units_per_em() as f32;
+    let units_per_pixel = units_per_em / ATLAS_PIXELS_PER_EM;
+    let padding = MSDF_RANGE.ceil() as usize;
+
+    let mut rasterized: Vec<RasterizedGlyph> = ATLAS_CHARACTERS
+        .filter_map(|c| {
+            let id = face.glyph_index(c)?;
+            let advance = face.glyph_hor_advance(id).unwrap_or(0) as f32 / units_per_em;
+            let mut outline = Outline::default();
+            let Some(bounds) = face.outline_glyph(id, &mut outline) else {
+                // Glyphs without outlines (i.e. space) only advance the pen
+                return Some(RasterizedGlyph {
+                    c,
+                    size: 0,
+                    data: Vec::new(),
+                    glyph: SmudGlyph {
+                        advance,
+                        center: Vec2::ZERO,
+                        half_size: 0.,
+                        uv: Vec4::ZERO,
+                    },
+                });
+            };
+
+            let min = Vec2::new(bounds.x_min as f32, bounds.y_min as f32);
+            let max = Vec2::new(bounds.x_max as f32, bounds.y_max as f32);
+            let center = (min + max) / 2.;
+            let extent = (max - min).max_element() / units_per_pixel;
+            let size = extent.ceil() as usize + 2 * padding;
+            let half_size_units = size as f32 * units_per_pixel / 2.;
+            let origin = center + Vec2::new(-half_size_units, half_size_units);
+
+            Some(RasterizedGlyph {
+                c,
+                size,
+                data: outline.generate_msdf(size, size, origin, units_per_pixel),
+                glyph: SmudGlyph {
+                    advance,
+                    center: center / units_per_em,
+                    half_size: half_size_units / units_per_em,
+                    uv: Vec4::ZERO,
+                },
+            })
+        })
+        .collect();
+
+    // Simple shelf packing, tallest glyphs first
+    rasterized.sort_by_key(|g| std::cmp::Reverse(g.size));
+    let mut positions = Vec::with_capacity(rasterized.len());
+    let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+    for glyph in &rasterized {
+        if x + glyph.size > ATLAS_WIDTH {
+            x = 0;
+            y += shelf_height + ATLAS_GUTTER;
+            shelf_height = 0;
+        }
+        positions.push((x, y));
+        x += glyph.size + ATLAS_GUTTER;
+        shelf_height = shelf_height.max(glyph.size);
+    }
+    let height = (y + shelf_height).max(1);
+
+    let mut data = vec![0; ATLAS_WIDTH * height * 4];
+    let mut glyphs = HashMap::default();
+
+    for (rasterized, (x, y)) in rasterized.into_iter().zip(positions) {
+        let RasterizedGlyph {
+            c,
+            size,
+            data: glyph_data,
+            mut glyph,
+        } = rasterized;
+
+        for row in 0..size {
+            let src = &glyph_data[row * size * 4..(row + 1) * size * 4];
+            let start = ((y + row) * ATLAS_WIDTH + x) * 4;
+            data[start..start + size * 4].copy_from_slice(src);
+        }
+
+        if size > 0 {
+            glyph.uv = Vec4::new(
+                x as f32 / ATLAS_WIDTH as f32,
+                y as f32 / height as f32,
+                (x + size) as f32 / ATLAS_WIDTH as f32,
+                (y + size) as f32 / height as f32,
+            );
+        }
+
+        glyphs.insert(c, glyph);
+    }
+
+    let mut atlas = Image::new(
+        Extent3d {
+            width: ATLAS_WIDTH as u32,
+            height: height as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        // The distances are linear data, so don't use an srgb format
+        TextureFormat::Rgba8Unorm,
+    );
+    atlas.sampler = ImageSampler::linear();
+
+    let line_height = (face.ascender() - face.descender() + face.line_gap()) as f32 / units_per_em;
+    let ascender = face.ascender() as f32 / units_per_em;
+
+    let font = SmudFont {
+        atlas: default(),
+        glyphs,
+        line_height,
+        ascender,
+    };
+
+    (font, atlas)
+}
+
+/// Horizontal alignment of the lines of a [`SmudText`] relative to its transform
+#[derive(Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SmudTextAlignment {
+    /// Lines start at the origin
+    Left,
+    /// Lines are centered on the origin
+    #[default]
+    Center,
+    /// Lines end at the origin
+    Right,
+}
+
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+/// Text rendered with multi-channel signed distance fields
+///
+/// Each glyph is drawn as an instance in the same pipeline as [`SmudShape`](crate::SmudShape)s,
+/// so text can use the same fills as shapes, including outlines and glows.
+pub struct SmudText {
+    /// The text to render, may contain several lines
+    pub text: String,
+    /// The font to use
+    pub font: Handle<SmudFont>,
+    /// The height of an em, in world units
+    pub font_size: f32,
+    /// The color used by the fill shader
+    pub color: Color,
+    /// Shader containing a wgsl function for the fill of the glyphs
+    ///
+    /// The shader needs to have the signature `fn fill(distance: f32, color: vec4<f32>) -> vec4<f32>`.
+    pub fill: Handle<Shader>,
+    /// Horizontal alignment of the lines
+    pub alignment: SmudTextAlignment,
+}
+
+impl Default for SmudText {
+    fn default() -> Self {
+        Self {
+            text: default(),
+            font: default(),
+            font_size: 32.,
+            color: Color::WHITE,
+            fill: SIMPLE_FILL_HANDLE,
+            alignment: default(),
+        }
+    }
+}
+
+#[derive(Bundle, Default, Clone, Debug)]
+/// Bundle with all the components needed for drawing sdf text in 2d world space
+pub struct SmudTextBundle {
+    /// The text, font and fill
+    pub text: SmudText,
+    /// A transform, the text is centered vertically around its origin
+    pub transform: Transform,
+    /// A compute transform
+    pub global_transform: GlobalTransform,
+    /// User indication of whether an entity is visible
+    pub visibility: Visibility,
+    /// The inherited visibility of the entity.
+    pub inherited_visibility: InheritedVisibility,
+    /// The view visibility of the entity.
+    pub view_visibility: ViewVisibility,
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn extract_texts(
+    mut commands: Commands,
+    mut extracted_shapes: ResMut<ExtractedShapes>,
+    fonts: Extract<Res<Assets<SmudFont>>>,
+    text_query: Extract<
+        Query<(
+            Entity,
+            &ViewVisibility,
+            &SmudText,
+            &GlobalTransform,
+            Option<&SmudTime>,
+        )>,
+    >,
+    changed_query: Extract<
+        Query<
+            (),
+            Or<(
+                Changed<SmudText>,
+                Changed<GlobalTransform>,
+                Changed<SmudTime>,
+            )>,
+        >,
+    >,
+) {
+    // Reloading a font changes the layout of its glyphs
+    let fonts_changed = fonts.is_changed();
+
+    for (entity, view_visibility, text, transform, text_time) in text_query.iter() {
+        if !view_visibility.get() {
+            continue;
+        }
+
+        let Some(font) = fonts.get(&text.font) else {
+            continue;
+        };
+
+        let changed = fonts_changed || changed_query.contains(entity);
+
+        for (index, (center, half_size, uv)) in font
+            .layout(&text.text, text.alignment)
+            .into_iter()
+            .enumerate()
+        {
+            let offset = (center * text.font_size).extend(0.);
+            let glyph_transform = *transform * GlobalTransform::from_translation(offset);
+
+            // Each glyph gets its own render world entity, so it can be sorted and batched
+            extracted_shapes.shapes.insert(
+                commands.spawn_empty().id(),
+                ExtractedShape {
+                    color: text.color,
+                    frame: half_size * text.font_size,
+                    params: uv,
+                    dash: Vec4::ZERO,
+                    sdf_shader: TEXT_SDF_HANDLE,
+                    fill_shader: text.fill.clone_weak(),
+                    transform: glyph_transform,
+                    time: instance_time(text_time),
+                    atlas: Some(font.atlas.id()),
+                    three_d: None,
+                    changed,
+                    key: (entity, index as u32),
+                    anchor: Vec2::ZERO,
+                    screen_space: false,
+                },
+            );
+        }
+    }
+}
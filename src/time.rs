@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+/// Per-instance clock for animated sdfs and fills
+///
+/// Shaders read the time of the shape they're drawing through `smud::time()`. Shapes without
+/// this component all use the elapsed time of the game clock, and animate in lockstep. With it,
+/// each shape keeps its own time, which starts at zero when the component is added and advances
+/// with the game clock, so pausing [`Time<Virtual>`] pauses all shapes.
+pub struct SmudTime {
+    /// Added to the time of the shape, use different offsets to desynchronize shapes
+    pub offset: f32,
+    /// How fast the time of the shape advances compared to the game clock
+    pub scale: f32,
+    /// Stops the time of the shape from advancing
+    pub paused: bool,
+    /// Scaled time accumulated while not paused
+    pub elapsed: f32,
+}
+
+impl Default for SmudTime {
+    fn default() -> Self {
+        Self {
+            offset: 0.,
+            scale: 1.,
+            paused: false,
+            elapsed: 0.,
+        }
+    }
+}
+
+impl SmudTime {
+    /// Creates a clock starting at the given offset
+    pub fn from_offset(offset: f32) -> Self {
+        Self {
+            offset,
+            ..default()
+        }
+    }
+
+    /// Sets how fast the clock advances
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// The time passed to the shaders of the shape
+    pub fn time(&self) -> f32 {
+        self.elapsed + self.offset
+    }
+}
+
+/// The time passed to the shaders of a shape, `None` for shapes following `globals.time`
+///
+/// Reading the global time on the gpu keeps the instance data of shapes without a clock the same
+/// between frames, so it doesn't need to be uploaded again.
+pub(crate) fn instance_time(shape_time: Option<&SmudTime>) -> Option<f32> {
+    shape_time.map(SmudTime::time)
+}
+
+/// The time in the instance data, as (own time, weight of `globals.time`)
+pub(crate) fn vertex_time(time: Option<f32>) -> [f32; 2] {
+    match time {
+        Some(time) => [time, 0.],
+        None => [0., 1.],
+    }
+}
+
+pub(crate) fn advance_shape_times(time: Res<Time>, mut shape_times: Query<&mut SmudTime>) {
+    for mut shape_time in &mut shape_times {
+        if !shape_time.paused {
+            shape_time.elapsed += time.delta_seconds() * shape_time.scale;
+        }
+    }
+}
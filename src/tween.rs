@@ -0,0 +1,208 @@
+use bevy::prelude::*;
+
+use crate::{Frame, SmudMorph, SmudParams, SmudShape};
+
+/// How a [`SmudTween`] continues once it reaches its end
+#[derive(Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TweenRepeat {
+    /// Stop at the end values
+    #[default]
+    Once,
+    /// Jump back to the start values and play again
+    Loop,
+    /// Play backwards to the start values, then forwards again
+    PingPong,
+}
+
+/// The rate of change over the duration of a [`SmudTween`]
+#[derive(Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TweenEasing {
+    /// Constant rate of change
+    #[default]
+    Linear,
+    /// Starts and ends slowly
+    EaseInOut,
+}
+
+impl TweenEasing {
+    fn ease(self, t: f32) -> f32 {
+        match self {
+            TweenEasing::Linear => t,
+            TweenEasing::EaseInOut => t * t * (3. - 2. * t),
+        }
+    }
+}
+
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+/// A lightweight animation of the [`SmudShape`] on the same entity
+///
+/// Only the fields that have start and end values are animated. Colors are interpolated in
+/// linear space, so the transitions don't look muddy. The morph factor and params are applied to
+/// the [`SmudMorph`] and [`SmudParams`] on the same entity, if there are any.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_smud::{Frame, SmudTween, TweenRepeat};
+/// let tween = SmudTween::new(2.)
+///     .with_color(Color::RED, Color::BLUE)
+///     .with_frame(Frame::Quad(50.), Frame::Quad(100.))
+///     .with_repeat(TweenRepeat::PingPong);
+/// ```
+pub struct SmudTween {
+    /// The start and end color
+    pub color: Option<(Color, Color)>,
+    /// The start and end frame
+    pub frame: Option<(Frame, Frame)>,
+    /// The start and end value of [`SmudMorph::t`]
+    pub morph: Option<(f32, f32)>,
+    /// The start and end value of [`SmudParams`]
+    pub params: Option<(Vec4, Vec4)>,
+    /// Duration of one play-through in seconds
+    pub duration: f32,
+    /// Seconds since the start of the current play-through
+    pub elapsed: f32,
+    /// What happens after reaching the end
+    pub repeat: TweenRepeat,
+    /// The rate of change
+    pub easing: TweenEasing,
+    /// Whether the current play-through is going backwards, used by [`TweenRepeat::PingPong`]
+    pub reversed: bool,
+}
+
+impl Default for SmudTween {
+    fn default() -> Self {
+        Self {
+            color: None,
+            frame: None,
+            morph: None,
+            params: None,
+            duration: 1.,
+            elapsed: 0.,
+            repeat: default(),
+            easing: default(),
+            reversed: false,
+        }
+    }
+}
+
+impl SmudTween {
+    /// Creates a tween with the given duration in seconds, not animating anything yet
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            ..default()
+        }
+    }
+
+    /// Animates the color of the shape
+    pub fn with_color(mut self, from: Color, to: Color) -> Self {
+        self.color = Some((from, to));
+        self
+    }
+
+    /// Animates the frame of the shape
+    pub fn with_frame(mut self, from: Frame, to: Frame) -> Self {
+        self.frame = Some((from, to));
+        self
+    }
+
+    /// Animates the blend factor of the [`SmudMorph`] of the shape
+    pub fn with_morph(mut self, from: f32, to: f32) -> Self {
+        self.morph = Some((from, to));
+        self
+    }
+
+    /// Animates the [`SmudParams`] of the shape
+    pub fn with_params(mut self, from: Vec4, to: Vec4) -> Self {
+        self.params = Some((from, to));
+        self
+    }
+
+    /// Sets what happens after reaching the end
+    pub fn with_repeat(mut self, repeat: TweenRepeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Sets the rate of change
+    pub fn with_easing(mut self, easing: TweenEasing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Whether a [`TweenRepeat::Once`] tween has reached its end
+    pub fn finished(&self) -> bool {
+        self.repeat == TweenRepeat::Once && self.elapsed >= self.duration
+    }
+
+    /// The eased progress from the start to the end values, between 0 and 1
+    pub fn progress(&self) -> f32 {
+        let t = if self.duration > 0. {
+            (self.elapsed / self.duration).clamp(0., 1.)
+        } else {
+            1.
+        };
+        let t = if self.reversed { 1. - t } else { t };
+        self.easing.ease(t)
+    }
+
+    fn tick(&mut self, delta: f32) {
+        self.elapsed += delta;
+        if self.elapsed < self.duration || self.duration <= 0. {
+            return;
+        }
+        match self.repeat {
+            TweenRepeat::Once => self.elapsed = self.duration,
+            TweenRepeat::Loop => self.elapsed %= self.duration,
+            TweenRepeat::PingPong => {
+                let play_throughs = (self.elapsed / self.duration) as u32;
+                self.elapsed %= self.duration;
+                self.reversed ^= play_throughs % 2 == 1;
+            }
+        }
+    }
+}
+
+/// Interpolates between two colors in linear space
+fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    let from = Vec4::from(from.as_linear_rgba_f32());
+    let to = Vec4::from(to.as_linear_rgba_f32());
+    let [r, g, b, a] = from.lerp(to, t).to_array();
+    Color::rgba_linear(r, g, b, a)
+}
+
+pub(crate) fn animate_tweens(
+    time: Res<Time>,
+    mut tweens: Query<(
+        &mut SmudTween,
+        &mut SmudShape,
+        Option<&mut SmudMorph>,
+        Option<&mut SmudParams>,
+    )>,
+) {
+    for (mut tween, mut shape, morph, params) in &mut tweens {
+        if tween.finished() {
+            continue;
+        }
+
+        tween.tick(time.delta_seconds());
+        let t = tween.progress();
+
+        if let Some((from, to)) = tween.color {
+            shape.color = lerp_color(from, to, t);
+        }
+
+        if let Some((Frame::Quad(from), Frame::Quad(to))) = tween.frame {
+            shape.frame = Frame::Quad(from + (to - from) * t);
+        }
+
+        if let (Some((from, to)), Some(mut morph)) = (tween.morph, morph) {
+            morph.t = from + (to - from) * t;
+        }
+
+        if let (Some((from, to)), Some(mut params)) = (tween.params, params) {
+            params.0 = from.lerp(to, t);
+        }
+    }
+}
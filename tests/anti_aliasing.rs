@@ -0,0 +1,97 @@
+//! The anti-aliasing modes, on the cpu and in the shaders.
+
+use std::{collections::HashMap, path::Path};
+
+use bevy::prelude::*;
+use bevy_smud::{sdf, *};
+use naga_oil::compose::{
+    ComposableModuleDescriptor, Composer, NagaModuleDescriptor, ShaderDefValue,
+};
+
+#[test]
+fn rasterizer_follows_anti_aliasing_mode() {
+    let circle = Handle::weak_from_u128(1);
+    let shape = SmudShape {
+        sdf: circle.clone(),
+        fill: SIMPLE_FILL_HANDLE,
+        frame: Frame::Quad(30.),
+        ..default()
+    };
+    // Rotated, so the edges of the circle aren't aligned with the pixels
+    let transform = GlobalTransform::from(Transform::from_rotation(Quat::from_rotation_z(0.3)));
+    let alphas = |anti_aliasing| {
+        let mut rasterizer = SmudRasterizer::new(64, 64);
+        rasterizer.register_sdf(&circle, |p| sdf::sd_circle(p, 25.));
+        rasterizer.anti_aliasing = anti_aliasing;
+        let image = rasterizer.render([(&shape, &transform)]).unwrap();
+        let mut alphas: Vec<u8> = image.data.iter().skip(3).step_by(4).copied().collect();
+        alphas.sort();
+        alphas.dedup();
+        alphas
+    };
+
+    for anti_aliasing in [SmudAntiAliasing::Nearest, SmudAntiAliasing::None] {
+        assert_eq!(alphas(anti_aliasing), [0, 255], "{anti_aliasing:?}");
+    }
+    // Blends four hard samples
+    assert_eq!(
+        alphas(SmudAntiAliasing::Supersampled),
+        [0, 63, 127, 191, 255]
+    );
+    for anti_aliasing in [SmudAntiAliasing::Fwidth, SmudAntiAliasing::Dpd] {
+        assert!(alphas(anti_aliasing).len() > 5, "{anti_aliasing:?}");
+    }
+}
+
+/// Catches mistakes in the branches of `smud::sd_fill_alpha` that are disabled by default
+#[test]
+fn fills_compose_with_all_anti_aliasing_modes() {
+    let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+    for def in [
+        None,
+        Some("SMUD_AA_DPD"),
+        Some("SMUD_AA_NEAREST"),
+        Some("SMUD_AA_NONE"),
+        Some("SMUD_AA_SUPERSAMPLED"),
+        Some("SMUD_PIXEL_ART"),
+    ] {
+        let shader_defs: HashMap<_, _> = def
+            .map(|def| (def.to_string(), ShaderDefValue::Bool(true)))
+            .into_iter()
+            .collect();
+        let mut composer = Composer::default();
+        for file in [
+            "instance.wgsl",
+            "smud.wgsl",
+            "fills/simple.wgsl",
+            "fills/outline.wgsl",
+            "fills/dashed.wgsl",
+        ] {
+            let source = std::fs::read_to_string(assets.join(file)).unwrap();
+            if let Err(e) = composer.add_composable_module(ComposableModuleDescriptor {
+                source: &source,
+                file_path: file,
+                shader_defs: shader_defs.clone(),
+                ..default()
+            }) {
+                panic!("{def:?}: {}", e.emit_to_string(&composer));
+            }
+        }
+
+        let entry_point = "#import smud::simple_fill as simple\n\
+            #import smud::outline as outline\n\
+            #import smud::dashed_fill as dashed\n\
+            @fragment\n\
+            fn fragment(@location(0) d: f32) -> @location(0) vec4<f32> {\n\
+                return simple::fill(d, vec4<f32>(1.)) + outline::fill(d, vec4<f32>(1.)) + dashed::fill(d, vec4<f32>(1.));\n\
+            }\n";
+        if let Err(e) = composer.make_naga_module(NagaModuleDescriptor {
+            source: entry_point,
+            file_path: "entry_point.wgsl",
+            shader_defs,
+            ..default()
+        }) {
+            panic!("{def:?}: {}", e.emit_to_string(&composer));
+        }
+    }
+}
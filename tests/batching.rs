@@ -0,0 +1,149 @@
+//! Batching of shapes in the render world, rendered headless.
+//!
+//! Skipped when there's no gpu adapter, a software one like llvmpipe is enough.
+
+use std::{
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    core_pipeline::core_3d::Transparent3d,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_phase::{PhaseItem, RenderPhase},
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+        Render, RenderApp, RenderSet,
+    },
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
+use bevy_smud::{prelude::*, SmudInstanceUpload, SmudSettings};
+
+/// Batch ranges of the transparent 3d phase items, recorded before the render world is cleared
+#[derive(Resource, Clone, Default)]
+struct Batches(Arc<Mutex<Vec<Range<u32>>>>);
+
+fn record_batches(phases: Query<&RenderPhase<Transparent3d>>, batches: Res<Batches>) {
+    let mut batches = batches.0.lock().unwrap();
+    batches.clear();
+    for phase in &phases {
+        batches.extend(
+            phase
+                .items
+                .iter()
+                .map(|item| item.batch_range().clone())
+                .filter(|range| !range.is_empty()),
+        );
+    }
+}
+
+fn has_gpu() -> bool {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+    bevy::tasks::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+        .is_some()
+}
+
+#[test]
+fn billboards_and_planes_are_batched_separately() {
+    if !has_gpu() {
+        eprintln!("no gpu adapter, skipping");
+        return;
+    }
+    assert_batched_separately(SmudInstanceUpload::Retained);
+}
+
+#[test]
+fn rebuilt_instances_are_batched_the_same() {
+    if !has_gpu() {
+        eprintln!("no gpu adapter, skipping");
+        return;
+    }
+    assert_batched_separately(SmudInstanceUpload::Rebuild);
+}
+
+fn assert_batched_separately(instance_upload: SmudInstanceUpload) {
+    let batches = Batches::default();
+    let mut app = App::new();
+    app.add_plugins((
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            })
+            .disable::<WinitPlugin>(),
+        SmudPlugin,
+    ))
+    .insert_resource(SmudSettings {
+        instance_upload,
+        ..default()
+    });
+    // Normally done by `App::run`, creates the render pipelines
+    app.finish();
+    app.cleanup();
+    app.sub_app_mut(RenderApp)
+        .insert_resource(batches.clone())
+        .add_systems(
+            Render,
+            record_batches
+                .after(RenderSet::Render)
+                .before(RenderSet::Cleanup),
+        );
+
+    let world = &mut app.world;
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: 64,
+            height: 64,
+            ..default()
+        },
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    image.texture_descriptor.usage |= TextureUsages::RENDER_ATTACHMENT;
+    let image = world.resource_mut::<Assets<Image>>().add(image);
+    world.spawn(Camera3dBundle {
+        camera: Camera {
+            target: RenderTarget::Image(image),
+            ..default()
+        },
+        transform: Transform::from_xyz(0., 0., 10.),
+        ..default()
+    });
+
+    // The same shaders, so only the mode tells the shapes apart
+    let sdf = world
+        .resource_mut::<Assets<Shader>>()
+        .add_sdf_expr("smud::sd_circle(p, 40.)");
+    for (mode, z) in [(SmudShape3d::Billboard, 0.), (SmudShape3d::Plane, 1.)] {
+        world.spawn((
+            ShapeBundle {
+                shape: SmudShape {
+                    color: Color::WHITE,
+                    sdf: sdf.clone(),
+                    frame: Frame::Quad(50.),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0., 0., z).with_scale(Vec3::splat(0.01)),
+                ..default()
+            },
+            mode,
+        ));
+    }
+
+    // Shaders load in the background, so wait for both shapes to be drawn
+    for _ in 0..500 {
+        app.update();
+        let batches = batches.0.lock().unwrap();
+        if batches.iter().map(|range| range.len()).sum::<usize>() == 2 {
+            assert_eq!(batches.len(), 2, "{batches:?}");
+            return;
+        }
+        drop(batches);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    panic!("the shapes were never drawn");
+}
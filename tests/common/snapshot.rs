@@ -0,0 +1,181 @@
+//! Golden-image testing of shapes rendered with [`SmudRasterizer`](bevy_smud::SmudRasterizer)
+
+use std::path::{Path, PathBuf};
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::{CompressedImageFormats, ImageSampler, ImageType, TextureError},
+    },
+};
+use thiserror::Error;
+
+/// Set this environment variable to write new reference images instead of comparing
+pub const UPDATE_SNAPSHOTS_VAR: &str = "SMUD_UPDATE_SNAPSHOTS";
+
+/// Possible errors produced by [`check_snapshot`]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    /// An [IO](std::io) Error
+    #[error("Could not access snapshot: {0}")]
+    Io(#[from] std::io::Error),
+    /// There is no reference image yet
+    #[error("Missing snapshot {0}, run with {UPDATE_SNAPSHOTS_VAR}=1 to create it")]
+    Missing(PathBuf),
+    /// The reference image couldn't be decoded
+    #[error("Could not decode snapshot: {0}")]
+    Decode(#[from] TextureError),
+    /// The image couldn't be encoded as png
+    #[error("Could not encode png: {0}")]
+    Encode(String),
+    /// The reference image has a different size
+    #[error("Snapshot is {expected}, but the image is {actual}")]
+    SizeMismatch {
+        /// Size of the reference image
+        expected: UVec2,
+        /// Size of the rendered image
+        actual: UVec2,
+    },
+    /// Pixels differ by more than the tolerance
+    #[error(
+        "{differing_pixels} pixels differ by more than the tolerance, by up to {max_difference}. \
+        See {}",
+        diff_path.display()
+    )]
+    Mismatch {
+        /// Number of pixels that differ by more than the tolerance
+        differing_pixels: usize,
+        /// Largest difference of any channel
+        max_difference: u8,
+        /// Where the diff image was written
+        diff_path: PathBuf,
+    },
+}
+
+/// The pixels of two images that differ by more than a tolerance
+#[derive(Debug)]
+pub struct ImageDiff {
+    /// Number of pixels that differ by more than the tolerance
+    pub differing_pixels: usize,
+    /// Largest difference of any channel
+    pub max_difference: u8,
+    /// The expected image faded out, with differing pixels in red
+    pub image: Image,
+}
+
+/// Compares two rgba8 images of the same size
+///
+/// Returns `None` if no channel differs by more than `tolerance`.
+pub fn diff_images(actual: &Image, expected: &Image, tolerance: u8) -> Option<ImageDiff> {
+    assert_eq!(image_size(actual), image_size(expected));
+
+    let mut differing_pixels = 0;
+    let mut max_difference = 0;
+    let mut data = Vec::with_capacity(expected.data.len());
+
+    for (a, e) in actual
+        .data
+        .chunks_exact(4)
+        .zip(expected.data.chunks_exact(4))
+    {
+        let difference = a.iter().zip(e).map(|(a, e)| a.abs_diff(*e)).max().unwrap();
+        max_difference = max_difference.max(difference);
+        if difference > tolerance {
+            differing_pixels += 1;
+            data.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let faded = |c: u8| 192 + c / 4;
+            data.extend_from_slice(&[faded(e[0]), faded(e[1]), faded(e[2]), 255]);
+        }
+    }
+
+    (differing_pixels > 0).then(|| ImageDiff {
+        differing_pixels,
+        max_difference,
+        image: rgba8_image(image_size(expected), data),
+    })
+}
+
+/// Compares an image, i.e. rendered by [`SmudRasterizer`](bevy_smud::SmudRasterizer), against a
+/// reference png
+///
+/// On failure, the image and a diff image are written next to the reference, as
+/// `<name>.actual.png` and `<name>.diff.png`. Set the `SMUD_UPDATE_SNAPSHOTS` environment
+/// variable to write the image as the new reference instead.
+pub fn check_snapshot(
+    image: &Image,
+    path: impl AsRef<Path>,
+    tolerance: u8,
+) -> Result<(), SnapshotError> {
+    let path = path.as_ref();
+
+    if std::env::var_os(UPDATE_SNAPSHOTS_VAR).is_some() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        return save_png(image, path);
+    }
+
+    if !path.exists() {
+        return Err(SnapshotError::Missing(path.to_owned()));
+    }
+    let expected = Image::from_buffer(
+        &std::fs::read(path)?,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+        ImageSampler::Default,
+    )?;
+
+    if image_size(&expected) != image_size(image) {
+        return Err(SnapshotError::SizeMismatch {
+            expected: image_size(&expected),
+            actual: image_size(image),
+        });
+    }
+
+    let Some(diff) = diff_images(image, &expected, tolerance) else {
+        return Ok(());
+    };
+
+    let diff_path = path.with_extension("diff.png");
+    save_png(&diff.image, &diff_path)?;
+    save_png(image, &path.with_extension("actual.png"))?;
+
+    Err(SnapshotError::Mismatch {
+        differing_pixels: diff.differing_pixels,
+        max_difference: diff.max_difference,
+        diff_path,
+    })
+}
+
+/// Writes an 8-bit rgba image as png
+fn save_png(image: &Image, path: &Path) -> Result<(), SnapshotError> {
+    let encode_error = |e: &dyn std::fmt::Display| SnapshotError::Encode(e.to_string());
+    image
+        .clone()
+        .try_into_dynamic()
+        .map_err(|e| encode_error(&e))?
+        .save(path)
+        .map_err(|e| encode_error(&e))
+}
+
+fn image_size(image: &Image) -> UVec2 {
+    let size = image.texture_descriptor.size;
+    UVec2::new(size.width, size.height)
+}
+
+fn rgba8_image(size: UVec2, data: Vec<u8>) -> Image {
+    Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
@@ -0,0 +1,395 @@
+//! Golden-image tests of the gallery shapes, using the cpu versions of the sdfs.
+//!
+//! The cpu versions are checked against the wgsl sdfs, evaluated on the gpu, so the snapshots
+//! also catch regressions in the shaders.
+//!
+//! Run with `SMUD_UPDATE_SNAPSHOTS=1` to accept changes to the reference images.
+
+mod common;
+
+use std::path::Path;
+
+use wgpu::util::DeviceExt;
+
+use bevy::prelude::*;
+use bevy_smud::{sdf, *};
+use common::snapshot::check_snapshot;
+use naga_oil::compose::{ComposableModuleDescriptor, Composer, NagaModuleDescriptor};
+
+type Sdf = fn(Vec2) -> f32;
+
+/// Rust ports of the shapes in `assets/gallery`
+const GALLERY: &[(&str, Sdf)] = &[
+    ("blobby_cross", |p| {
+        sdf::sd_blobby_cross(p / 20., 0.7) * 20. - 4.
+    }),
+    ("box", |p| sdf::sd_box(p, Vec2::new(30., 20.))),
+    ("circle", |p| sdf::sd_circle(p, 25.)),
+    ("donut", |p| sdf::sd_circle(p, 18.).abs() - 3.),
+    ("egg", |p| sdf::sd_egg(p, 25., 10.)),
+    ("ellipse", |p| sdf::sd_ellipse(p, 25., 15.)),
+    ("heart", |p| {
+        sdf::sd_heart(p / 40. - Vec2::new(0., -0.5)) * 40.
+    }),
+    ("hexagon", |p| sdf::sd_hexagon(p, 20.)),
+    ("horseshoe", |p| {
+        sdf::sd_horseshoe(p, sdf::sin_cos(0.4), 17., Vec2::new(6., 4.))
+    }),
+    ("moon", |p| sdf::sd_moon(p, 10., 25., 20.)),
+    ("pie", |p| sdf::sd_pie(p, sdf::sin_cos(0.8), 25.)),
+    ("rounded_x", |p| sdf::sd_rounded_x(p, 30., 4.)),
+    ("segment", |p| {
+        sdf::sd_segment(p, Vec2::splat(-13.), Vec2::splat(13.)) - 3.
+    }),
+    ("stairs", |p| {
+        sdf::sd_stairs((p - Vec2::splat(-20.)) / 5., Vec2::ONE, 8.) * 5.
+    }),
+    ("star_4", |p| sdf::sd_star(p * 0.5, 10., 4, 3.)),
+    ("star_5", |p| sdf::sd_star_5(p, 10., 2.)),
+    ("triangle", |p| sdf::sd_equilateral_triangle(p, 20.)),
+    ("vesica", |p| sdf::sd_vesica(p, 30., 15.)),
+];
+
+const TOLERANCE: u8 = 2;
+
+fn snapshot_path(name: &str) -> String {
+    format!("{}/tests/snapshots/{name}.png", env!("CARGO_MANIFEST_DIR"))
+}
+
+fn gallery_handle(index: usize) -> Handle<Shader> {
+    Handle::weak_from_u128(0x5eed_0000 + index as u128)
+}
+
+fn gallery_rasterizer(width: u32, height: u32) -> SmudRasterizer {
+    let mut rasterizer = SmudRasterizer::new(width, height);
+    for (i, (_, sdf)) in GALLERY.iter().enumerate() {
+        rasterizer.register_sdf(&gallery_handle(i), *sdf);
+    }
+    rasterizer
+}
+
+#[test]
+fn gallery_snapshots() {
+    let rasterizer = gallery_rasterizer(64, 64);
+
+    let failures: Vec<_> = GALLERY
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (name, _))| {
+            let shape = SmudShape {
+                color: Color::rgb(0.36, 0.41, 0.45),
+                sdf: gallery_handle(i),
+                fill: SIMPLE_FILL_HANDLE,
+                frame: Frame::Quad(32.),
+                ..default()
+            };
+            let image = rasterizer
+                .render([(&shape, &GlobalTransform::IDENTITY)])
+                .unwrap();
+            check_snapshot(&image, snapshot_path(&format!("gallery/{name}")), TOLERANCE)
+                .err()
+                .map(|e| format!("{name}: {e}"))
+        })
+        .collect();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn fills_and_transforms_snapshot() {
+    let mut rasterizer = gallery_rasterizer(128, 96);
+    rasterizer.clear_color = Color::rgb(0.7, 0.8, 0.7);
+    rasterizer.zoom = 0.5;
+
+    let fills = [
+        SIMPLE_FILL_HANDLE,
+        DEFAULT_FILL_HANDLE,
+        Handle::weak_from_u128(0x5eed_f111),
+    ];
+    rasterizer.register_fill(&fills[2], CpuFill::Outline);
+
+    let shapes: Vec<_> = fills
+        .iter()
+        .enumerate()
+        .map(|(i, fill)| {
+            let shape = SmudShape {
+                color: Color::rgba(0.9 - i as f32 * 0.3, 0.2 + i as f32 * 0.3, 0.5, 0.8),
+                sdf: gallery_handle(i * 6),
+                fill: fill.clone(),
+                frame: Frame::Quad(50.),
+                ..default()
+            };
+            let transform = Transform::from_xyz(i as f32 * 70. - 70., 0., i as f32)
+                .with_rotation(Quat::from_rotation_z(i as f32 * 0.5))
+                .with_scale(Vec3::splat(1. + i as f32 * 0.25));
+            (shape, GlobalTransform::from(transform))
+        })
+        .collect();
+
+    let image = rasterizer
+        .render(shapes.iter().map(|(shape, transform)| (shape, transform)))
+        .unwrap();
+    check_snapshot(&image, snapshot_path("fills_and_transforms"), TOLERANCE).unwrap();
+}
+
+#[test]
+fn anchors_move_shapes() {
+    let rasterizer = gallery_rasterizer(128, 128);
+    let alpha = |image: &Image, world: Vec2| {
+        let pixel = Vec2::new(64. + world.x, 64. - world.y).as_uvec2();
+        image.data[((pixel.y * 128 + pixel.x) * 4 + 3) as usize]
+    };
+
+    for (anchor, center) in [
+        (Anchor::Center, Vec2::ZERO),
+        (Anchor::BottomLeft, Vec2::new(32., 32.)),
+        (Anchor::TopCenter, Vec2::new(0., -32.)),
+        (Anchor::Custom(Vec2::new(0.5, 0.25)), Vec2::new(-32., -16.)),
+    ] {
+        let shape = SmudShape {
+            sdf: gallery_handle(1),
+            fill: SIMPLE_FILL_HANDLE,
+            frame: Frame::Quad(32.),
+            anchor,
+            ..default()
+        };
+        let image = rasterizer
+            .render([(&shape, &GlobalTransform::IDENTITY)])
+            .unwrap();
+        // The box has a half size of 30x20
+        assert_eq!(alpha(&image, center), 255, "{anchor:?}");
+        assert_eq!(
+            alpha(&image, center + Vec2::new(25., 15.)),
+            255,
+            "{anchor:?}"
+        );
+        assert_eq!(alpha(&image, center + Vec2::new(35., 0.)), 0, "{anchor:?}");
+        assert_eq!(alpha(&image, center - Vec2::new(0., 25.)), 0, "{anchor:?}");
+    }
+}
+
+#[test]
+fn gallery_is_complete() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/gallery");
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        assert!(
+            GALLERY.iter().any(|(n, _)| *n == name),
+            "{name} is missing a cpu version in tests/gallery.rs"
+        );
+    }
+}
+
+/// The snapshots only render the Rust ports, so check that they agree with the wgsl sdfs.
+///
+/// Skipped when there is no gpu adapter, not even a software one.
+#[test]
+fn gallery_shaders_match_rust_ports() {
+    let Some(gpu) = Gpu::new() else {
+        eprintln!("no gpu adapter, skipping");
+        return;
+    };
+
+    let samples: Vec<Vec2> = (-20..=20)
+        .flat_map(|y| (-20..=20).map(move |x| Vec2::new(x as f32, y as f32) * 3. + 0.5))
+        .collect();
+
+    let mut failures = Vec::new();
+    for (name, sdf) in GALLERY {
+        let distances = gpu.evaluate_sdf(name, &samples);
+        let worst = samples
+            .iter()
+            .zip(distances)
+            .map(|(p, gpu_d)| (*p, sdf(*p), gpu_d))
+            .max_by(|a, b| (a.1 - a.2).abs().total_cmp(&(b.1 - b.2).abs()))
+            .unwrap();
+        let (p, cpu_d, gpu_d) = worst;
+        if (cpu_d - gpu_d).abs() > SDF_TOLERANCE * cpu_d.abs().max(1.) {
+            failures.push(format!(
+                "{name}: {cpu_d} on the cpu, {gpu_d} on the gpu at {p}"
+            ));
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+/// Relative difference allowed between the cpu and gpu versions of the sdfs
+const SDF_TOLERANCE: f32 = 1e-3;
+
+/// Evaluates gallery sdfs with a compute shader
+struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+}
+
+impl Gpu {
+    fn new() -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..default()
+        });
+        let adapter = bevy::tasks::block_on(instance.request_adapter(&default()))?;
+        if !adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+        {
+            return None;
+        }
+        let (device, queue) = bevy::tasks::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                limits: wgpu::Limits::downlevel_defaults(),
+                ..default()
+            },
+            None,
+        ))
+        .ok()?;
+        Some(Self { device, queue })
+    }
+
+    fn evaluate_sdf(&self, name: &str, samples: &[Vec2]) -> Vec<f32> {
+        let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+        let mut composer = Composer::default();
+        for file in [
+            "instance.wgsl",
+            "smud.wgsl",
+            &format!("gallery/{name}.wgsl"),
+        ] {
+            let source = std::fs::read_to_string(assets.join(file)).unwrap();
+            add_module(&mut composer, &source, file);
+        }
+        let entry_point = format!(
+            "#import smud::gallery::{name} as shape\n\
+            @group(0) @binding(0) var<storage, read> samples: array<vec2<f32>>;\n\
+            @group(0) @binding(1) var<storage, read_write> distances: array<f32>;\n\
+            @compute @workgroup_size(64)\n\
+            fn main(@builtin(global_invocation_id) id: vec3<u32>) {{\n\
+                if (id.x < arrayLength(&samples)) {{\n\
+                    distances[id.x] = shape::sdf(samples[id.x]);\n\
+                }}\n\
+            }}\n"
+        );
+        let module = composer
+            .make_naga_module(NagaModuleDescriptor {
+                source: &entry_point,
+                file_path: "entry_point.wgsl",
+                ..default()
+            })
+            .unwrap_or_else(|e| panic!("{name}: {}", e.emit_to_string(&composer)));
+
+        let shader = self
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(name),
+                source: wgpu::ShaderSource::Naga(std::borrow::Cow::Owned(module)),
+            });
+        let pipeline = self
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(name),
+                layout: None,
+                module: &shader,
+                entry_point: "main",
+            });
+
+        let size = (samples.len() * std::mem::size_of::<f32>()) as u64;
+        let samples = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("samples"),
+                contents: bytemuck::cast_slice(
+                    &samples.iter().map(Vec2::to_array).collect::<Vec<_>>(),
+                ),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+        let buffer = |label, usage| {
+            self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+        let distances = buffer(
+            "distances",
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        );
+        let readback = buffer(
+            "readback",
+            wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        );
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: samples.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: distances.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = self.device.create_command_encoder(&default());
+        {
+            let mut pass = encoder.begin_compute_pass(&default());
+            pass.set_pipeline(&pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups((size as u32 / 4).div_ceil(64), 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&distances, 0, &readback, 0, size);
+        self.queue.submit([encoder.finish()]);
+
+        let slice = readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |result| result.unwrap());
+        self.device.poll(wgpu::Maintain::Wait);
+        let distances = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+        distances
+    }
+}
+
+/// Catches changes to `smud.wgsl` that break the gallery shaders, which the cpu versions can't
+#[test]
+fn gallery_shaders_compose() {
+    let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+    let mut composer = Composer::default();
+    for file in ["instance.wgsl", "smud.wgsl"] {
+        let source = std::fs::read_to_string(assets.join(file)).unwrap();
+        add_module(&mut composer, &source, file);
+    }
+
+    for (name, _) in GALLERY {
+        let file = format!("gallery/{name}.wgsl");
+        let source = std::fs::read_to_string(assets.join(&file)).unwrap();
+        add_module(&mut composer, &source, &file);
+
+        let entry_point = format!(
+            "#import smud::gallery::{name} as shape\n\
+            @fragment\n\
+            fn fragment(@location(0) p: vec2<f32>) -> @location(0) vec4<f32> {{\n\
+                return vec4<f32>(shape::sdf(p));\n\
+            }}\n"
+        );
+        if let Err(e) = composer.make_naga_module(NagaModuleDescriptor {
+            source: &entry_point,
+            file_path: "entry_point.wgsl",
+            ..default()
+        }) {
+            panic!("{name}: {}", e.emit_to_string(&composer));
+        }
+    }
+}
+
+fn add_module(composer: &mut Composer, source: &str, file_path: &str) {
+    if let Err(e) = composer.add_composable_module(ComposableModuleDescriptor {
+        source,
+        file_path,
+        ..default()
+    }) {
+        panic!("{}", e.emit_to_string(composer));
+    }
+}
@@ -0,0 +1,58 @@
+//! The shader modules generated from the instance data structs.
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{ShaderImport, Source},
+};
+use bevy_smud::*;
+use naga_oil::compose::{Composer, NagaModuleDescriptor, ShaderDefValue};
+
+/// The instance modules generated from the Rust structs are valid when reading instances from
+/// vertex buffers, storage buffers, and when writing them from compute shaders
+#[test]
+fn instance_modules_compose() {
+    let modules = instance_modules();
+    assert_eq!(modules.len(), 2);
+
+    let variants = [
+        vec![],
+        vec![
+            ("SMUD_STORAGE_INSTANCES", ShaderDefValue::Bool(true)),
+            ("SMUD_INSTANCE_GROUP", ShaderDefValue::UInt(2)),
+        ],
+        vec![("SMUD_INSTANCE_WRITER", ShaderDefValue::Bool(true))],
+    ];
+    for (import_path, source) in &modules {
+        for defs in &variants {
+            let mut composer = Composer::default();
+            if let Err(e) = composer.make_naga_module(NagaModuleDescriptor {
+                source,
+                file_path: import_path,
+                shader_defs: defs.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+                ..default()
+            }) {
+                panic!("{import_path}: {}", e.emit_to_string(&composer));
+            }
+        }
+    }
+}
+
+/// The shader modules the plugin generates for its instance data, by import path
+fn instance_modules() -> Vec<(String, String)> {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Shader>()
+        .add_plugins(SmudPlugin);
+    let shaders = app.world.resource::<Assets<Shader>>();
+    shaders
+        .iter()
+        .filter_map(|(_, shader)| match (&shader.import_path, &shader.source) {
+            (ShaderImport::Custom(path), Source::Wgsl(source))
+                if path.starts_with("smud::shape_instance") =>
+            {
+                Some((path.clone(), source.to_string()))
+            }
+            _ => None,
+        })
+        .collect()
+}
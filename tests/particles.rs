@@ -0,0 +1,58 @@
+//! The particle compute shader, which is only compiled when particles are spawned.
+
+use std::{collections::HashMap, path::Path};
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{ShaderImport, Source},
+};
+use bevy_smud::*;
+use naga_oil::compose::{
+    ComposableModuleDescriptor, Composer, NagaModuleDescriptor, ShaderDefValue,
+};
+
+#[test]
+fn particles_shader_composes() {
+    let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+    let source = std::fs::read_to_string(assets.join("particles.wgsl")).unwrap();
+    let mut composer = Composer::default();
+    let instance_defs: HashMap<_, _> =
+        [("SMUD_INSTANCE_WRITER".into(), ShaderDefValue::Bool(true))].into();
+    if let Err(e) = composer.add_composable_module(ComposableModuleDescriptor {
+        source: &shape_instance_module(),
+        file_path: "smud::shape_instance",
+        shader_defs: instance_defs.clone(),
+        ..default()
+    }) {
+        panic!("{}", e.emit_to_string(&composer));
+    }
+    if let Err(e) = composer.make_naga_module(NagaModuleDescriptor {
+        source: &source,
+        file_path: "particles.wgsl",
+        shader_defs: instance_defs,
+        ..default()
+    }) {
+        panic!("{}", e.emit_to_string(&composer));
+    }
+}
+
+/// The instance module the plugin generates from `ShapeVertex`, which the particles write
+fn shape_instance_module() -> String {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Shader>()
+        .add_plugins(SmudPlugin);
+    let shaders = app.world.resource::<Assets<Shader>>();
+    let source = shaders
+        .iter()
+        .find_map(|(_, shader)| match (&shader.import_path, &shader.source) {
+            (ShaderImport::Custom(path), Source::Wgsl(source))
+                if path == "smud::shape_instance" =>
+            {
+                Some(source.to_string())
+            }
+            _ => None,
+        })
+        .unwrap();
+    source
+}
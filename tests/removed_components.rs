@@ -0,0 +1,70 @@
+//! Removing components that change how a shape is drawn has to upload its instance data again,
+//! which change detection alone doesn't catch.
+
+use bevy::prelude::*;
+use bevy_smud::prelude::*;
+
+/// Whether the shape was marked changed since the last frame, recorded every frame
+#[derive(Resource, Default)]
+struct Changed(Vec<bool>);
+
+fn record_changes(shapes: Query<Ref<SmudShape>>, mut changed: ResMut<Changed>) {
+    changed
+        .0
+        .push(shapes.iter().any(|shape| shape.is_changed()));
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Shader>()
+        .init_asset::<Image>()
+        .add_plugins(SmudPlugin)
+        .init_resource::<Changed>()
+        // Runs first, so it sees what was marked at the end of the previous frame
+        .add_systems(First, record_changes);
+    app
+}
+
+/// Removes a component from a shape, and checks that the shape is marked changed
+fn check_removal<T: Component>(component: T) {
+    let mut app = app();
+    let entity = app
+        .world
+        .spawn((ShapeBundle::default(), component))
+        .with_children(|parent| {
+            parent.spawn(ShapeBundle::default());
+        })
+        .id();
+    // Spawning is a change, which is over after these
+    app.update();
+    app.update();
+    app.update();
+
+    app.world.entity_mut(entity).remove::<T>();
+    app.update();
+    app.update();
+
+    let changed = &app.world.resource::<Changed>().0;
+    assert_eq!(
+        changed[changed.len() - 3..],
+        [false, false, true],
+        "{}",
+        std::any::type_name::<T>()
+    );
+}
+
+#[test]
+fn removing_polygons_marks_shapes_changed() {
+    check_removal(SmudPolygon::new([Vec2::ZERO, Vec2::X, Vec2::Y]));
+}
+
+#[test]
+fn removing_polylines_marks_shapes_changed() {
+    check_removal(SmudPolyline::new([Vec2::ZERO, Vec2::X], 2.));
+}
+
+#[test]
+fn removing_csgs_marks_shapes_changed() {
+    check_removal(SmudCsg::default());
+}
@@ -0,0 +1,123 @@
+//! Shapes built with [`SmudSdf`], on the cpu and as wgsl
+
+use std::path::Path;
+
+use bevy::{prelude::*, render::render_resource::Source};
+use bevy_smud::*;
+use naga_oil::compose::{ComposableModuleDescriptor, Composer, NagaModuleDescriptor};
+
+/// Uses every primitive and operator
+fn everything() -> SmudSdf {
+    let primitives = [
+        SmudSdf::circle(10.),
+        SmudSdf::rect(Vec2::new(10., 5.)),
+        SmudSdf::RoundedRect {
+            half_size: Vec2::new(10., 5.),
+            radii: Vec4::new(1., 2., 3., 4.),
+        },
+        SmudSdf::ellipse(Vec2::new(10., 6.)),
+        SmudSdf::line(Vec2::ZERO, Vec2::new(10., 5.), 2.),
+        SmudSdf::triangle([Vec2::ZERO, Vec2::X * 10., Vec2::Y * 10.]),
+        SmudSdf::equilateral_triangle(8.),
+        SmudSdf::hexagon(8.),
+        SmudSdf::star_5(10., 0.5).annular(1.),
+    ];
+    let ops = [
+        CsgOp::Union,
+        CsgOp::Subtract,
+        CsgOp::Intersect,
+        CsgOp::SmoothUnion(3.),
+        CsgOp::SmoothSubtract(3.),
+        CsgOp::SmoothIntersect(3.),
+    ];
+    primitives
+        .into_iter()
+        .enumerate()
+        .fold(SmudSdf::circle(1.), |shape, (i, primitive)| {
+            let primitive = primitive
+                .rotate(i as f32)
+                .translate(Vec2::new(i as f32 * 5., 0.))
+                .scale(1.5);
+            shape.csg(ops[i % ops.len()], primitive)
+        })
+}
+
+#[test]
+fn wgsl_composes() {
+    let mut shaders = Assets::<Shader>::default();
+    let handle = everything().shader(&mut shaders);
+    let shader = shaders.get(&handle).unwrap();
+    let Source::Wgsl(source) = &shader.source else {
+        unreachable!()
+    };
+
+    let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+    let mut composer = Composer::default();
+    for file in ["instance.wgsl", "smud.wgsl"] {
+        let source = std::fs::read_to_string(assets.join(file)).unwrap();
+        add_module(&mut composer, &source, file);
+    }
+    add_module(&mut composer, source, "everything.wgsl");
+
+    let entry_point = format!(
+        "#import {} as shape\n\
+        @fragment\n\
+        fn fragment(@location(0) p: vec2<f32>) -> @location(0) vec4<f32> {{\n\
+            return vec4<f32>(shape::sdf(p));\n\
+        }}\n",
+        shader.import_path().module_name()
+    );
+    if let Err(e) = composer.make_naga_module(NagaModuleDescriptor {
+        source: &entry_point,
+        file_path: "entry_point.wgsl",
+        ..default()
+    }) {
+        panic!("{}", e.emit_to_string(&composer));
+    }
+}
+
+#[test]
+fn distances() {
+    let circle = SmudSdf::circle(10.).translate(Vec2::new(20., 0.));
+    assert_eq!(circle.distance(Vec2::new(20., 0.)), -10.);
+    assert_eq!(circle.distance(Vec2::ZERO), 10.);
+
+    let rect = SmudSdf::rect(Vec2::new(10., 2.)).rotate(std::f32::consts::FRAC_PI_2);
+    assert!((rect.distance(Vec2::new(0., 10.))).abs() < 1e-5);
+    assert!((rect.distance(Vec2::new(2., 0.))).abs() < 1e-5);
+
+    let ring = SmudSdf::circle(10.).subtract(SmudSdf::circle(5.));
+    assert!(ring.distance(Vec2::ZERO) > 0.);
+    assert!(ring.distance(Vec2::new(7., 0.)) < 0.);
+    assert_eq!(SmudSdf::circle(10.).scale(2.).distance(Vec2::ZERO), -20.);
+}
+
+#[test]
+fn bounds_contain_shape() {
+    let shape = everything();
+    let bounds = shape.bounds();
+    let Frame::Quad(frame) = shape.frame();
+    assert!(bounds.min.min_element() >= -frame && bounds.max.max_element() <= frame);
+
+    let outside = bounds.inset(1.);
+    for i in 0..=100 {
+        for (x, y) in [(i, 0), (i, 100), (0, i), (100, i)] {
+            let t = Vec2::new(x as f32, y as f32) / 100.;
+            let p = outside.min + outside.size() * t;
+            assert!(shape.distance(p) > 0., "{p} is inside the shape");
+        }
+    }
+}
+
+fn add_module(composer: &mut Composer, source: &str, file_path: &str) {
+    let result = composer
+        .add_composable_module(ComposableModuleDescriptor {
+            source,
+            file_path,
+            ..default()
+        })
+        .map(|_| ());
+    if let Err(e) = result {
+        panic!("{}", e.emit_to_string(composer));
+    }
+}
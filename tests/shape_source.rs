@@ -0,0 +1,85 @@
+//! Round-trips of [`SmudShapeSource`] through serde and reflection, which is what scenes use
+
+use bevy::{
+    prelude::*,
+    reflect::{
+        serde::{ReflectSerializer, UntypedReflectDeserializer},
+        TypeRegistry,
+    },
+};
+use bevy_smud::{prelude::*, SmudShaderSource};
+use serde::de::DeserializeSeed;
+
+fn sources() -> Vec<SmudShapeSource> {
+    vec![
+        SmudShapeSource::default(),
+        SmudShapeSource {
+            color: Color::rgba(0.1, 0.2, 0.3, 0.4),
+            sdf: SmudShaderSource::Path("gallery/circle.wgsl".into()),
+            fill: SmudShaderSource::Body("return color;".into()),
+            frame: Frame::Quad(30.),
+            anchor: Anchor::TopLeft,
+        },
+        SmudShapeSource {
+            color: Color::TEAL,
+            sdf: SmudShaderSource::Expr("smud::sd_circle(p, 50.)".into()),
+            fill: SmudShaderSource::Wgsl("#define_import_path a\nfn fill() {}".into()),
+            frame: Frame::Quad(55.),
+            anchor: Anchor::Custom(Vec2::new(0.25, -0.5)),
+        },
+    ]
+}
+
+#[test]
+fn serde_round_trip() {
+    for source in sources() {
+        let ron = ron::to_string(&source).unwrap();
+        let deserialized: SmudShapeSource = ron::from_str(&ron).unwrap();
+        assert_eq!(source, deserialized);
+    }
+}
+
+#[test]
+fn reflect_round_trip() {
+    let mut registry = TypeRegistry::default();
+    registry.register::<SmudShapeSource>();
+
+    for source in sources() {
+        let ron = ron::to_string(&ReflectSerializer::new(&source, &registry)).unwrap();
+        let mut deserializer = ron::Deserializer::from_str(&ron).unwrap();
+        let reflected = UntypedReflectDeserializer::new(&registry)
+            .deserialize(&mut deserializer)
+            .unwrap();
+        let deserialized = SmudShapeSource::from_reflect(reflected.as_ref()).unwrap();
+        assert_eq!(source, deserialized);
+    }
+}
+
+#[test]
+fn from_shape() {
+    let mut shaders = Assets::<Shader>::default();
+    let sdf = shaders.add_sdf_expr("smud::sd_circle(p, 50.)");
+    let shape = SmudShape {
+        sdf: sdf.clone(),
+        fill: SIMPLE_FILL_HANDLE,
+        ..default()
+    };
+
+    let source = SmudShapeSource::from_shape(&shape, &shaders).unwrap();
+
+    let SmudShaderSource::Wgsl(wgsl) = &source.sdf else {
+        panic!("expected wgsl source, got {:?}", source.sdf);
+    };
+    assert!(wgsl.contains("smud::sd_circle(p, 50.)"));
+    let SmudShaderSource::Internal(uuid) = source.fill else {
+        panic!("expected internal shader, got {:?}", source.fill);
+    };
+    assert_eq!(
+        Handle::<Shader>::weak_from_u128(uuid.as_u128()),
+        SIMPLE_FILL_HANDLE
+    );
+    assert_eq!(source.color, shape.color);
+
+    shaders.remove(&sdf);
+    assert_eq!(SmudShapeSource::from_shape(&shape, &shaders), None);
+}
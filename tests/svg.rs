@@ -0,0 +1,197 @@
+//! Conversion of svg documents into smud shapes
+
+use std::path::Path;
+
+use bevy::{prelude::*, render::render_resource::Source};
+use bevy_smud::*;
+use naga_oil::compose::{ComposableModuleDescriptor, Composer, NagaModuleDescriptor};
+
+const ICON: &str = include_str!("../assets/icons/bevy_smud.svg");
+
+#[test]
+fn icon_shapes() {
+    let mut shaders = Assets::<Shader>::default();
+    let svg = SmudSvg::parse(ICON, &mut shaders).unwrap();
+
+    assert_eq!(svg.size, Vec2::splat(64.));
+    let kinds: Vec<&str> = svg
+        .shapes
+        .iter()
+        .map(|shape| match shape.geometry {
+            SvgGeometry::Sdf => "sdf",
+            SvgGeometry::Polygon(_) => "polygon",
+            SvgGeometry::Path(_) => "path",
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            // rect fill and stroke, ellipse, circle fill and stroke
+            "sdf", "sdf", "sdf", "sdf", "sdf",
+            // ring with a hole, triangle, quadratic curve, polyline
+            "polygon", "polygon", "path", "path",
+        ]
+    );
+
+    // The rect covers the whole document, centered on the origin
+    assert_eq!(svg.shapes[0].transform.translation, Vec3::ZERO);
+    // y points up
+    let circle = svg.shapes[3].transform.translation;
+    assert!(circle.x > 0. && circle.y > 0., "{circle}");
+
+    // Both rings are in one polygon, so it doesn't need a shader of its own
+    let SvgGeometry::Polygon(ring) = &svg.shapes[5].geometry else {
+        unreachable!()
+    };
+    assert_eq!(ring.ring_lengths.len(), 2);
+    assert_eq!(ring.ring_lengths.iter().sum::<usize>(), ring.points.len());
+
+    let SvgGeometry::Polygon(triangle) = &svg.shapes[6].geometry else {
+        unreachable!()
+    };
+    assert_eq!(triangle.points.len(), 3);
+    assert!(triangle.ring_lengths.is_empty());
+    assert!((svg.shapes[6].shape.color.a() - 0.8).abs() < 1e-6);
+
+    let SvgGeometry::Path(curve) = &svg.shapes[7].geometry else {
+        unreachable!()
+    };
+    assert_eq!(curve.cap, StrokeCap::Round);
+    assert_eq!(curve.commands.len(), 3);
+}
+
+#[test]
+fn generated_sdfs_compose() {
+    let mut shaders = Assets::<Shader>::default();
+    let svg = SmudSvg::parse(ICON, &mut shaders).unwrap();
+
+    let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+    let mut composer = Composer::default();
+    for file in ["instance.wgsl", "smud.wgsl"] {
+        let source = std::fs::read_to_string(assets.join(file)).unwrap();
+        add_module(&mut composer, &source, file);
+    }
+
+    for (i, shape) in svg.shapes.iter().enumerate() {
+        if !matches!(shape.geometry, SvgGeometry::Sdf) {
+            continue;
+        }
+        let shader = shaders.get(&shape.shape.sdf).unwrap();
+        let Source::Wgsl(source) = &shader.source else {
+            unreachable!()
+        };
+        add_module(&mut composer, source, &format!("shape{i}.wgsl"));
+
+        let entry_point = format!(
+            "#import {} as shape\n\
+            @fragment\n\
+            fn fragment(@location(0) p: vec2<f32>) -> @location(0) vec4<f32> {{\n\
+                return vec4<f32>(shape::sdf(p));\n\
+            }}\n",
+            shader.import_path().module_name()
+        );
+        if let Err(e) = composer.make_naga_module(NagaModuleDescriptor {
+            source: &entry_point,
+            file_path: "entry_point.wgsl",
+            ..default()
+        }) {
+            panic!("shape {i}: {}", e.emit_to_string(&composer));
+        }
+    }
+}
+
+#[test]
+fn invalid_attributes() {
+    let mut shaders = Assets::<Shader>::default();
+    let error = SmudSvg::parse(r#"<svg><circle r="big"/></svg>"#, &mut shaders).unwrap_err();
+    assert!(matches!(error, SmudSvgError::Attribute { .. }), "{error}");
+    assert!(SmudSvg::parse("<svg>", &mut shaders).is_err());
+}
+
+fn add_module(composer: &mut Composer, source: &str, file_path: &str) {
+    let result = composer
+        .add_composable_module(ComposableModuleDescriptor {
+            source,
+            file_path,
+            ..default()
+        })
+        .map(|_| ());
+    if let Err(e) = result {
+        panic!("{}", e.emit_to_string(composer));
+    }
+}
+
+#[test]
+fn export_primitives() {
+    let sdf = SmudSdf::circle(10.)
+        .union(SmudSdf::rounded_rect(Vec2::new(20., 5.), 2.).translate(Vec2::new(0., 10.)));
+    let svg = sdf.to_svg(Color::rgb_u8(255, 128, 0));
+
+    assert!(svg.contains(r#"<circle r="10"/>"#), "{svg}");
+    assert!(svg.contains(r#"rx="2""#), "{svg}");
+    assert!(svg.contains("#ff8000"), "{svg}");
+    assert!(!svg.contains("<path"), "{svg}");
+
+    // Overlapping elements of a translucent union are composited as a whole
+    let translucent = sdf.to_svg(Color::rgba(1., 0.5, 0., 0.5));
+    assert!(translucent.contains(r#" opacity="0.498""#), "{translucent}");
+    assert!(!translucent.contains("fill-opacity"), "{translucent}");
+
+    // Reimporting gives the same shapes back, with y pointing up
+    let mut shaders = Assets::<Shader>::default();
+    let imported = SmudSvg::parse(&svg, &mut shaders).unwrap();
+    assert_eq!(imported.shapes.len(), 2);
+    let rect = imported.shapes[1].transform.translation - imported.shapes[0].transform.translation;
+    assert!(rect.abs_diff_eq(Vec3::new(0., 10., 0.), 1e-3), "{rect}");
+}
+
+#[test]
+fn export_smooth_union_contour() {
+    let sdf = SmudSdf::circle(10.)
+        .smooth_union(SmudSdf::circle(8.).translate(Vec2::new(15., 0.)), 5.)
+        .subtract(SmudSdf::circle(3.));
+    let svg = sdf.to_svg(Color::BLACK);
+
+    let mut shaders = Assets::<Shader>::default();
+    let imported = SmudSvg::parse(&svg, &mut shaders).unwrap();
+    // The hole makes it a single polygon with two rings
+    let [shape] = &imported.shapes[..] else {
+        panic!("expected a single shape: {svg}");
+    };
+    let SvgGeometry::Polygon(polygon) = &shape.geometry else {
+        panic!("expected a polygon: {svg}");
+    };
+    assert_eq!(polygon.ring_lengths.len(), 2);
+
+    // The traced points lie on the edge of the shape
+    let step = sdf.bounds().size().max_element() / 128.;
+    let d = svg
+        .split(" d=\"")
+        .nth(1)
+        .unwrap()
+        .split('"')
+        .next()
+        .unwrap();
+    let numbers: Vec<f32> = d
+        .split(|c: char| c.is_ascii_alphabetic() || c == ' ')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().unwrap())
+        .collect();
+    assert!(numbers.len() > 100);
+    for point in numbers.chunks_exact(2) {
+        let p = Vec2::new(point[0], point[1]);
+        assert!(sdf.distance(p).abs() < step, "{p} is off the contour");
+    }
+}
+
+#[test]
+fn export_contour_in_tight_bounds() {
+    // A ring cut off by bounds that only cover its upper half
+    let sdf = SmudSdf::circle(10.).annular(2.);
+    let bounds = Rect::new(-15., 5., 15., 15.);
+    let path = sdf.svg_contour(bounds);
+
+    // Both arcs of the ring are left open instead of being closed along the bounds
+    assert_eq!(path.matches('M').count(), 2, "{path}");
+    assert!(!path.contains('Z'), "{path}");
+}
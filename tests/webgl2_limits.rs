@@ -0,0 +1,152 @@
+//! Rendering with the limits of WebGL2, which has no storage buffers or compute shaders,
+//! rendered headless.
+//!
+//! Skipped when there's no gpu adapter, a software one like llvmpipe is enough.
+
+use std::sync::{Arc, Mutex};
+
+use bevy::{
+    core_pipeline::core_2d::Transparent2d,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_phase::{PhaseItem, RenderPhase},
+        render_resource::{
+            CachedPipelineState, Extent3d, PipelineCache, PipelineCacheError, TextureDimension,
+            TextureFormat, TextureUsages,
+        },
+        settings::{WgpuLimits, WgpuSettings},
+        Render, RenderApp, RenderPlugin, RenderSet,
+    },
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
+use bevy_smud::prelude::*;
+
+/// The instances drawn with ready pipelines, and the errors of the pipelines that failed
+#[derive(Resource, Clone, Default)]
+struct Drawn(Arc<Mutex<(usize, Vec<String>)>>);
+
+fn record_drawn(
+    phases: Query<&RenderPhase<Transparent2d>>,
+    pipeline_cache: Res<PipelineCache>,
+    drawn: Res<Drawn>,
+) {
+    let mut drawn = drawn.0.lock().unwrap();
+    *drawn = default();
+    for item in phases.iter().flat_map(|phase| &phase.items) {
+        match pipeline_cache.get_render_pipeline_state(item.pipeline) {
+            CachedPipelineState::Ok(_) => drawn.0 += item.batch_range().len(),
+            // Shaders that are still loading are retried
+            CachedPipelineState::Queued
+            | CachedPipelineState::Err(
+                PipelineCacheError::ShaderNotLoaded(_)
+                | PipelineCacheError::ShaderImportNotYetAvailable,
+            ) => {}
+            CachedPipelineState::Err(error) => drawn.1.push(format!("{error:?}")),
+        }
+    }
+}
+
+fn has_gpu() -> bool {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+    bevy::tasks::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+        .is_some()
+}
+
+#[test]
+fn shapes_are_drawn_from_vertex_buffers_without_storage_buffers() {
+    if !has_gpu() {
+        eprintln!("no gpu adapter, skipping");
+        return;
+    }
+
+    let drawn = Drawn::default();
+    let mut app = App::new();
+    app.add_plugins((
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            })
+            .set(RenderPlugin {
+                render_creation: WgpuSettings {
+                    constrained_limits: Some(WgpuLimits::downlevel_webgl2_defaults()),
+                    ..default()
+                }
+                .into(),
+            })
+            .disable::<WinitPlugin>(),
+        SmudPlugin,
+    ));
+    // Normally done by `App::run`, creates the render pipelines
+    app.finish();
+    app.cleanup();
+    app.sub_app_mut(RenderApp)
+        .insert_resource(drawn.clone())
+        .add_systems(
+            Render,
+            record_drawn
+                .after(RenderSet::Render)
+                .before(RenderSet::Cleanup),
+        );
+
+    let world = &mut app.world;
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: 64,
+            height: 64,
+            ..default()
+        },
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    image.texture_descriptor.usage |= TextureUsages::RENDER_ATTACHMENT;
+    let image = world.resource_mut::<Assets<Image>>().add(image);
+    world.spawn(Camera2dBundle {
+        camera: Camera {
+            target: RenderTarget::Image(image),
+            ..default()
+        },
+        ..default()
+    });
+
+    let sdf = world
+        .resource_mut::<Assets<Shader>>()
+        .add_sdf_expr("smud::sd_circle(p, 20.)");
+    let shape = SmudShape {
+        color: Color::WHITE,
+        sdf,
+        frame: Frame::Quad(25.),
+        ..default()
+    };
+    world.spawn(ShapeBundle {
+        shape: shape.clone(),
+        ..default()
+    });
+    // Polygons and particles need storage buffers, so they're skipped
+    world.spawn((
+        ShapeBundle {
+            shape: shape.clone(),
+            ..default()
+        },
+        SmudPolygon::new([Vec2::ZERO, Vec2::X * 10., Vec2::Y * 10.]),
+    ));
+    world.spawn((ShapeBundle { shape, ..default() }, SmudParticles::default()));
+
+    // Shaders load in the background, so wait for the circle to be drawn
+    for _ in 0..500 {
+        app.update();
+        let drawn = drawn.0.lock().unwrap();
+        assert!(drawn.1.is_empty(), "{:?}", drawn.1);
+        if drawn.0 > 0 {
+            assert_eq!(drawn.0, 1);
+            return;
+        }
+        drop(drawn);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    panic!("the circle was never drawn");
+}